@@ -300,6 +300,11 @@ impl OffscreenTexturePool {
         }
     }
 
+    /// Drop every pooled texture, e.g. after the color format changes.
+    pub fn clear(&mut self) {
+        self.available.clear();
+    }
+
     /// Acquire a texture matching the given dimensions and sample count, plus a depth/stencil
     /// attachment for render passes that write depth or stencil.
     pub fn acquire_with_depth(
@@ -722,11 +727,13 @@ pub(crate) fn compile_composite_pipeline(
 }
 
 /// Compile a fullscreen texture-sampling pipeline without stencil/depth usage.
-/// Used for capture downsampling before running the user effect shader.
+/// Used for capture downsampling before running the user effect shader, and with
+/// `blend` to composite an offscreen frame over a caller's render target.
 pub(crate) fn compile_texture_blit_pipeline(
     device: &wgpu::Device,
     format: wgpu::TextureFormat,
     input_bind_group_layout: &wgpu::BindGroupLayout,
+    blend: Option<wgpu::BlendState>,
 ) -> wgpu::RenderPipeline {
     let wgsl = build_composite_wgsl();
 
@@ -756,7 +763,7 @@ pub(crate) fn compile_texture_blit_pipeline(
             compilation_options: Default::default(),
             targets: &[Some(wgpu::ColorTargetState {
                 format,
-                blend: None,
+                blend,
                 write_mask: wgpu::ColorWrites::ALL,
             })],
         }),
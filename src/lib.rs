@@ -181,14 +181,25 @@ pub use gradient::types::{
     RadialGradientDesc, RadialGradientShape, RadialGradientSize, SpreadMode,
 };
 pub use renderer::{
-    types::DrawCommandError, MathRect, Renderer, RendererContext, RendererCreationError,
-    ShapeOverflow, TextureLayer,
+    types::{DrawCommandError, ReadbackError, RenderTargetError, RenderToTextureError},
+    ContextConfig, HeadlessConfig, MathRect, ReadbackAlpha, ReadbackFormat, ReadbackHandle,
+    ReadbackOptions, ReadbackRegion, Renderer, RendererConfig, RendererContext,
+    RendererCreationError, ShapeOverflow, TextureLayer, TiledRenderOptions,
 };
 pub use shape::*;
 pub use stroke::Stroke;
-pub use texture_manager::{premultiply_rgba8_srgb_inplace, TextureManager};
+pub use texture_manager::{
+    premultiply_rgba8_srgb_inplace, SamplerOptions, TextureAddressMode, TextureFilter,
+    TextureFormat, TextureManager, TextureManagerError, TextureMemoryReport, TextureMemoryUsage,
+    TextureOptions, YuvColorMatrix, YuvFormat, YuvOptions, YuvPlane, YuvRange,
+};
 pub use vertex::InstanceTransform as TransformInstance;
 
+#[cfg(feature = "image")]
+pub use texture_manager::DecodedImage;
+
+#[cfg(feature = "render_metrics")]
+pub use renderer::metrics::LayerCacheMetrics;
 #[cfg(feature = "render_metrics")]
 pub use renderer::metrics::PhaseTimings;
 #[cfg(feature = "render_metrics")]
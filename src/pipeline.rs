@@ -855,12 +855,35 @@ pub fn encode_copy_texture_to_buffer(
     width: u32,
     height: u32,
     padded_bytes_per_row: u32,
+) {
+    encode_copy_texture_region_to_buffer(
+        encoder,
+        texture,
+        buffer,
+        (0, 0),
+        (width, height),
+        padded_bytes_per_row,
+    );
+}
+
+/// Encode a copy of the `size` texels at `origin` from a texture to a buffer.
+pub fn encode_copy_texture_region_to_buffer(
+    encoder: &mut wgpu::CommandEncoder,
+    texture: &wgpu::Texture,
+    buffer: &wgpu::Buffer,
+    origin: (u32, u32),
+    (width, height): (u32, u32),
+    padded_bytes_per_row: u32,
 ) {
     encoder.copy_texture_to_buffer(
         wgpu::TexelCopyTextureInfo {
             texture,
             mip_level: 0,
-            origin: wgpu::Origin3d::ZERO,
+            origin: wgpu::Origin3d {
+                x: origin.0,
+                y: origin.1,
+                z: 0,
+            },
             aspect: wgpu::TextureAspect::All,
         },
         wgpu::TexelCopyBufferInfo {
@@ -924,7 +947,9 @@ pub struct ArgbParams {
     pub width: u32,
     pub height: u32,
     pub padded_bpr: u32,
-    pub _pad: u32,
+    /// Bit 0: texels are RGBA rather than BGRA ordered. Bit 1: texels are linear `f16` RGBA,
+    /// written out sRGB encoded. Bit 2: alpha is written as opaque.
+    pub flags: u32,
 }
 
 /// Create or update a uniform buffer for ArgbParams.
@@ -1197,6 +1222,100 @@ pub fn create_backdrop_stencil_keep_color_pipeline(
     })
 }
 
+/// Creates the pipeline that composites a soft-clipped subtree through its parent's geometry.
+///
+/// Uses stencil Equal + Keep so ancestor clips still apply, and samples the subtree texture
+/// bound at group 3 through the backdrop texture layout.
+pub fn create_soft_clip_composite_pipeline(
+    device: &Device,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+    uniform_bgl: &wgpu::BindGroupLayout,
+    texture_bgl_layer0: &wgpu::BindGroupLayout,
+    texture_bgl_layer1: &wgpu::BindGroupLayout,
+    backdrop_texture_bgl: &wgpu::BindGroupLayout,
+) -> RenderPipeline {
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("soft_clip_composite_shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("shaders/shader.wgsl").into()),
+    });
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("soft_clip_composite_pipeline_layout"),
+        bind_group_layouts: &[
+            uniform_bgl,
+            texture_bgl_layer0,
+            texture_bgl_layer1,
+            backdrop_texture_bgl,
+        ],
+        push_constant_ranges: &[],
+    });
+
+    let stencil_face = wgpu::StencilFaceState {
+        compare: wgpu::CompareFunction::Equal,
+        fail_op: wgpu::StencilOperation::Keep,
+        depth_fail_op: wgpu::StencilOperation::Keep,
+        pass_op: wgpu::StencilOperation::Keep,
+    };
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("soft_clip_composite_pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: Some("vs_main"),
+            compilation_options: Default::default(),
+            buffers: &[
+                CustomVertex::desc(),
+                InstanceTransform::desc(),
+                InstanceColor::desc(),
+                InstanceMetadata::desc(),
+            ],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: Some("fs_soft_clip_composite"),
+            compilation_options: Default::default(),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState {
+                    color: wgpu::BlendComponent {
+                        src_factor: wgpu::BlendFactor::One,
+                        dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                        operation: wgpu::BlendOperation::Add,
+                    },
+                    alpha: wgpu::BlendComponent {
+                        src_factor: wgpu::BlendFactor::One,
+                        dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                        operation: wgpu::BlendOperation::Add,
+                    },
+                }),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: wgpu::TextureFormat::Depth24PlusStencil8,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Always,
+            stencil: wgpu::StencilState {
+                front: stencil_face,
+                back: stencil_face,
+                read_mask: 0xff,
+                write_mask: 0x00,
+            },
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState {
+            count: sample_count,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+        cache: None,
+    })
+}
+
 pub fn create_gradient_stencil_keep_color_pipeline(
     device: &Device,
     format: wgpu::TextureFormat,
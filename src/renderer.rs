@@ -26,20 +26,30 @@ use crate::texture_manager::TextureManager;
 use crate::util::{to_logical, PoolManager};
 use crate::vertex::{CustomVertex, InstanceColor, InstanceMetadata, InstanceTransform};
 use crate::CachedShapeHandle;
+pub use config::{ContextConfig, HeadlessConfig, RendererConfig};
 pub use construction::RendererCreationError;
+pub use readback::{
+    ReadbackAlpha, ReadbackFormat, ReadbackHandle, ReadbackOptions, ReadbackRegion,
+};
+pub use tiled_export::TiledRenderOptions;
 
+mod config;
 mod construction;
 mod draw_queue;
 mod effects;
+mod layers;
 #[cfg(feature = "render_metrics")]
 pub mod metrics;
 mod passes;
+#[cfg(feature = "png")]
+mod png_export;
 mod preparation;
 mod readback;
 mod rect_utils;
 mod rendering;
 mod shape_effects;
 mod surface;
+mod tiled_export;
 mod traversal;
 pub(crate) mod types;
 
@@ -72,6 +82,10 @@ pub enum ShapeOverflow {
     Hidden,
     /// Descendants can render outside this shape, while still inheriting ancestor clips.
     Visible,
+    /// Descendants are clipped to this shape's horizontal extent and can overflow vertically.
+    ClipX,
+    /// Descendants are clipped to this shape's vertical extent and can overflow horizontally.
+    ClipY,
 }
 
 /// The renderer for the Grafo library. This is the main struct used to render shapes and images.
@@ -198,6 +212,12 @@ pub struct Renderer<'a> {
     rtb_cached_width: u32,
     rtb_cached_height: u32,
 
+    /// Idle readback buffers shared with in-flight [`ReadbackHandle`]s.
+    async_readback_buffers: readback::AsyncReadbackBuffers,
+    /// Set for headless renderers created with `transparent(false)`, whose readbacks report
+    /// every pixel as opaque.
+    opaque_readback: bool,
+
     /// Current MSAA sample count (1 = off, 4 = 4x, etc.)
     msaa_sample_count: u32,
 
@@ -222,6 +242,12 @@ pub struct Renderer<'a> {
     backdrop_effects: HashMap<usize, EffectInstance>,
     /// Per-node cached shape effect attachments, keyed by node_id.
     shape_effects: HashMap<usize, ShapeEffectInstance>,
+    /// Nodes queued with [`ShapeDrawCommandOptions::soft_clip`](crate::ShapeDrawCommandOptions::soft_clip).
+    soft_clip_nodes: Vec<usize>,
+    /// Nodes hinted with [`Renderer::cache_as_layer`].
+    layer_nodes: Vec<usize>,
+    /// Subtree rasters retained while referenced by consecutive rendered frames.
+    layer_cache: layers::LayerCache,
     /// Exact GPU results retained while referenced by consecutive rendered frames.
     shape_effect_cache: shape_effects::ShapeEffectResultCache,
     /// Rasterized shape masks retained while referenced by consecutive rendered frames.
@@ -243,6 +269,8 @@ pub struct Renderer<'a> {
     // ── Backdrop effect infrastructure ─────────────────────────────────
     /// Fullscreen sampling pipeline used to downsample a captured backdrop region.
     texture_blit_pipeline: Option<wgpu::RenderPipeline>,
+    /// Premultiplied-alpha blit layering an offscreen frame over a `render_into` target.
+    target_composite_pipeline: Option<wgpu::RenderPipeline>,
     /// Premultiplied-alpha pipeline for layering a transparent group prefix into a backdrop.
     backdrop_layer_composite_pipeline: Option<wgpu::RenderPipeline>,
     /// Bind group layout used by the group-prefix backdrop compositor.
@@ -255,6 +283,10 @@ pub struct Renderer<'a> {
     backdrop_color_pipeline: Option<wgpu::RenderPipeline>,
     /// Gradient color pipeline with stencil Keep for backdrop shapes.
     backdrop_color_gradient_pipeline: Option<wgpu::RenderPipeline>,
+    /// Coverage-weighted pipeline that composites soft-clipped subtrees through their parent.
+    soft_clip_composite_pipeline: Option<wgpu::RenderPipeline>,
+    /// Viewport-sized sampling uniform shared by all soft-clip composites.
+    soft_clip_material_params_buffer: Option<wgpu::Buffer>,
 
     /// Pipeline for rendering leaf nodes (no children) with stencil Equal + Keep.
     /// Avoids the redundant increment + decrement pair for childless shapes.
@@ -290,6 +322,10 @@ pub struct Renderer<'a> {
     /// Cache activity for shape effects during the most recently rendered frame.
     last_shape_effect_cache_metrics: self::metrics::ShapeEffectCacheMetrics,
 
+    #[cfg(feature = "render_metrics")]
+    /// Layer cache activity during the most recently rendered frame.
+    last_layer_cache_metrics: self::metrics::LayerCacheMetrics,
+
     /// Wall-clock CPU time spent inside the most recent `render_to_texture_view()` call.
     ///
     /// This measures CPU-side traversal planning, render/effect pass encoding,
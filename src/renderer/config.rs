@@ -0,0 +1,359 @@
+//! Builders for [`RendererContext`](super::RendererContext) and [`Renderer`](super::Renderer)
+//! creation options.
+
+use super::construction::RendererCreationError;
+
+/// How a [`RendererContext`](super::RendererContext) picks its instance, adapter, and device.
+#[derive(Clone, Debug)]
+pub struct ContextConfig {
+    /// Backends the adapter may come from.
+    pub backends: wgpu::Backends,
+    pub power_preference: wgpu::PowerPreference,
+    /// Only accept a software adapter, e.g. for CI machines without a GPU.
+    pub force_fallback_adapter: bool,
+    /// Case-insensitive substring the adapter's name must contain. `None` lets WGPU choose.
+    pub adapter_name: Option<String>,
+    /// Limits the device is requested with; the adapter must support them.
+    pub limits: wgpu::Limits,
+}
+
+impl Default for ContextConfig {
+    fn default() -> Self {
+        Self {
+            backends: wgpu::Backends::all(),
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            force_fallback_adapter: false,
+            adapter_name: None,
+            limits: wgpu::Limits::default(),
+        }
+    }
+}
+
+impl ContextConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn backends(mut self, backends: wgpu::Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    pub fn power_preference(mut self, power_preference: wgpu::PowerPreference) -> Self {
+        self.power_preference = power_preference;
+        self
+    }
+
+    pub fn force_fallback_adapter(mut self, force_fallback_adapter: bool) -> Self {
+        self.force_fallback_adapter = force_fallback_adapter;
+        self
+    }
+
+    pub fn adapter_name(mut self, adapter_name: impl Into<String>) -> Self {
+        self.adapter_name = Some(adapter_name.into());
+        self
+    }
+
+    pub fn limits(mut self, limits: wgpu::Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+}
+
+/// Options for creating a windowed [`Renderer`](super::Renderer).
+#[derive(Clone, Debug)]
+pub struct RendererConfig {
+    pub physical_size: (u32, u32),
+    pub scale_factor: f64,
+    /// `Fifo`, `Mailbox`, and `Immediate` must be supported by the surface; the `Auto*` modes
+    /// always are.
+    pub present_mode: wgpu::PresentMode,
+    pub desired_maximum_frame_latency: u32,
+    /// Surface formats in order of preference. The one picked must be an 8-bit RGBA or BGRA
+    /// format or `Rgba16Float`, as for [`Renderer::set_target_format`](crate::Renderer::set_target_format).
+    /// When empty, an sRGB BGRA or RGBA format is preferred and the surface's first format is
+    /// the fallback.
+    pub surface_formats: Vec<wgpu::TextureFormat>,
+    /// Requests a premultiplied or postmultiplied alpha mode when the surface offers one.
+    pub transparent: bool,
+    /// 1 disables MSAA; 4 is the only multisampled count supported.
+    pub msaa_samples: u32,
+    /// Used by constructors that create their own [`RendererContext`](super::RendererContext).
+    pub context: ContextConfig,
+}
+
+impl RendererConfig {
+    pub fn new(physical_size: (u32, u32), scale_factor: f64) -> Self {
+        Self {
+            physical_size,
+            scale_factor,
+            present_mode: wgpu::PresentMode::AutoVsync,
+            desired_maximum_frame_latency: 2,
+            surface_formats: Vec::new(),
+            transparent: false,
+            msaa_samples: 1,
+            context: ContextConfig::default(),
+        }
+    }
+
+    pub fn present_mode(mut self, present_mode: wgpu::PresentMode) -> Self {
+        self.present_mode = present_mode;
+        self
+    }
+
+    /// Shorthand for [`wgpu::PresentMode::AutoVsync`] or [`wgpu::PresentMode::AutoNoVsync`].
+    pub fn vsync(self, vsync: bool) -> Self {
+        self.present_mode(if vsync {
+            wgpu::PresentMode::AutoVsync
+        } else {
+            wgpu::PresentMode::AutoNoVsync
+        })
+    }
+
+    pub fn desired_maximum_frame_latency(mut self, frames: u32) -> Self {
+        self.desired_maximum_frame_latency = frames;
+        self
+    }
+
+    pub fn surface_formats(mut self, formats: impl Into<Vec<wgpu::TextureFormat>>) -> Self {
+        self.surface_formats = formats.into();
+        self
+    }
+
+    pub fn transparent(mut self, transparent: bool) -> Self {
+        self.transparent = transparent;
+        self
+    }
+
+    pub fn msaa_samples(mut self, msaa_samples: u32) -> Self {
+        self.msaa_samples = msaa_samples;
+        self
+    }
+
+    pub fn context(mut self, context: ContextConfig) -> Self {
+        self.context = context;
+        self
+    }
+}
+
+/// Options for creating a headless [`Renderer`](super::Renderer) that renders offscreen.
+#[derive(Clone, Debug)]
+pub struct HeadlessConfig {
+    pub physical_size: (u32, u32),
+    pub scale_factor: f64,
+    /// `Rgba8Unorm`, `Bgra8Unorm`, their sRGB variants, or `Rgba16Float`.
+    pub format: wgpu::TextureFormat,
+    /// Keeps premultiplied alpha in readbacks. When `false`, frames read back opaque, as an
+    /// opaque window would show them.
+    pub transparent: bool,
+    /// 1 disables MSAA; 4 is the only multisampled count supported.
+    pub msaa_samples: u32,
+    /// Used by constructors that create their own [`RendererContext`](super::RendererContext).
+    pub context: ContextConfig,
+}
+
+impl HeadlessConfig {
+    pub fn new(physical_size: (u32, u32), scale_factor: f64) -> Self {
+        Self {
+            physical_size,
+            scale_factor,
+            format: wgpu::TextureFormat::Bgra8UnormSrgb,
+            transparent: true,
+            msaa_samples: 1,
+            context: ContextConfig::default(),
+        }
+    }
+
+    pub fn format(mut self, format: wgpu::TextureFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn transparent(mut self, transparent: bool) -> Self {
+        self.transparent = transparent;
+        self
+    }
+
+    pub fn msaa_samples(mut self, msaa_samples: u32) -> Self {
+        self.msaa_samples = msaa_samples;
+        self
+    }
+
+    pub fn context(mut self, context: ContextConfig) -> Self {
+        self.context = context;
+        self
+    }
+}
+
+/// Checks `present_mode` against the modes a surface supports.
+pub(super) fn validate_present_mode(
+    present_mode: wgpu::PresentMode,
+    supported: &[wgpu::PresentMode],
+) -> Result<wgpu::PresentMode, RendererCreationError> {
+    let automatic = matches!(
+        present_mode,
+        wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync
+    );
+    if automatic || supported.contains(&present_mode) {
+        Ok(present_mode)
+    } else {
+        Err(RendererCreationError::UnsupportedPresentMode(present_mode))
+    }
+}
+
+/// Picks the first of `preferred` that a surface supports.
+pub(super) fn select_preferred_format(
+    preferred: &[wgpu::TextureFormat],
+    supported: &[wgpu::TextureFormat],
+) -> Result<wgpu::TextureFormat, RendererCreationError> {
+    preferred
+        .iter()
+        .copied()
+        .find(|format| supported.contains(format))
+        .ok_or_else(|| RendererCreationError::UnsupportedSurfaceFormat(preferred.to_vec()))
+}
+
+/// Checks the MSAA sample count and that `format_features` allow it.
+pub(super) fn validate_msaa_samples(
+    msaa_samples: u32,
+    format_features: wgpu::TextureFormatFeatureFlags,
+) -> Result<u32, RendererCreationError> {
+    match msaa_samples {
+        1 => Ok(1),
+        4 if format_features.contains(
+            wgpu::TextureFormatFeatureFlags::MULTISAMPLE_X4
+                | wgpu::TextureFormatFeatureFlags::MULTISAMPLE_RESOLVE,
+        ) =>
+        {
+            Ok(4)
+        }
+        _ => Err(RendererCreationError::UnsupportedSampleCount(msaa_samples)),
+    }
+}
+
+/// Whether the renderer can draw into and read back `format`, given the adapter's
+/// `format_features` for it.
+pub(super) fn is_supported_target_format(
+    format: wgpu::TextureFormat,
+    format_features: wgpu::TextureFormatFeatures,
+) -> bool {
+    let required_usages = wgpu::TextureUsages::RENDER_ATTACHMENT
+        | wgpu::TextureUsages::TEXTURE_BINDING
+        | wgpu::TextureUsages::COPY_SRC
+        | wgpu::TextureUsages::COPY_DST;
+    let required_flags =
+        wgpu::TextureFormatFeatureFlags::FILTERABLE | wgpu::TextureFormatFeatureFlags::BLENDABLE;
+    matches!(
+        format,
+        wgpu::TextureFormat::Rgba8Unorm
+            | wgpu::TextureFormat::Rgba8UnormSrgb
+            | wgpu::TextureFormat::Bgra8Unorm
+            | wgpu::TextureFormat::Bgra8UnormSrgb
+            | wgpu::TextureFormat::Rgba16Float
+    ) && format_features.allowed_usages.contains(required_usages)
+        && format_features.flags.contains(required_flags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explicit_present_modes_must_be_supported_by_the_surface() {
+        let supported = [wgpu::PresentMode::Fifo, wgpu::PresentMode::Immediate];
+        for present_mode in [
+            wgpu::PresentMode::AutoVsync,
+            wgpu::PresentMode::AutoNoVsync,
+            wgpu::PresentMode::Immediate,
+        ] {
+            assert_eq!(
+                validate_present_mode(present_mode, &supported).unwrap(),
+                present_mode
+            );
+        }
+        assert!(matches!(
+            validate_present_mode(wgpu::PresentMode::Mailbox, &supported),
+            Err(RendererCreationError::UnsupportedPresentMode(
+                wgpu::PresentMode::Mailbox
+            ))
+        ));
+    }
+
+    #[test]
+    fn preferred_surface_formats_are_tried_in_order() {
+        let supported = [
+            wgpu::TextureFormat::Bgra8Unorm,
+            wgpu::TextureFormat::Rgba16Float,
+        ];
+        assert_eq!(
+            select_preferred_format(
+                &[
+                    wgpu::TextureFormat::Rgba8UnormSrgb,
+                    wgpu::TextureFormat::Rgba16Float,
+                    wgpu::TextureFormat::Bgra8Unorm,
+                ],
+                &supported,
+            )
+            .unwrap(),
+            wgpu::TextureFormat::Rgba16Float
+        );
+        assert!(matches!(
+            select_preferred_format(&[wgpu::TextureFormat::Rgba8UnormSrgb], &supported),
+            Err(RendererCreationError::UnsupportedSurfaceFormat(formats))
+                if formats == [wgpu::TextureFormat::Rgba8UnormSrgb]
+        ));
+    }
+
+    #[test]
+    fn msaa_sample_counts_need_format_support() {
+        let multisampled = wgpu::TextureFormatFeatureFlags::MULTISAMPLE_X4
+            | wgpu::TextureFormatFeatureFlags::MULTISAMPLE_RESOLVE;
+        assert_eq!(
+            validate_msaa_samples(1, wgpu::TextureFormatFeatureFlags::empty()).unwrap(),
+            1
+        );
+        assert_eq!(validate_msaa_samples(4, multisampled).unwrap(), 4);
+        for (samples, features) in [
+            (4, wgpu::TextureFormatFeatureFlags::MULTISAMPLE_X4),
+            (2, multisampled),
+            (8, multisampled),
+            (0, multisampled),
+        ] {
+            assert!(matches!(
+                validate_msaa_samples(samples, features),
+                Err(RendererCreationError::UnsupportedSampleCount(count)) if count == samples
+            ));
+        }
+    }
+
+    #[test]
+    fn target_formats_need_readback_support_and_render_usages() {
+        let renderable = wgpu::TextureFormatFeatures {
+            allowed_usages: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::COPY_DST,
+            flags: wgpu::TextureFormatFeatureFlags::FILTERABLE
+                | wgpu::TextureFormatFeatureFlags::BLENDABLE,
+        };
+        for format in [
+            wgpu::TextureFormat::Rgba8Unorm,
+            wgpu::TextureFormat::Bgra8UnormSrgb,
+            wgpu::TextureFormat::Rgba16Float,
+        ] {
+            assert!(is_supported_target_format(format, renderable));
+        }
+        assert!(!is_supported_target_format(
+            wgpu::TextureFormat::Rgba32Float,
+            renderable
+        ));
+        let not_blendable = wgpu::TextureFormatFeatures {
+            flags: wgpu::TextureFormatFeatureFlags::FILTERABLE,
+            ..renderable
+        };
+        assert!(!is_supported_target_format(
+            wgpu::TextureFormat::Rgba16Float,
+            not_blendable
+        ));
+    }
+}
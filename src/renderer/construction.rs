@@ -1,3 +1,7 @@
+use super::config::{
+    is_supported_target_format, select_preferred_format, validate_msaa_samples,
+    validate_present_mode, ContextConfig, HeadlessConfig, RendererConfig,
+};
 use super::shape_effects::ShapeEffectRendererResources;
 use super::types::DrawCommand;
 use super::*;
@@ -76,42 +80,143 @@ pub enum RendererCreationError {
     /// The context's adapter cannot render to the provided surface.
     #[error("The renderer context adapter does not support the provided surface")]
     UnsupportedSurface,
+    /// A device passed to [`RendererContext::from_wgpu`] lacks features the renderer needs.
+    #[error("The provided device is missing required features: {0:?}")]
+    MissingDeviceFeatures(wgpu::Features),
+    /// No adapter's name contains [`ContextConfig::adapter_name`].
+    #[error("No GPU adapter matches the name {0:?}")]
+    AdapterNotFound(String),
+    /// The adapter cannot provide [`ContextConfig::limits`].
+    #[error("The GPU adapter does not support the requested limits")]
+    UnsupportedLimits,
+    /// The surface does not support [`RendererConfig::present_mode`].
+    #[error("The surface does not support present mode {0:?}")]
+    UnsupportedPresentMode(wgpu::PresentMode),
+    /// The surface supports none of [`RendererConfig::surface_formats`], or the one it picks
+    /// is not a format the renderer can draw into and read back.
+    #[error("The surface supports none of the preferred formats {0:?}")]
+    UnsupportedSurfaceFormat(Vec<wgpu::TextureFormat>),
+    /// The MSAA sample count is not 1 or 4, or the surface format cannot be multisampled.
+    #[error("MSAA with {0} samples is not supported")]
+    UnsupportedSampleCount(u32),
+    /// [`HeadlessConfig::format`] is not a supported render target format on this adapter.
+    #[error("Texture format {0:?} is not a supported render target format")]
+    UnsupportedTargetFormat(wgpu::TextureFormat),
+    /// [`RendererConfig::desired_maximum_frame_latency`] must be at least 1.
+    #[error("Invalid maximum frame latency: {0} (must be at least 1)")]
+    InvalidFrameLatency(u32),
 }
 
 impl RendererContext {
+    /// Device features the renderer requests, and requires of devices passed to
+    /// [`Self::from_wgpu`]. Empty unless the `performance_measurement` feature is enabled.
+    pub fn required_features() -> wgpu::Features {
+        #[cfg(feature = "performance_measurement")]
+        return wgpu::Features::TIMESTAMP_QUERY | wgpu::Features::DEPTH32FLOAT_STENCIL8;
+        #[cfg(not(feature = "performance_measurement"))]
+        wgpu::Features::empty()
+    }
+
     /// Creates GPU resources that can be shared by any number of independent renderers.
     ///
     /// The context deliberately has no surface. A renderer created from it validates and
     /// configures its own surface, so windows can be added later without rebuilding the device.
     pub async fn try_new() -> Result<Self, RendererCreationError> {
-        let instance = Arc::new(wgpu::Instance::new(&InstanceDescriptor::default()));
-        let adapter = Arc::new(
-            instance
-                .request_adapter(&wgpu::RequestAdapterOptions {
-                    power_preference: wgpu::PowerPreference::HighPerformance,
-                    compatible_surface: None,
-                    force_fallback_adapter: false,
-                })
-                .await?,
-        );
+        Self::try_new_with_config(&ContextConfig::default()).await
+    }
+
+    /// Creates a context with the backends, adapter, and limits chosen by `config`.
+    pub async fn try_new_with_config(
+        config: &ContextConfig,
+    ) -> Result<Self, RendererCreationError> {
+        let instance = Arc::new(wgpu::Instance::new(&InstanceDescriptor {
+            backends: config.backends,
+            ..InstanceDescriptor::default()
+        }));
+        let adapter = match &config.adapter_name {
+            Some(adapter_name) => {
+                let adapter_name_lowercase = adapter_name.to_lowercase();
+                instance
+                    .enumerate_adapters(config.backends)
+                    .into_iter()
+                    .find(|adapter| {
+                        let info = adapter.get_info();
+                        info.name.to_lowercase().contains(&adapter_name_lowercase)
+                            && (!config.force_fallback_adapter
+                                || info.device_type == wgpu::DeviceType::Cpu)
+                    })
+                    .ok_or_else(|| RendererCreationError::AdapterNotFound(adapter_name.clone()))?
+            }
+            None => {
+                instance
+                    .request_adapter(&wgpu::RequestAdapterOptions {
+                        power_preference: config.power_preference,
+                        compatible_surface: None,
+                        force_fallback_adapter: config.force_fallback_adapter,
+                    })
+                    .await?
+            }
+        };
+        if !config.limits.check_limits(&adapter.limits()) {
+            return Err(RendererCreationError::UnsupportedLimits);
+        }
+        let adapter = Arc::new(adapter);
 
         let (device, queue) = adapter
             .request_device(&wgpu::DeviceDescriptor {
                 label: None,
-                #[cfg(feature = "performance_measurement")]
-                required_features: wgpu::Features::TIMESTAMP_QUERY
-                    | wgpu::Features::DEPTH32FLOAT_STENCIL8,
-                #[cfg(not(feature = "performance_measurement"))]
-                required_features: wgpu::Features::empty(),
-                required_limits: wgpu::Limits::default(),
+                required_features: Self::required_features(),
+                required_limits: config.limits.clone(),
                 memory_hints: Default::default(),
                 trace: Default::default(),
             })
             .await?;
-        let device = Arc::new(device);
-        let queue = Arc::new(queue);
 
-        Ok(Self {
+        Ok(Self::from_parts(
+            instance,
+            adapter,
+            Arc::new(device),
+            Arc::new(queue),
+        ))
+    }
+
+    /// Creates a context on a device the application already owns, so grafo shares it with
+    /// the rest of the application instead of requesting its own.
+    ///
+    /// Textures created on `device` can then be drawn by grafo through
+    /// [`TextureManager::insert_wgpu_texture`], and textures grafo manages can be read back
+    /// with [`TextureManager::wgpu_texture`], without copying through the CPU. `adapter` must
+    /// be the one `device` was requested from, and `instance` the one that created `adapter`.
+    ///
+    /// `device` must have been requested with at least [`Self::required_features`];
+    /// otherwise [`RendererCreationError::MissingDeviceFeatures`] is returned.
+    pub fn from_wgpu(
+        instance: wgpu::Instance,
+        adapter: wgpu::Adapter,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+    ) -> Result<Self, RendererCreationError> {
+        let missing_features = Self::required_features() - device.features();
+        if !missing_features.is_empty() {
+            return Err(RendererCreationError::MissingDeviceFeatures(
+                missing_features,
+            ));
+        }
+        Ok(Self::from_parts(
+            Arc::new(instance),
+            Arc::new(adapter),
+            Arc::new(device),
+            Arc::new(queue),
+        ))
+    }
+
+    fn from_parts(
+        instance: Arc<wgpu::Instance>,
+        adapter: Arc<wgpu::Adapter>,
+        device: Arc<wgpu::Device>,
+        queue: Arc<wgpu::Queue>,
+    ) -> Self {
+        Self {
             inner: Arc::new(RendererContextInner {
                 instance,
                 adapter,
@@ -120,7 +225,27 @@ impl RendererContext {
                 device,
                 queue,
             }),
-        })
+        }
+    }
+
+    /// The instance that created [`Self::adapter`].
+    pub fn instance(&self) -> &wgpu::Instance {
+        &self.inner.instance
+    }
+
+    /// The adapter [`Self::device`] was requested from.
+    pub fn adapter(&self) -> &wgpu::Adapter {
+        &self.inner.adapter
+    }
+
+    /// The device every renderer and texture of this context lives on.
+    pub fn device(&self) -> &wgpu::Device {
+        &self.inner.device
+    }
+
+    /// The queue renderers of this context submit their work to.
+    pub fn queue(&self) -> &wgpu::Queue {
+        &self.inner.queue
     }
 
     /// Creates a shared GPU context, panicking when no compatible device is available.
@@ -186,39 +311,85 @@ impl<'a> Renderer<'a> {
         transparent: bool,
         msaa_samples: u32,
     ) -> Result<Self, RendererCreationError> {
+        Self::try_new_with_context_and_config(
+            context,
+            window,
+            &RendererConfig::new(physical_size, scale_factor)
+                .vsync(vsync)
+                .transparent(transparent)
+                .msaa_samples(Self::validate_sample_count_static(msaa_samples)),
+        )
+    }
+
+    /// Creates a renderer, and a context for it from [`RendererConfig::context`].
+    pub async fn try_new_with_config(
+        window: impl Into<SurfaceTarget<'static>>,
+        config: &RendererConfig,
+    ) -> Result<Self, RendererCreationError> {
+        Self::try_new_with_context_and_config(
+            RendererContext::try_new_with_config(&config.context).await?,
+            window,
+            config,
+        )
+    }
+
+    /// Creates a renderer for `window` on an existing context; [`RendererConfig::context`] is
+    /// ignored.
+    ///
+    /// Returns a typed error when the surface does not support the requested present mode,
+    /// formats, or MSAA sample count.
+    pub fn try_new_with_context_and_config(
+        context: RendererContext,
+        window: impl Into<SurfaceTarget<'static>>,
+        config: &RendererConfig,
+    ) -> Result<Self, RendererCreationError> {
+        if config.desired_maximum_frame_latency == 0 {
+            return Err(RendererCreationError::InvalidFrameLatency(0));
+        }
         let surface = context.inner.instance.create_surface(window)?;
 
         let surface_caps = surface.get_capabilities(&context.inner.adapter);
         if surface_caps.formats.is_empty() {
             return Err(RendererCreationError::UnsupportedSurface);
         }
-        let swapchain_format = pick_surface_format(&surface_caps.formats);
-        let alpha_mode = pick_alpha_mode(&surface_caps.alpha_modes, transparent);
+        let swapchain_format = if config.surface_formats.is_empty() {
+            pick_surface_format(&surface_caps.formats)
+        } else {
+            select_preferred_format(&config.surface_formats, &surface_caps.formats)?
+        };
+        let format_features = context
+            .inner
+            .adapter
+            .get_texture_format_features(swapchain_format);
+        if !config.surface_formats.is_empty()
+            && !is_supported_target_format(swapchain_format, format_features)
+        {
+            return Err(RendererCreationError::UnsupportedSurfaceFormat(
+                config.surface_formats.clone(),
+            ));
+        }
+        let present_mode = validate_present_mode(config.present_mode, &surface_caps.present_modes)?;
+        let msaa_sample_count = validate_msaa_samples(config.msaa_samples, format_features.flags)?;
+        let alpha_mode = pick_alpha_mode(&surface_caps.alpha_modes, config.transparent);
 
-        let config = wgpu::SurfaceConfiguration {
+        let surface_config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
             format: swapchain_format,
-            width: physical_size.0,
-            height: physical_size.1,
-            present_mode: if vsync {
-                wgpu::PresentMode::AutoVsync
-            } else {
-                wgpu::PresentMode::AutoNoVsync
-            },
-            desired_maximum_frame_latency: 2,
+            width: config.physical_size.0,
+            height: config.physical_size.1,
+            present_mode,
+            desired_maximum_frame_latency: config.desired_maximum_frame_latency,
             alpha_mode,
             view_formats: vec![],
         };
-        surface.configure(&context.inner.device, &config);
-
-        let msaa_sample_count = Self::validate_sample_count_static(msaa_samples);
+        surface.configure(&context.inner.device, &surface_config);
 
         Self::build_from_context(
             context,
             Some(surface),
-            config,
-            physical_size,
-            scale_factor,
+            surface_config,
+            config.physical_size,
+            config.scale_factor,
             msaa_sample_count,
         )
     }
@@ -401,6 +572,8 @@ impl<'a> Renderer<'a> {
             rtb_readback_buffer: None,
             rtb_cached_width: 0,
             rtb_cached_height: 0,
+            async_readback_buffers: Default::default(),
+            opaque_readback: false,
             msaa_sample_count,
             msaa_color_texture: None,
             msaa_color_texture_view: None,
@@ -410,6 +583,9 @@ impl<'a> Renderer<'a> {
             group_effects: HashMap::new(),
             backdrop_effects: HashMap::new(),
             shape_effects: HashMap::new(),
+            soft_clip_nodes: Vec::new(),
+            layer_nodes: Vec::new(),
+            layer_cache: FrameCache::new(),
             shape_effect_cache: FrameCache::new(),
             shape_effect_mask_cache: FrameCache::new(),
             shape_effect_resources,
@@ -418,11 +594,14 @@ impl<'a> Renderer<'a> {
             composite_bgl: None,
             effect_sampler: None,
             texture_blit_pipeline: None,
+            target_composite_pipeline: None,
             backdrop_layer_composite_pipeline: None,
             backdrop_layer_composite_bind_group_layout: None,
             stencil_only_pipeline: None,
             backdrop_color_pipeline: None,
             backdrop_color_gradient_pipeline: None,
+            soft_clip_composite_pipeline: None,
+            soft_clip_material_params_buffer: None,
             leaf_draw_pipeline: Arc::new(leaf_draw_pipeline),
             leaf_draw_gradient_pipeline: Arc::new(leaf_draw_gradient_pipeline),
             and_gradient_pipeline: Arc::new(and_gradient_pipeline),
@@ -438,6 +617,8 @@ impl<'a> Renderer<'a> {
             last_pipeline_switch_counts: Default::default(),
             #[cfg(feature = "render_metrics")]
             last_shape_effect_cache_metrics: Default::default(),
+            #[cfg(feature = "render_metrics")]
+            last_layer_cache_metrics: Default::default(),
             last_render_to_texture_view_cpu_time: Default::default(),
             scratch: RendererScratch::new(),
         };
@@ -563,6 +744,9 @@ impl<'a> Renderer<'a> {
         if let Some(buf) = &self.rtb_readback_buffer {
             println!("RTB readback buffer: {} bytes", buf.size());
         }
+        for buf in self.async_readback_buffers.lock().unwrap().iter() {
+            println!("Idle async readback buffer: {} bytes", buf.size());
+        }
 
         println!("\n--- Uniform Buffers ---");
         println!(
@@ -772,29 +956,68 @@ impl<'a> Renderer<'a> {
         physical_size: (u32, u32),
         scale_factor: f64,
     ) -> Result<Self, RendererCreationError> {
-        let swapchain_format = wgpu::TextureFormat::Bgra8UnormSrgb;
+        Self::try_new_headless_with_context_and_config(
+            context,
+            &HeadlessConfig::new(physical_size, scale_factor),
+        )
+    }
+
+    /// Creates a headless renderer, and a context for it from [`HeadlessConfig::context`].
+    pub async fn try_new_headless_with_config(
+        config: &HeadlessConfig,
+    ) -> Result<Self, RendererCreationError> {
+        Self::try_new_headless_with_context_and_config(
+            RendererContext::try_new_with_config(&config.context).await?,
+            config,
+        )
+    }
+
+    /// Creates a headless renderer on an existing context; [`HeadlessConfig::context`] is
+    /// ignored.
+    ///
+    /// Returns a typed error when the adapter cannot render to the requested format or with
+    /// the requested MSAA sample count.
+    pub fn try_new_headless_with_context_and_config(
+        context: RendererContext,
+        config: &HeadlessConfig,
+    ) -> Result<Self, RendererCreationError> {
+        let format_features = context
+            .inner
+            .adapter
+            .get_texture_format_features(config.format);
+        if !is_supported_target_format(config.format, format_features) {
+            return Err(RendererCreationError::UnsupportedTargetFormat(
+                config.format,
+            ));
+        }
+        let msaa_sample_count = validate_msaa_samples(config.msaa_samples, format_features.flags)?;
+        let alpha_mode = if config.transparent {
+            CompositeAlphaMode::PreMultiplied
+        } else {
+            CompositeAlphaMode::Opaque
+        };
 
-        let config = wgpu::SurfaceConfiguration {
+        let surface_config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
-            format: swapchain_format,
-            width: physical_size.0,
-            height: physical_size.1,
+            format: config.format,
+            width: config.physical_size.0,
+            height: config.physical_size.1,
             present_mode: wgpu::PresentMode::AutoVsync,
             desired_maximum_frame_latency: 2,
-            alpha_mode: CompositeAlphaMode::Opaque,
+            alpha_mode,
             view_formats: vec![],
         };
 
-        let msaa_sample_count = 1;
-
-        Self::build_from_context(
+        let mut renderer = Self::build_from_context(
             context,
             None,
-            config,
-            physical_size,
-            scale_factor,
+            surface_config,
+            config.physical_size,
+            config.scale_factor,
             msaa_sample_count,
-        )
+        )?;
+        renderer.opaque_readback = !config.transparent;
+        Ok(renderer)
     }
 
     /// Creates a headless renderer without a window surface, panicking on
@@ -863,6 +1086,7 @@ impl<'a> Renderer<'a> {
         self.shape_texture_bind_group_layout_foreground = Arc::new(and_texture_bgl_layer1);
         self.shape_effect_cache.clear();
         self.shape_effect_mask_cache.clear();
+        self.layer_cache.clear();
         self.backdrop_texture_bind_group_layout =
             Arc::new(create_backdrop_texture_bind_group_layout(&self.device));
         self.shape_texture_layout_epoch += 1;
@@ -936,11 +1160,13 @@ impl<'a> Renderer<'a> {
 
         // Reset lazily-created pipelines so they pick up the new layout
         self.texture_blit_pipeline = None;
+        self.target_composite_pipeline = None;
         self.backdrop_layer_composite_pipeline = None;
         self.backdrop_layer_composite_bind_group_layout = None;
         self.stencil_only_pipeline = None;
         self.backdrop_color_pipeline = None;
         self.backdrop_color_gradient_pipeline = None;
+        self.soft_clip_composite_pipeline = None;
 
         // Refresh per-shape gradient bind groups against the new layout so the
         // next render does not allocate gradient resources on the render path.
@@ -1,11 +1,28 @@
 use super::types::{ClipRectDrawData, DrawCommandError};
 use super::*;
-use crate::shape::ShapeTextureBinding;
+use crate::shape::{BorderRadii, ShapeTextureBinding};
 use crate::ShapeDrawCommandOptions;
-use crate::ShapeTextureFitMode;
+use crate::ShapeOverflow;
+use crate::ShapeTextureOptions;
+use crate::Stroke;
 
-fn clip_rect_supports_transform(transform: InstanceTransform) -> bool {
-    rect_utils::extract_axis_aligned_rect_transform(Some(transform)).is_some()
+/// A texture layer's sampling and binding, resolved when its shape is added.
+#[derive(Debug, Clone, Default)]
+struct ResolvedTextureLayer {
+    sampling: preparation::TextureLayerSampling,
+    binding: ShapeTextureBinding,
+}
+
+/// Half-length, in local units, of the single-axis overflow band along the unclipped axis when
+/// the viewport can't be mapped into the shape's local space, e.g. past a perspective horizon.
+const AXIS_CLIP_BAND_FALLBACK_EXTENT: f32 = 100_000.0;
+
+fn clip_rect_supports_scissor(
+    border_radii: BorderRadii,
+    transform: Option<InstanceTransform>,
+) -> bool {
+    border_radii == BorderRadii::default()
+        && rect_utils::extract_axis_aligned_rect_transform(transform).is_some()
 }
 
 impl<'a> Renderer<'a> {
@@ -71,6 +88,7 @@ impl<'a> Renderer<'a> {
             return Err(DrawCommandError::ShapeNotLoaded(cache_key));
         };
         self.append_buffers_for_shape(&mut draw_data, &options);
+        self.attach_axis_clip_geometry(&mut draw_data);
         self.add_draw_command(DrawCommand::CachedShape(draw_data), parent_shape_id)
     }
 
@@ -96,17 +114,19 @@ impl<'a> Renderer<'a> {
         let mut draw_data = CachedShapeDrawData::new(cached_shape, &options);
 
         self.append_buffers_for_shape(&mut draw_data, &options);
+        self.attach_axis_clip_geometry(&mut draw_data);
         self.add_draw_command(DrawCommand::CachedShape(draw_data), parent_shape_id)
     }
 
-    /// Adds an axis-aligned scissor clipping rectangle without preparing geometry.
+    /// Adds a clipping rectangle without a visible surface.
     ///
-    /// This node clips its children like a transparent rect parent by default when its
-    /// transform preserves axis alignment. Rotated, skewed, or perspective transforms are
-    /// rejected by the transform setters because this node intentionally has no geometry
-    /// for stencil fallback. To let children overflow from a shape parent, queue that parent with
-    /// [`ShapeDrawCommandOptions::clips_children(false)`] instead of relying on the older
-    /// overflow API wording.
+    /// This node clips its children like a transparent rect parent. When its transform
+    /// preserves axis alignment, it clips with a hardware scissor and prepares no geometry.
+    /// Rotated, skewed, or perspective transforms fall back to stencil clipping against
+    /// geometry synthesized for the rect. Group, backdrop, and shape effects can be attached
+    /// like on any shape node, using the rect bounds as the capture area. To let children
+    /// overflow from a shape parent, queue that parent with
+    /// [`ShapeDrawCommandOptions::clips_children(false)`].
     ///
     /// When `parent_shape_id` is `Some`, the clipping rectangle is attached as a child of
     /// that node and inherits ancestor clips.
@@ -116,23 +136,167 @@ impl<'a> Renderer<'a> {
         parent_shape_id: Option<usize>,
         transform: Option<impl Into<InstanceTransform>>,
         clips_children: bool,
+    ) -> Result<usize, DrawCommandError> {
+        self.add_rounded_clipping_rect(
+            rect_bounds,
+            BorderRadii::default(),
+            parent_shape_id,
+            transform,
+            clips_children,
+        )
+    }
+
+    /// Adds a clipping rectangle with rounded corners without a visible surface.
+    ///
+    /// Behaves like [`add_clipping_rect`](Self::add_clipping_rect), but children are clipped to
+    /// the rounded outline. Zero radii with an axis-aligned transform keep the scissor fast
+    /// path; anything else clips through synthesized stencil geometry.
+    pub fn add_rounded_clipping_rect(
+        &mut self,
+        rect_bounds: [(f32, f32); 2],
+        border_radii: BorderRadii,
+        parent_shape_id: Option<usize>,
+        transform: Option<impl Into<InstanceTransform>>,
+        clips_children: bool,
     ) -> Result<usize, DrawCommandError> {
         let transform = transform.map(Into::into);
-        if let Some(transform) = transform {
-            if !clip_rect_supports_transform(transform) {
-                return Err(DrawCommandError::UnsupportedClipRectTransform);
-            }
-        }
+        let stencil_geometry = if clip_rect_supports_scissor(border_radii, transform) {
+            None
+        } else {
+            Some(self.synthesize_clip_rect_geometry(rect_bounds, border_radii, transform))
+        };
         self.add_draw_command(
             DrawCommand::ClipRect(ClipRectDrawData::new(
                 rect_bounds,
+                border_radii,
                 transform,
                 clips_children,
+                stencil_geometry,
             )),
             parent_shape_id,
         )
     }
 
+    /// Gives a scissor-clipped clip rect drawable geometry so effects can render it.
+    /// Children keep clipping through the scissor as long as the rect stays axis-aligned.
+    pub(super) fn ensure_clip_rect_stencil_geometry(&mut self, node_id: usize) {
+        let Some(DrawCommand::ClipRect(clip_rect)) = self.draw_tree.get(node_id) else {
+            return;
+        };
+        if clip_rect.stencil_geometry.is_some() {
+            return;
+        }
+        let (rect_bounds, border_radii, transform) = (
+            clip_rect.rect_bounds,
+            clip_rect.border_radii,
+            clip_rect.transform,
+        );
+        let stencil_geometry =
+            self.synthesize_clip_rect_geometry(rect_bounds, border_radii, transform);
+        if let Some(DrawCommand::ClipRect(clip_rect)) = self.draw_tree.get_mut(node_id) {
+            clip_rect.stencil_geometry = Some(stencil_geometry);
+        }
+    }
+
+    /// Synthesizes the stencil band for single-axis overflow unless the shape is a rect whose
+    /// transform allows a scissor. The band is the parent's geometry swept along the unclipped
+    /// axis, spanning every canvas up to the device's texture size limit on that axis.
+    fn attach_axis_clip_geometry(&mut self, draw_data: &mut CachedShapeDrawData) {
+        let (clipped_axis, free_axis) = match draw_data.overflow {
+            ShapeOverflow::ClipX => (0, 1),
+            ShapeOverflow::ClipY => (1, 0),
+            ShapeOverflow::Hidden | ShapeOverflow::Visible => return,
+        };
+        if draw_data.cached_shape.is_rect
+            && rect_utils::extract_axis_aligned_rect_transform(draw_data.transform).is_some()
+        {
+            return;
+        }
+
+        let vertex_buffers = Arc::clone(draw_data.cached_shape.vertex_buffers());
+        let intervals = rect_utils::swept_intervals(
+            &vertex_buffers.vertices,
+            &vertex_buffers.indices,
+            clipped_axis,
+        );
+        if intervals.is_empty() {
+            return;
+        }
+
+        let [local_min, local_max] = draw_data.cached_shape.local_bounds();
+        let (shape_min, shape_max) = match free_axis {
+            0 => (local_min.0, local_max.0),
+            _ => (local_min.1, local_max.1),
+        };
+        let canvas_extent =
+            self.device.limits().max_texture_dimension_2d as f32 / self.scale_factor as f32;
+        let covered_screen_rect = [
+            (-canvas_extent, -canvas_extent),
+            (2.0 * canvas_extent, 2.0 * canvas_extent),
+        ];
+        let (free_min, free_max) =
+            match rect_utils::screen_rect_to_local_bounds(covered_screen_rect, draw_data.transform)
+            {
+                Some([min, max]) if free_axis == 0 => (min.0.min(shape_min), max.0.max(shape_max)),
+                Some([min, max]) => (min.1.min(shape_min), max.1.max(shape_max)),
+                None => (
+                    shape_min - AXIS_CLIP_BAND_FALLBACK_EXTENT,
+                    shape_max + AXIS_CLIP_BAND_FALLBACK_EXTENT,
+                ),
+            };
+
+        let mut builder = Shape::builder();
+        for (start, end) in intervals {
+            let corner = |clipped: f32, free: f32| match clipped_axis {
+                0 => (clipped, free),
+                _ => (free, clipped),
+            };
+            builder = builder
+                .begin(corner(start, free_min))
+                .line_to(corner(end, free_min))
+                .line_to(corner(end, free_max))
+                .line_to(corner(start, free_max))
+                .close();
+        }
+        let band = self.synthesize_stencil_geometry(&builder.build(), draw_data.transform);
+        draw_data.axis_clip_geometry = Some(Box::new(band));
+    }
+
+    fn synthesize_clip_rect_geometry(
+        &mut self,
+        rect_bounds: [(f32, f32); 2],
+        border_radii: BorderRadii,
+        transform: Option<InstanceTransform>,
+    ) -> CachedShapeDrawData {
+        let shape = if border_radii == BorderRadii::default() {
+            Shape::rect(rect_bounds, Stroke::default())
+        } else {
+            Shape::rounded_rect(rect_bounds, border_radii, Stroke::default())
+        };
+        self.synthesize_stencil_geometry(&shape, transform)
+    }
+
+    /// Tessellates `shape` into the queue's buffers for use as synthesized stencil geometry.
+    fn synthesize_stencil_geometry(
+        &mut self,
+        shape: &Shape,
+        transform: Option<InstanceTransform>,
+    ) -> CachedShapeDrawData {
+        let cached_shape = CachedShapeHandle::new(
+            shape,
+            &mut self.tessellator,
+            &mut self.buffers_pool_manager,
+            None,
+        );
+        let options = ShapeDrawCommandOptions {
+            transform,
+            ..ShapeDrawCommandOptions::default()
+        };
+        let mut draw_data = CachedShapeDrawData::new(cached_shape, &options);
+        self.append_buffers_for_shape(&mut draw_data, &options);
+        draw_data
+    }
+
     fn append_buffers_for_shape(
         &mut self,
         cached_shape_data: &mut CachedShapeDrawData,
@@ -156,10 +320,19 @@ impl<'a> Renderer<'a> {
         if let Some((index_start, index_count)) = index_range {
             cached_shape_data.index_buffer_range = Some((index_start, index_count));
             cached_shape_data.is_empty = false;
-            let texture_uv_scales = self.compute_texture_uv_scales(
+            let texture_layers = self.resolve_texture_layers(
                 cached_shape_data.cached_shape.texture_mapping_size(),
                 draw_options,
             );
+            for (texture_binding, texture_layer) in cached_shape_data
+                .texture_bindings
+                .iter_mut()
+                .zip(&texture_layers)
+            {
+                if texture_layer.binding.is_present() {
+                    *texture_binding = texture_layer.binding.clone();
+                }
+            }
             let instance_index = preparation::append_instance_data(
                 &mut self.temp_instance_transforms,
                 &mut self.temp_instance_colors,
@@ -174,7 +347,7 @@ impl<'a> Renderer<'a> {
                         .texture_bindings
                         .each_ref()
                         .map(ShapeTextureBinding::is_present),
-                    texture_uv_scales,
+                    layers: texture_layers.each_ref().map(|layer| layer.sampling),
                 },
             );
             *cached_shape_data.instance_index_mut() = Some(instance_index);
@@ -188,26 +361,28 @@ impl<'a> Renderer<'a> {
         draw_command: DrawCommand,
         parent_shape_id: Option<usize>,
     ) -> Result<usize, DrawCommandError> {
-        if self.draw_tree.is_empty() {
-            let node_id = self.draw_tree.add_node(draw_command);
-            Ok(node_id)
+        let soft_clip = draw_command.soft_clip();
+        let node_id = if self.draw_tree.is_empty() {
+            self.draw_tree.add_node(draw_command)
         } else if let Some(parent_shape_id) = parent_shape_id {
             // Mark the parent as non-leaf since it now has a child.
             if let Some(parent) = self.draw_tree.get_mut(parent_shape_id) {
                 parent.set_not_leaf();
-                let node_id = self.draw_tree.add_child(parent_shape_id, draw_command);
-                Ok(node_id)
+                self.draw_tree.add_child(parent_shape_id, draw_command)
             } else {
-                Err(DrawCommandError::InvalidShapeId(parent_shape_id))
+                return Err(DrawCommandError::InvalidShapeId(parent_shape_id));
             }
         } else {
             // Adding to root — mark root as non-leaf.
             if let Some(root) = self.draw_tree.get_mut(0) {
                 root.set_not_leaf();
             }
-            let node_id = self.draw_tree.add_child_to_root(draw_command);
-            Ok(node_id)
+            self.draw_tree.add_child_to_root(draw_command)
+        };
+        if soft_clip {
+            self.soft_clip_nodes.push(node_id);
         }
+        Ok(node_id)
     }
 
     fn refresh_geometry_cache(&mut self, cached_shape_data: &CachedShapeDrawData) {
@@ -228,65 +403,73 @@ impl<'a> Renderer<'a> {
         self.group_effects.clear();
         self.backdrop_effects.clear();
         self.shape_effects.clear();
+        self.soft_clip_nodes.clear();
+        self.layer_nodes.clear();
         // Keep scratch storage bounded even if queue contents fluctuate frame-to-frame.
         self.trim_scratch_on_resize_or_policy();
         // Clear memory buffers that are used for GPU upload
         self.clear_buffers();
     }
 
-    fn compute_texture_uv_scales(
+    fn resolve_texture_layers(
         &self,
         texture_mapping_size: [f32; 2],
         draw_options: &ShapeDrawCommandOptions,
-    ) -> [[f32; 2]; 2] {
+    ) -> [ResolvedTextureLayer; 2] {
         [
-            self.compute_texture_uv_scale_for_layer(
-                draw_options.background_texture.texture_id,
-                draw_options.background_texture.fit_mode,
-                texture_mapping_size,
-            ),
-            self.compute_texture_uv_scale_for_layer(
-                draw_options.foreground_texture.texture_id,
-                draw_options.foreground_texture.fit_mode,
-                texture_mapping_size,
-            ),
+            self.resolve_texture_layer(&draw_options.background_texture, texture_mapping_size),
+            self.resolve_texture_layer(&draw_options.foreground_texture, texture_mapping_size),
         ]
     }
 
-    fn compute_texture_uv_scale_for_layer(
+    /// Resolves a layer's fit mode, source rect, sampler, and atlas placement into its
+    /// sampling and binding.
+    fn resolve_texture_layer(
         &self,
-        texture_id: Option<u64>,
-        texture_fit_mode: ShapeTextureFitMode,
+        texture_options: &ShapeTextureOptions,
         texture_mapping_size: [f32; 2],
-    ) -> [f32; 2] {
-        if texture_fit_mode != ShapeTextureFitMode::OriginalSize {
-            return [1.0, 1.0];
-        }
-
-        let Some(texture_id) = texture_id else {
-            return [1.0, 1.0];
+    ) -> ResolvedTextureLayer {
+        let Some(texture_id) = texture_options.texture_id else {
+            return ResolvedTextureLayer::default();
         };
+        let texture_dimensions = self.texture_manager.texture_dimensions(texture_id);
+        let [(min_u, min_v), (max_u, max_v)] = texture_options
+            .source_rect
+            .and_then(|source_rect| source_rect.uv_rect(texture_dimensions))
+            .unwrap_or([(0.0, 0.0), (1.0, 1.0)]);
+        let region_texels = texture_dimensions.map(|(width, height)| {
+            [
+                width as f32 * (max_u - min_u).abs(),
+                height as f32 * (max_v - min_v).abs(),
+            ]
+        });
+        let shape_size = texture_mapping_size.map(|length| length * self.scale_factor as f32);
 
-        let Some((texture_width, texture_height)) =
-            self.texture_manager.texture_dimensions(texture_id)
-        else {
-            return [1.0, 1.0];
+        let mut sampling =
+            preparation::fit_texture_layer(texture_options.fit_mode, shape_size, region_texels);
+        sampling.region = [min_u, min_v, max_u, max_v];
+        let sampler = texture_options
+            .sampler
+            .unwrap_or_else(|| self.texture_manager.texture_sampler(texture_id));
+        sampling.address_modes = [sampler.address_mode_u, sampler.address_mode_v];
+        sampling.is_mask = self.texture_manager.is_mask_texture(texture_id);
+        if let Some((yuv_options, luma_uv_rect)) = self.texture_manager.yuv_sampling(texture_id) {
+            sampling.region = crate::texture_manager::map_uv_region(luma_uv_rect, sampling.region);
+            sampling.yuv = Some(yuv_options);
+        }
+        let binding = match self.texture_manager.atlas_placement(texture_id) {
+            Some(placement) => {
+                sampling.region = placement.map_region(sampling.region);
+                ShapeTextureBinding::AtlasPage {
+                    page_id: placement.page_id,
+                    sampler,
+                }
+            }
+            None => ShapeTextureBinding::Managed {
+                texture_id,
+                sampler,
+            },
         };
-
-        self.compute_texture_uv_scale_from_dimensions(
-            texture_mapping_size,
-            (texture_width, texture_height),
-        )
-    }
-
-    pub(super) fn compute_texture_uv_scale_from_dimensions(
-        &self,
-        texture_mapping_size: [f32; 2],
-        texture_dimensions: (u32, u32),
-    ) -> [f32; 2] {
-        [
-            texture_mapping_size[0] * self.scale_factor as f32 / texture_dimensions.0.max(1) as f32,
-            texture_mapping_size[1] * self.scale_factor as f32 / texture_dimensions.1.max(1) as f32,
-        ]
+        ResolvedTextureLayer { sampling, binding }
     }
 }
@@ -1,7 +1,8 @@
 use super::*;
 use crate::pipeline::{
     create_backdrop_gradient_stencil_keep_color_pipeline,
-    create_backdrop_stencil_keep_color_pipeline, create_buffer_init, create_stencil_only_pipeline,
+    create_backdrop_stencil_keep_color_pipeline, create_buffer_init,
+    create_soft_clip_composite_pipeline, create_stencil_only_pipeline,
 };
 
 fn overwrite_effect_params(storage: &mut Vec<u8>, params: &[u8]) {
@@ -242,6 +243,33 @@ impl<'a> Renderer<'a> {
 
         let loaded_effect =
             compile_effect_pipeline(&self.device, pass_sources, self.config.format)?;
+        self.install_loaded_effect(effect_id, loaded_effect);
+        Ok(())
+    }
+
+    /// Compiles every loaded effect for `format`, keyed by effect id.
+    pub(super) fn compile_loaded_effects_for_format(
+        &self,
+        format: wgpu::TextureFormat,
+    ) -> Result<Vec<(u64, LoadedEffect)>, EffectError> {
+        self.loaded_effects
+            .iter()
+            .map(|(&effect_id, loaded_effect)| {
+                let pass_sources: Vec<&str> = loaded_effect
+                    .pass_sources
+                    .iter()
+                    .map(|source| source.as_ref())
+                    .collect();
+                Ok((
+                    effect_id,
+                    compile_effect_pipeline(&self.device, &pass_sources, format)?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Stores a compiled effect and refreshes the instances and caches that reference it.
+    pub(super) fn install_loaded_effect(&mut self, effect_id: u64, loaded_effect: LoadedEffect) {
         self.loaded_effects.insert(effect_id, loaded_effect);
         let loaded_effect = self
             .loaded_effects
@@ -266,7 +294,8 @@ impl<'a> Renderer<'a> {
         });
         self.shape_effect_cache
             .retain(|cache_key, _| cache_key.effect_id != effect_id);
-        Ok(())
+        self.layer_cache
+            .retain(|_, layer| !layer.effect_ids.contains(&effect_id));
     }
 
     pub fn set_group_effect(
@@ -278,15 +307,6 @@ impl<'a> Renderer<'a> {
         if self.draw_tree.get(node_id).is_none() {
             return Err(EffectError::NodeNotFound(node_id));
         }
-        if self
-            .draw_tree
-            .get(node_id)
-            .is_some_and(DrawCommand::is_clip_rect)
-        {
-            return Err(EffectError::InvalidParams(
-                "clip rectangles do not support group effects".to_string(),
-            ));
-        }
 
         validate_effect_params(&self.loaded_effects, effect_id, params)?;
 
@@ -341,18 +361,10 @@ impl<'a> Renderer<'a> {
         if self.draw_tree.get(node_id).is_none() {
             return Err(EffectError::NodeNotFound(node_id));
         }
-        if self
-            .draw_tree
-            .get(node_id)
-            .is_some_and(DrawCommand::is_clip_rect)
-        {
-            return Err(EffectError::InvalidParams(
-                "clip rectangles do not support backdrop effects".to_string(),
-            ));
-        }
 
         validate_effect_params(&self.loaded_effects, effect_id, params)?;
         validate_backdrop_config(&backdrop_config)?;
+        self.ensure_clip_rect_stencil_geometry(node_id);
 
         let instance = build_effect_instance(
             &self.device,
@@ -420,18 +432,13 @@ impl<'a> Renderer<'a> {
         params: &[u8],
         config: effect::ShapeEffectConfig,
     ) -> Result<(), EffectError> {
-        let draw_command = self
-            .draw_tree
-            .get(node_id)
-            .ok_or(EffectError::NodeNotFound(node_id))?;
-        if draw_command.is_clip_rect() {
-            return Err(EffectError::InvalidParams(
-                "clip rectangles do not support shape effects".to_string(),
-            ));
+        if self.draw_tree.get(node_id).is_none() {
+            return Err(EffectError::NodeNotFound(node_id));
         }
 
         validate_effect_params(&self.loaded_effects, effect_id, params)?;
         validate_shape_effect_config(&config)?;
+        self.ensure_clip_rect_stencil_geometry(node_id);
         self.shape_effects.insert(
             node_id,
             effect::ShapeEffectInstance {
@@ -490,6 +497,8 @@ impl<'a> Renderer<'a> {
             .retain(|_, instance| instance.effect_id != effect_id);
         self.shape_effect_cache
             .retain(|cache_key, _| cache_key.effect_id != effect_id);
+        self.layer_cache
+            .retain(|_, layer| !layer.effect_ids.contains(&effect_id));
     }
 
     pub(super) fn ensure_composite_pipeline(&mut self) {
@@ -514,6 +523,24 @@ impl<'a> Renderer<'a> {
             &self.device,
             self.config.format,
             composite_bind_group_layout,
+            None,
+        ));
+    }
+
+    pub(super) fn ensure_target_composite_pipeline(&mut self) {
+        if self.target_composite_pipeline.is_some() {
+            return;
+        }
+
+        let composite_bind_group_layout = self
+            .composite_bgl
+            .as_ref()
+            .expect("composite bind group layout must exist before the target composite pipeline");
+        self.target_composite_pipeline = Some(effect::compile_texture_blit_pipeline(
+            &self.device,
+            self.config.format,
+            composite_bind_group_layout,
+            Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
         ));
     }
 
@@ -594,6 +621,24 @@ impl<'a> Renderer<'a> {
         );
         self.backdrop_color_gradient_pipeline = Some(pipeline);
     }
+
+    pub(super) fn ensure_soft_clip_composite_pipeline(&mut self) {
+        if self.soft_clip_composite_pipeline.is_some() {
+            return;
+        }
+
+        let uniform_bind_group_layout = self.and_pipeline.get_bind_group_layout(0);
+        let pipeline = create_soft_clip_composite_pipeline(
+            &self.device,
+            self.config.format,
+            self.msaa_sample_count,
+            &uniform_bind_group_layout,
+            &self.shape_texture_bind_group_layout_background,
+            &self.shape_texture_bind_group_layout_foreground,
+            &self.backdrop_texture_bind_group_layout,
+        );
+        self.soft_clip_composite_pipeline = Some(pipeline);
+    }
 }
 
 #[cfg(test)]
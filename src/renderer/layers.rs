@@ -0,0 +1,569 @@
+//! Subtrees cached as rasterized layers with [`Renderer::cache_as_layer`].
+//!
+//! A layer is rasterized into a viewport-sized pooled texture and composited like a group
+//! effect result. The raster is keyed by a hash of everything the subtree draws except node
+//! translations, so unchanged content is reused as is, and content that only moved by whole
+//! physical pixels is shifted with a texture copy instead of being redrawn.
+
+use super::passes::transformed_bounds_to_logical_screen_rect;
+use super::types::{DrawCommand, DrawCommandError};
+use super::Renderer;
+use crate::cache::{CachedTessellation, FrameCache};
+use crate::effect::PooledTexture;
+use crate::shape::{CachedShapeDrawData, ShapeTextureBinding};
+use crate::vertex::InstanceTransform;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+/// Largest difference, in physical pixels, still treated as the same position.
+const POSITION_TOLERANCE: f32 = 1e-3;
+
+/// Identifies a layer raster. Node translations are left out, so a layer that only moved
+/// still finds its entry.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(super) struct LayerCacheKey {
+    content_hash: u64,
+    physical_size: (u32, u32),
+    scale_factor_bits: u64,
+    fringe_width_bits: u32,
+    texture_format: wgpu::TextureFormat,
+    msaa_sample_count: u32,
+}
+
+/// Allocations whose addresses were hashed. Holding them keeps the addresses from being
+/// reused by different content while the raster is cached.
+#[derive(Default)]
+struct LayerResources {
+    tessellations: Vec<Arc<CachedTessellation>>,
+    bind_groups: Vec<Arc<wgpu::BindGroup>>,
+}
+
+/// A layer subtree's content as of this frame.
+pub(super) struct LayerSignature {
+    pub key: LayerCacheKey,
+    /// Logical translation of every node, in pre-order.
+    translations: Vec<[f32; 2]>,
+    /// Physical-pixel rect containing everything the subtree draws, when that rect is on
+    /// screen and the subtree may be shifted.
+    movable_bounds: Option<[u32; 4]>,
+    effect_ids: Vec<u64>,
+    resources: LayerResources,
+}
+
+pub(super) struct CachedLayer {
+    /// Viewport-sized raster of the subtree.
+    pub texture: PooledTexture,
+    /// Samples `texture` for the group composite pipeline.
+    pub bind_group: wgpu::BindGroup,
+    translations: Vec<[f32; 2]>,
+    movable_bounds: Option<[u32; 4]>,
+    /// Effects run while rasterizing; reloading one discards the raster.
+    pub effect_ids: Vec<u64>,
+    _resources: LayerResources,
+}
+
+pub(super) type LayerCache = FrameCache<LayerCacheKey, Arc<CachedLayer>>;
+
+/// How a cached raster stands in for a layer this frame.
+#[derive(Debug, PartialEq)]
+pub(super) enum LayerReuse {
+    /// The layer did not move.
+    Exact,
+    /// The layer moved by `offset` physical pixels; `region` is the part of the raster,
+    /// as `[x, y, width, height]`, that is still on screen.
+    Shifted {
+        offset: [i32; 2],
+        region: Option<[u32; 4]>,
+    },
+}
+
+impl CachedLayer {
+    pub(super) fn new(
+        signature: LayerSignature,
+        texture: PooledTexture,
+        bind_group: wgpu::BindGroup,
+    ) -> Self {
+        Self {
+            texture,
+            bind_group,
+            translations: signature.translations,
+            movable_bounds: signature.movable_bounds,
+            effect_ids: signature.effect_ids,
+            _resources: signature.resources,
+        }
+    }
+
+    /// The single-sampled texture holding the raster.
+    pub(super) fn raster_texture(&self) -> &wgpu::Texture {
+        self.texture
+            .resolve_texture
+            .as_ref()
+            .unwrap_or(&self.texture.color_texture)
+    }
+
+    /// Whether this raster can be drawn for `signature`, which shares its key.
+    pub(super) fn reuse_for(
+        &self,
+        signature: &LayerSignature,
+        physical_size: (u32, u32),
+        scale_factor: f64,
+    ) -> Option<LayerReuse> {
+        layer_reuse(
+            &self.translations,
+            self.movable_bounds,
+            &signature.translations,
+            physical_size,
+            scale_factor,
+        )
+    }
+}
+
+/// Compares the node translations a raster was drawn with against the current ones. All
+/// nodes must have moved together by whole physical pixels, and the raster must have held
+/// the entire subtree, for it to be shifted.
+fn layer_reuse(
+    cached_translations: &[[f32; 2]],
+    cached_movable_bounds: Option<[u32; 4]>,
+    translations: &[[f32; 2]],
+    physical_size: (u32, u32),
+    scale_factor: f64,
+) -> Option<LayerReuse> {
+    if cached_translations.len() != translations.len() || translations.is_empty() {
+        return None;
+    }
+    let scale_factor = scale_factor as f32;
+    let physical_delta = |index: usize| {
+        [
+            (translations[index][0] - cached_translations[index][0]) * scale_factor,
+            (translations[index][1] - cached_translations[index][1]) * scale_factor,
+        ]
+    };
+    let root_delta = physical_delta(0);
+    let moved_together = (1..translations.len()).all(|index| {
+        let delta = physical_delta(index);
+        (delta[0] - root_delta[0]).abs() <= POSITION_TOLERANCE
+            && (delta[1] - root_delta[1]).abs() <= POSITION_TOLERANCE
+    });
+    if !moved_together {
+        return None;
+    }
+    if root_delta
+        .iter()
+        .all(|component| component.abs() <= POSITION_TOLERANCE)
+    {
+        return Some(LayerReuse::Exact);
+    }
+
+    let bounds = cached_movable_bounds?;
+    let offset = root_delta.map(|component| component.round());
+    if (0..2).any(|axis| (root_delta[axis] - offset[axis]).abs() > POSITION_TOLERANCE) {
+        return None;
+    }
+    let offset = offset.map(|component| component as i32);
+    let visible_span = |start: u32, end: u32, offset: i32, limit: u32| {
+        let start = i64::from(start).max(-i64::from(offset));
+        let end = i64::from(end).min(i64::from(limit) - i64::from(offset));
+        (start < end).then_some((start as u32, (end - start) as u32))
+    };
+    let region = visible_span(bounds[0], bounds[2], offset[0], physical_size.0)
+        .zip(visible_span(
+            bounds[1],
+            bounds[3],
+            offset[1],
+            physical_size.1,
+        ))
+        .map(|((x, width), (y, height))| [x, y, width, height]);
+    Some(LayerReuse::Shifted { offset, region })
+}
+
+fn is_affine(transform: &InstanceTransform) -> bool {
+    transform.col0[3] == 0.0
+        && transform.col1[3] == 0.0
+        && transform.col2[3] == 0.0
+        && transform.col3[3] == 1.0
+}
+
+/// Hashes every component of `transform` except its x and y translation.
+fn hash_transform_without_translation(transform: &InstanceTransform, state: &mut impl Hasher) {
+    for column in [transform.col0, transform.col1, transform.col2] {
+        column.map(f32::to_bits).hash(state);
+    }
+    transform.col3[2].to_bits().hash(state);
+    transform.col3[3].to_bits().hash(state);
+}
+
+impl Renderer<'_> {
+    /// Hints that the subtree rooted at `node_id` rarely changes, so it can be rasterized once
+    /// and composited from that raster on later frames.
+    ///
+    /// The raster is redrawn automatically when anything in the subtree changes: geometry,
+    /// fills, transforms, textures or their contents, effects, or the subtree's structure.
+    /// Geometry is compared by tessellation, so shapes queued without a geometry id redraw the
+    /// layer whenever the queue is rebuilt.
+    /// When all of its nodes only moved by the same whole number of physical pixels, the raster
+    /// is shifted instead, provided the node clips its children and was fully on screen when
+    /// rasterized.
+    ///
+    /// Each layer holds a viewport-sized texture, so reserve this for expensive subtrees. The
+    /// hint is ignored on nodes with a group effect or soft clip, and on subtrees containing
+    /// backdrop effects, since those depend on what is drawn behind them. Like effects, the
+    /// hint is cleared with the draw queue; rasters are kept while consecutive frames use them.
+    ///
+    /// Textures rendered into outside grafo must be reported with
+    /// [`TextureManager::mark_texture_modified`](crate::TextureManager::mark_texture_modified)
+    /// for layers sampling them to be redrawn.
+    pub fn cache_as_layer(&mut self, node_id: usize) -> Result<(), DrawCommandError> {
+        if self.draw_tree.get(node_id).is_none() {
+            return Err(DrawCommandError::InvalidShapeId(node_id));
+        }
+        if !self.layer_nodes.contains(&node_id) {
+            self.layer_nodes.push(node_id);
+        }
+        Ok(())
+    }
+
+    /// Gathers the content of the layer rooted at `node_id`, or `None` when the node cannot be
+    /// cached as a layer this frame.
+    pub(super) fn layer_signature(&self, node_id: usize) -> Option<LayerSignature> {
+        let root = self.draw_tree.get(node_id)?;
+        if self.group_effects.contains_key(&node_id)
+            || (root.soft_clip() && !root.is_leaf())
+            || super::traversal::subtree_has_backdrop_effects(
+                &self.draw_tree,
+                &self.backdrop_effects,
+                node_id,
+            )
+        {
+            return None;
+        }
+
+        let mut hasher = DefaultHasher::new();
+        let mut translations = Vec::new();
+        let mut effect_ids = Vec::new();
+        let mut resources = LayerResources::default();
+        let mut all_affine = true;
+        self.hash_layer_node(
+            node_id,
+            &mut hasher,
+            &mut translations,
+            &mut effect_ids,
+            &mut resources,
+            &mut all_affine,
+        );
+
+        let movable_bounds = (all_affine
+            && root.overflow() == crate::ShapeOverflow::Hidden
+            && !self.shape_effects.contains_key(&node_id))
+        .then(|| self.on_screen_physical_bounds(root))
+        .flatten();
+
+        Some(LayerSignature {
+            key: LayerCacheKey {
+                content_hash: hasher.finish(),
+                physical_size: self.physical_size,
+                scale_factor_bits: self.scale_factor.to_bits(),
+                fringe_width_bits: self.fringe_width.to_bits(),
+                texture_format: self.config.format,
+                msaa_sample_count: self.msaa_sample_count,
+            },
+            translations,
+            movable_bounds,
+            effect_ids,
+            resources,
+        })
+    }
+
+    fn hash_layer_node(
+        &self,
+        node_id: usize,
+        hasher: &mut DefaultHasher,
+        translations: &mut Vec<[f32; 2]>,
+        effect_ids: &mut Vec<u64>,
+        resources: &mut LayerResources,
+        all_affine: &mut bool,
+    ) {
+        let Some(draw_command) = self.draw_tree.get(node_id) else {
+            return;
+        };
+        match draw_command {
+            DrawCommand::CachedShape(shape) => {
+                0u8.hash(hasher);
+                self.hash_layer_shape(shape, hasher, resources);
+            }
+            DrawCommand::ClipRect(clip_rect) => {
+                1u8.hash(hasher);
+                clip_rect
+                    .rect_bounds
+                    .map(|(x, y)| [x.to_bits(), y.to_bits()])
+                    .hash(hasher);
+                let radii = clip_rect.border_radii;
+                [
+                    radii.top_left,
+                    radii.top_right,
+                    radii.bottom_left,
+                    radii.bottom_right,
+                ]
+                .map(f32::to_bits)
+                .hash(hasher);
+                clip_rect.clips_children.hash(hasher);
+                clip_rect.is_leaf.hash(hasher);
+            }
+        }
+
+        let transform = draw_command
+            .transform()
+            .unwrap_or_else(InstanceTransform::identity);
+        hash_transform_without_translation(&transform, hasher);
+        translations.push([transform.col3[0], transform.col3[1]]);
+        *all_affine &= is_affine(&transform);
+
+        if let Some(instance) = self.group_effects.get(&node_id) {
+            instance.effect_id.hash(hasher);
+            instance.params.hash(hasher);
+            effect_ids.push(instance.effect_id);
+        }
+        if let Some(instance) = self.shape_effects.get(&node_id) {
+            instance.effect_id.hash(hasher);
+            instance.params.hash(hasher);
+            let config = instance.config;
+            [
+                config.left_outset,
+                config.top_outset,
+                config.right_outset,
+                config.bottom_outset,
+                config.downsample,
+            ]
+            .map(f32::to_bits)
+            .hash(hasher);
+            effect_ids.push(instance.effect_id);
+        }
+
+        let children = self.draw_tree.children(node_id);
+        children.len().hash(hasher);
+        for &child_id in children {
+            self.hash_layer_node(
+                child_id,
+                hasher,
+                translations,
+                effect_ids,
+                resources,
+                all_affine,
+            );
+        }
+    }
+
+    fn hash_layer_shape(
+        &self,
+        shape: &CachedShapeDrawData,
+        hasher: &mut DefaultHasher,
+        resources: &mut LayerResources,
+    ) {
+        let tessellation = &shape.cached_shape.tessellation;
+        (Arc::as_ptr(tessellation) as usize).hash(hasher);
+        resources.tessellations.push(Arc::clone(tessellation));
+        shape.is_empty.hash(hasher);
+        shape.is_leaf.hash(hasher);
+        shape.overflow.hash(hasher);
+        shape.soft_clip.hash(hasher);
+
+        // Instance data carries the solid color and texture sampling. The draw order is only
+        // relative to the rest of the queue, so it is left out.
+        if let Some(instance_index) = shape.instance_index {
+            if let Some(color) = self.temp_instance_colors.get(instance_index) {
+                bytemuck::bytes_of(color).hash(hasher);
+            }
+            if let Some(metadata) = self.temp_instance_metadata.get(instance_index) {
+                let draw_order_size = std::mem::size_of::<f32>();
+                bytemuck::bytes_of(metadata)[draw_order_size..].hash(hasher);
+            }
+        }
+
+        for binding in &shape.texture_bindings {
+            match binding {
+                ShapeTextureBinding::None => 0u8.hash(hasher),
+                ShapeTextureBinding::Managed {
+                    texture_id,
+                    sampler,
+                } => {
+                    1u8.hash(hasher);
+                    texture_id.hash(hasher);
+                    // A reused layer still shows the texture, though it is not bound.
+                    self.texture_manager.mark_texture_used(*texture_id);
+                    self.texture_manager
+                        .texture_revision(*texture_id)
+                        .hash(hasher);
+                    sampler.hash(hasher);
+                }
+                ShapeTextureBinding::AtlasPage { page_id, sampler } => {
+                    2u8.hash(hasher);
+                    page_id.hash(hasher);
+                    self.texture_manager
+                        .atlas_page_revision(*page_id)
+                        .hash(hasher);
+                    sampler.hash(hasher);
+                }
+                ShapeTextureBinding::Direct {
+                    texture_id,
+                    bind_group,
+                } => {
+                    3u8.hash(hasher);
+                    texture_id.hash(hasher);
+                    (Arc::as_ptr(bind_group) as usize).hash(hasher);
+                    resources.bind_groups.push(Arc::clone(bind_group));
+                }
+            }
+        }
+
+        match &shape.gradient_bind_group {
+            Some(bind_group) => {
+                (Arc::as_ptr(bind_group) as usize).hash(hasher);
+                resources.bind_groups.push(Arc::clone(bind_group));
+            }
+            None => 0usize.hash(hasher),
+        }
+    }
+
+    /// The node's screen rect in physical pixels, padded for antialiasing, when it lies
+    /// entirely within the viewport.
+    fn on_screen_physical_bounds(&self, draw_command: &DrawCommand) -> Option<[u32; 4]> {
+        let [(min_x, min_y), (max_x, max_y)] = transformed_bounds_to_logical_screen_rect(
+            draw_command.local_bounds(),
+            draw_command.transform(),
+        );
+        let scale_factor = self.scale_factor as f32;
+        let padding = 1.0 + self.fringe_width;
+        let bounds = [
+            (min_x * scale_factor - padding).floor(),
+            (min_y * scale_factor - padding).floor(),
+            (max_x * scale_factor + padding).ceil(),
+            (max_y * scale_factor + padding).ceil(),
+        ];
+        let (width, height) = self.physical_size;
+        let on_screen = bounds[0] >= 0.0
+            && bounds[1] >= 0.0
+            && bounds[2] <= width as f32
+            && bounds[3] <= height as f32;
+        on_screen.then(|| bounds.map(|component| component as u32))
+    }
+
+    /// Draws a cached raster moved by `offset` into a fresh pooled texture.
+    pub(super) fn encode_shifted_layer(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        cached_layer: &CachedLayer,
+        offset: [i32; 2],
+        region: Option<[u32; 4]>,
+    ) -> PooledTexture {
+        let (width, height) = self.physical_size;
+        let shifted = self.offscreen_texture_pool.acquire_color_only(
+            &self.device,
+            width,
+            height,
+            self.config.format,
+            1,
+        );
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("shifted_layer_clear"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &shifted.color_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        if let Some([x, y, region_width, region_height]) = region {
+            encoder.copy_texture_to_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture: cached_layer.raster_texture(),
+                    mip_level: 0,
+                    origin: wgpu::Origin3d { x, y, z: 0 },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::TexelCopyTextureInfo {
+                    texture: &shifted.color_texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: x.saturating_add_signed(offset[0]),
+                        y: y.saturating_add_signed(offset[1]),
+                        z: 0,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::Extent3d {
+                    width: region_width,
+                    height: region_height,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+        shifted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unmoved_layers_are_reused_exactly() {
+        let translations = [[10.0, 20.0], [15.0, 25.0]];
+        assert_eq!(
+            layer_reuse(&translations, None, &translations, (100, 100), 2.0),
+            Some(LayerReuse::Exact)
+        );
+    }
+
+    #[test]
+    fn layers_moved_by_whole_pixels_are_shifted_and_clipped_to_the_viewport() {
+        let cached = [[10.0, 20.0], [15.0, 25.0]];
+        let moved = [[40.0, 15.0], [45.0, 20.0]];
+        assert_eq!(
+            layer_reuse(&cached, Some([20, 40, 60, 80]), &moved, (100, 100), 2.0),
+            Some(LayerReuse::Shifted {
+                offset: [60, -10],
+                region: Some([20, 40, 20, 40]),
+            })
+        );
+        let off_screen = [[70.0, 20.0], [75.0, 25.0]];
+        assert_eq!(
+            layer_reuse(
+                &cached,
+                Some([20, 40, 60, 80]),
+                &off_screen,
+                (100, 100),
+                2.0
+            ),
+            Some(LayerReuse::Shifted {
+                offset: [120, 0],
+                region: None,
+            })
+        );
+    }
+
+    #[test]
+    fn layers_are_redrawn_when_they_cannot_be_shifted() {
+        let cached = [[10.0, 20.0], [15.0, 25.0]];
+        let bounds = Some([20, 40, 60, 80]);
+        // Children moved relative to the root.
+        let rearranged = [[10.0, 20.0], [16.0, 25.0]];
+        assert_eq!(
+            layer_reuse(&cached, bounds, &rearranged, (100, 100), 2.0),
+            None
+        );
+        // Subpixel movement would resample the raster.
+        let subpixel = [[10.25, 20.0], [15.25, 25.0]];
+        assert_eq!(
+            layer_reuse(&cached, bounds, &subpixel, (100, 100), 2.0),
+            None
+        );
+        // The raster may not hold the whole subtree.
+        let moved = [[11.0, 20.0], [16.0, 25.0]];
+        assert_eq!(layer_reuse(&cached, None, &moved, (100, 100), 2.0), None);
+    }
+}
@@ -15,6 +15,19 @@ pub struct ShapeEffectCacheMetrics {
     pub collected_masks: u64,
 }
 
+/// Per-frame activity for subtrees cached with [`Renderer::cache_as_layer`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LayerCacheMetrics {
+    /// Layers composited from an unchanged raster.
+    pub hits: u64,
+    /// Layers composited from a raster shifted to their new position.
+    pub shifted_hits: u64,
+    /// Layers rasterized this frame.
+    pub misses: u64,
+    /// Rasters dropped because no layer used them.
+    pub collected: u64,
+}
+
 /// Per-frame pipeline switch counts for diagnosing GPU state-change overhead.
 ///
 /// Each field counts how many times the corresponding `set_pipeline` call was issued
@@ -37,6 +50,9 @@ pub struct PipelineSwitchCounts {
     pub scissor_clips: u32,
     /// Number of stencil-modifying draw passes.
     pub stencil_passes: u32,
+    /// Number of clipping parents that exceeded the 8-bit stencil depth and were
+    /// clipped to their screen-space bounds instead.
+    pub stencil_overflow_fallbacks: u32,
 }
 
 impl PipelineSwitchCounts {
@@ -49,6 +65,7 @@ impl PipelineSwitchCounts {
         self.total_switches += other.total_switches;
         self.scissor_clips += other.scissor_clips;
         self.stencil_passes += other.stencil_passes;
+        self.stencil_overflow_fallbacks += other.stencil_overflow_fallbacks;
     }
 }
 
@@ -274,6 +291,11 @@ impl<'a> Renderer<'a> {
     pub fn last_shape_effect_cache_metrics(&self) -> ShapeEffectCacheMetrics {
         self.last_shape_effect_cache_metrics
     }
+
+    /// Returns layer cache activity for the most recently rendered frame.
+    pub fn last_layer_cache_metrics(&self) -> LayerCacheMetrics {
+        self.last_layer_cache_metrics
+    }
 }
 
 #[cfg(test)]
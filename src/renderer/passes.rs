@@ -1,6 +1,6 @@
 use super::types::{
-    BackdropContext, BackdropSource, BoundTextureState, Buffers, ClipKind, Pipeline,
-    PipelineTracker, Pipelines, TraversalEvent,
+    BackdropContext, BackdropSource, BoundTextureState, Buffers, ClipKind, ClipRectDrawData,
+    Pipeline, PipelineTracker, Pipelines, TraversalEvent, MAX_STENCIL_DEPTH,
 };
 use super::*;
 use crate::pipeline::{
@@ -9,19 +9,24 @@ use crate::pipeline::{
 #[cfg(feature = "render_metrics")]
 use crate::renderer::metrics::{PipelineSwitchCounts, ShapeEffectCacheMetrics};
 use crate::renderer::rect_utils::{
-    intersect_scissor, should_skip_visible_rect_draw, try_scissor_for_rect,
+    compute_scissor_rect, extend_scissor_for_overflow, intersect_scissor,
+    should_skip_visible_rect_draw, try_scissor_for_rect,
 };
 use crate::shape::{CachedShapeDrawData, ShapeTextureBinding};
 use crate::util::GradientCache;
 
-/// Dispatch on `DrawCommand::Shape` / `DrawCommand::CachedShape`, binding the
-/// inner data to `$shape` so the same block can run for both variants.  
+/// Dispatch on `DrawCommand::CachedShape` / synthesized clip-rect stencil geometry, binding
+/// the inner data to `$shape` so the same block can run for both variants.
 /// The block receives `$shape: &mut impl DrawShapeCommand`.
 macro_rules! with_shape_mut {
     ($cmd:expr, $shape:ident => $body:expr) => {
         match $cmd {
             DrawCommand::CachedShape($shape) => $body,
-            DrawCommand::ClipRect(_) => unreachable!("clip rectangles do not own shape geometry"),
+            DrawCommand::ClipRect(ClipRectDrawData {
+                stencil_geometry: Some($shape),
+                ..
+            }) => $body,
+            DrawCommand::ClipRect(_) => unreachable!("clip rectangle has no stencil geometry"),
         }
     };
 }
@@ -207,18 +212,27 @@ fn bind_shape_texture_layers(
 ) {
     for (layer, texture_binding) in texture_bindings.iter().enumerate() {
         let effective_binding = match texture_binding {
-            ShapeTextureBinding::Managed(texture_id)
+            ShapeTextureBinding::Managed { texture_id, .. }
                 if !texture_manager.is_texture_loaded(*texture_id) =>
             {
                 ShapeTextureBinding::None
             }
+            ShapeTextureBinding::AtlasPage { page_id, .. }
+                if !texture_manager.is_atlas_page_loaded(*page_id) =>
+            {
+                ShapeTextureBinding::None
+            }
             texture_binding => texture_binding.clone(),
         };
         if !bound_texture_state.needs_rebind(layer, &effective_binding) {
             continue;
         }
         match &effective_binding {
-            ShapeTextureBinding::Managed(texture_id) => {
+            ShapeTextureBinding::Managed {
+                texture_id,
+                sampler,
+            } => {
+                texture_manager.mark_texture_used(*texture_id);
                 if let Ok(bind_group) = texture_manager.get_or_create_shape_bind_group(
                     if layer == 0 {
                         shape_texture_bind_group_layout_background
@@ -227,6 +241,21 @@ fn bind_shape_texture_layers(
                     },
                     shape_texture_layout_epoch,
                     *texture_id,
+                    *sampler,
+                ) {
+                    render_pass.set_bind_group(1 + layer as u32, &*bind_group, &[]);
+                }
+            }
+            ShapeTextureBinding::AtlasPage { page_id, sampler } => {
+                if let Some(bind_group) = texture_manager.get_or_create_atlas_page_bind_group(
+                    if layer == 0 {
+                        shape_texture_bind_group_layout_background
+                    } else {
+                        shape_texture_bind_group_layout_foreground
+                    },
+                    shape_texture_layout_epoch,
+                    *page_id,
+                    *sampler,
                 ) {
                     render_pass.set_bind_group(1 + layer as u32, &*bind_group, &[]);
                 }
@@ -495,6 +524,44 @@ pub(super) fn handle_leaf_draw_pass<'rp>(
     }
 }
 
+/// Composites a pre-rendered soft-clip subtree through the clipping shape's own geometry.
+/// The AA fringe of the shape scales the subtree, so the clip edge matches the shape's edge.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn handle_soft_clip_composite_pass<'rp>(
+    render_pass: &mut wgpu::RenderPass<'rp>,
+    currently_set_pipeline: &mut PipelineTracker,
+    bound_texture_state: &mut BoundTextureState,
+    stencil_stack: &[u32],
+    shape: &(impl DrawShapeCommand + ?Sized),
+    soft_clip_composite_pipeline: &wgpu::RenderPipeline,
+    subtree_bind_group: &wgpu::BindGroup,
+    pipelines: &Pipelines,
+    buffers: &Buffers,
+) {
+    let Some(index_range) = shape.index_buffer_range() else {
+        return;
+    };
+    if shape.is_empty() {
+        return;
+    }
+
+    render_pass.set_pipeline(soft_clip_composite_pipeline);
+    render_pass.set_bind_group(0, pipelines.and_bind_group, &[]);
+    render_pass.set_bind_group(1, &*pipelines.default_shape_texture_bind_groups[0], &[]);
+    render_pass.set_bind_group(2, &*pipelines.default_shape_texture_bind_groups[1], &[]);
+    render_pass.set_bind_group(3, subtree_bind_group, &[]);
+    currently_set_pipeline.switch_to(Pipeline::None);
+    bound_texture_state.invalidate();
+
+    if !bind_aggregated_geometry_buffers(render_pass, buffers) {
+        return;
+    }
+    bind_instance_buffers(render_pass, shape, buffers);
+
+    let parent_stencil = stencil_stack.last().copied().unwrap_or(0);
+    render_buffer_range_to_texture(index_range, render_pass, parent_stencil);
+}
+
 #[derive(Default)]
 pub(super) struct PendingLeafBatch {
     index_range: (usize, usize),
@@ -694,7 +761,7 @@ fn transform_point_to_logical_screen(
     (homogeneous_x * inverse_w, homogeneous_y * inverse_w)
 }
 
-fn transformed_bounds_to_logical_screen_rect(
+pub(super) fn transformed_bounds_to_logical_screen_rect(
     local_bounds: [(f32, f32); 2],
     transform: Option<InstanceTransform>,
 ) -> [(f32, f32); 2] {
@@ -951,7 +1018,7 @@ fn clear_texture_to_transparent(
 }
 
 #[allow(clippy::too_many_arguments)]
-fn blit_texture_to_texture(
+pub(super) fn blit_texture_to_texture(
     device: &wgpu::Device,
     encoder: &mut wgpu::CommandEncoder,
     pipeline: &wgpu::RenderPipeline,
@@ -959,6 +1026,7 @@ fn blit_texture_to_texture(
     input_view: &wgpu::TextureView,
     output_view: &wgpu::TextureView,
     sampler: &wgpu::Sampler,
+    load_op: wgpu::LoadOp<wgpu::Color>,
     label: &str,
 ) {
     let bind_group = effect::create_texture_sample_bind_group(
@@ -975,7 +1043,7 @@ fn blit_texture_to_texture(
             view: output_view,
             resolve_target: None,
             ops: wgpu::Operations {
-                load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                load: load_op,
                 store: wgpu::StoreOp::Store,
             },
         })],
@@ -1022,6 +1090,36 @@ fn composite_backdrop_foreground_layer(
     render_pass.draw(0..3, 0..1);
 }
 
+/// Bounding scissor for a clipping parent whose stencil clip would exceed [`MAX_STENCIL_DEPTH`].
+fn stencil_overflow_scissor_rect(
+    draw_command: &DrawCommand,
+    scale_factor: f64,
+    physical_size: (u32, u32),
+) -> (u32, u32, u32, u32) {
+    let viewport_scissor = (0, 0, physical_size.0, physical_size.1);
+    let logical_rect = transformed_bounds_to_logical_screen_rect(
+        draw_command.local_bounds(),
+        draw_command.transform(),
+    );
+    if !logical_rect_is_finite(logical_rect) {
+        return viewport_scissor;
+    }
+    compute_scissor_rect(logical_rect, None, scale_factor, physical_size)
+        .map(|scissor_rect| {
+            extend_scissor_for_overflow(scissor_rect, draw_command.overflow(), physical_size)
+        })
+        .unwrap_or(viewport_scissor)
+}
+
+fn report_stencil_overflow(node_id: usize, stencil_overflow_reported: &mut bool) {
+    if !*stencil_overflow_reported {
+        warn!(
+            "Node {node_id} exceeds {MAX_STENCIL_DEPTH} nested stencil clips; clipping its children to its bounds instead"
+        );
+        *stencil_overflow_reported = true;
+    }
+}
+
 /// Unified rendering function for all paths: main scene, effect subtrees,
 /// and behind-group rendering. Processes a flat event list from
 /// `plan_traversal_in_place`, breaking render passes at backdrop effect
@@ -1036,6 +1134,9 @@ fn composite_backdrop_foreground_layer(
 /// stencil buffer state, including scissor-optimized parents that skip
 /// stencil writes. This fixes the stencil mismatch that previously broke
 /// backdrop effects when ancestors used scissor clipping.
+///
+/// The first pass loads the color target with `first_color_load_op`; depth and
+/// stencil always start cleared.
 #[allow(clippy::too_many_arguments)]
 pub(super) fn render_segments(
     draw_tree: &mut easy_tree::Tree<DrawCommand>,
@@ -1049,7 +1150,7 @@ pub(super) fn render_segments(
     color_resolve_target: Option<&wgpu::TextureView>,
     depth_stencil_view: &wgpu::TextureView,
     backdrop_source: Option<BackdropSource<'_>>,
-    clear_first: bool,
+    first_color_load_op: wgpu::LoadOp<wgpu::Color>,
     pipelines: &Pipelines,
     buffers: &Buffers,
     gradient_cache: &mut GradientCache,
@@ -1066,12 +1167,13 @@ pub(super) fn render_segments(
     #[cfg(feature = "render_metrics")] shape_effect_cache_metrics: &mut ShapeEffectCacheMetrics,
 ) {
     let mut event_idx = 0;
-    let mut is_first_segment = clear_first;
+    let mut is_first_segment = true;
     let mut currently_set_pipeline = PipelineTracker::new();
     let mut bound_texture_state = BoundTextureState::default();
     let (width, height) = physical_size;
     let viewport_scissor = (0u32, 0u32, width, height);
     let mut pending_leaf_batch = PendingLeafBatch::default();
+    let mut stencil_overflow_reported = false;
     stencil_stack.clear();
     scissor_stack.clear();
     scissor_stack.push(viewport_scissor);
@@ -1111,7 +1213,7 @@ pub(super) fn render_segments(
                 depth_stencil_view,
                 RenderPassLoadOperations {
                     color_load_op: if is_first_segment {
-                        wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT)
+                        first_color_load_op
                     } else {
                         wgpu::LoadOp::Load
                     },
@@ -1175,7 +1277,24 @@ pub(super) fn render_segments(
                                 pipelines,
                                 buffers,
                             );
-                            if let Some(pipeline) = composite_pipeline {
+                            let soft_clip_shape = draw_tree.get(node_id).filter(|draw_command| {
+                                draw_command.soft_clip() && !draw_command.is_leaf()
+                            });
+                            if let (Some(DrawCommand::CachedShape(shape)), Some(pipeline)) =
+                                (soft_clip_shape, pipelines.soft_clip_composite_pipeline)
+                            {
+                                handle_soft_clip_composite_pass(
+                                    &mut render_pass,
+                                    &mut currently_set_pipeline,
+                                    &mut bound_texture_state,
+                                    stencil_stack,
+                                    shape,
+                                    pipeline,
+                                    result_bind_group,
+                                    pipelines,
+                                    buffers,
+                                );
+                            } else if let Some(pipeline) = composite_pipeline {
                                 let parent_stencil = stencil_stack.last().copied().unwrap_or(0);
                                 render_pass.set_pipeline(pipeline);
                                 render_pass.set_bind_group(0, result_bind_group, &[]);
@@ -1294,19 +1413,74 @@ pub(super) fn render_segments(
                                 // hardware, not by stencil buffer values.
                                 stencil_stack.push(parent_stencil);
                                 clip_kind_stack.push(ClipKind::Scissor);
-                            } else if draw_command.is_clip_rect() {
+                            } else if !draw_command.has_stencil_geometry() {
                                 let parent_stencil = stencil_stack.last().copied().unwrap_or(0);
                                 stencil_stack.push(parent_stencil);
                                 clip_kind_stack.push(ClipKind::NonClipping);
+                            } else if stencil_stack.last().copied().unwrap_or(0)
+                                >= MAX_STENCIL_DEPTH
+                            {
+                                // The 8-bit stencil is exhausted: clip children to the
+                                // parent's screen-space bounds instead of wrapping around.
+                                report_stencil_overflow(node_id, &mut stencil_overflow_reported);
+                                #[cfg(feature = "render_metrics")]
+                                currently_set_pipeline.record_stencil_overflow_fallback();
+                                let current_scissor =
+                                    scissor_stack.last().copied().unwrap_or(viewport_scissor);
+                                let clipped = intersect_scissor(
+                                    current_scissor,
+                                    stencil_overflow_scissor_rect(
+                                        draw_command,
+                                        scale_factor,
+                                        physical_size,
+                                    ),
+                                );
+                                scissor_stack.push(clipped);
+                                render_pass
+                                    .set_scissor_rect(clipped.0, clipped.1, clipped.2, clipped.3);
+
+                                let parent_stencil = stencil_stack.last().copied().unwrap_or(0);
+                                if !draw_command.is_clip_rect() {
+                                    with_shape_mut!(draw_command, shape => {
+                                        *shape.stencil_ref_mut() = Some(parent_stencil);
+                                        if !should_skip_visible_draw {
+                                            handle_leaf_draw_pass(
+                                                &mut render_pass,
+                                                &mut currently_set_pipeline,
+                                                &mut bound_texture_state,
+                                                stencil_stack,
+                                                shape,
+                                                pipelines,
+                                                buffers,
+                                            );
+                                        }
+                                    });
+                                }
+                                stencil_stack.push(parent_stencil);
+                                clip_kind_stack.push(ClipKind::Scissor);
                             } else {
-                                // Fall back to stencil increment.
+                                // Fall back to stencil increment. Single-axis overflow
+                                // draws the shape normally and stencils its band instead.
                                 with_shape_mut!(draw_command, shape => {
+                                    if shape.axis_clip_geometry.is_some()
+                                        && !should_skip_visible_draw
+                                    {
+                                        handle_leaf_draw_pass(
+                                            &mut render_pass,
+                                            &mut currently_set_pipeline,
+                                            &mut bound_texture_state,
+                                            stencil_stack,
+                                            shape,
+                                            pipelines,
+                                            buffers,
+                                        );
+                                    }
                                     handle_increment_pass(
                                         &mut render_pass,
                                         &mut currently_set_pipeline,
                                         &mut bound_texture_state,
                                         stencil_stack,
-                                        shape,
+                                        shape.stencil_clip_shape_mut(),
                                         pipelines,
                                         buffers,
                                     );
@@ -1363,7 +1537,7 @@ pub(super) fn render_segments(
                                             &mut currently_set_pipeline,
                                             &mut bound_texture_state,
                                             stencil_stack,
-                                            shape,
+                                            shape.stencil_clip_shape_mut(),
                                             pipelines,
                                             buffers,
                                         );
@@ -1405,7 +1579,7 @@ pub(super) fn render_segments(
                 depth_stencil_view,
                 RenderPassLoadOperations {
                     color_load_op: if is_first_segment {
-                        wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT)
+                        first_color_load_op
                     } else {
                         wgpu::LoadOp::Load
                     },
@@ -1441,7 +1615,19 @@ pub(super) fn render_segments(
             // Use the RUNTIME stencil stack — this correctly reflects scissor-
             // optimized ancestors that never wrote to the stencil buffer.
             let parent_stencil = stencil_stack.last().copied().unwrap_or(0);
-            let this_stencil = parent_stencil + 1;
+            // At the stencil limit the node draws against its parent's stencil and its
+            // children fall back to a bounds scissor.
+            let stencil_overflow = parent_stencil >= MAX_STENCIL_DEPTH;
+            if stencil_overflow {
+                report_stencil_overflow(backdrop_node_id, &mut stencil_overflow_reported);
+                #[cfg(feature = "render_metrics")]
+                currently_set_pipeline.record_stencil_overflow_fallback();
+            }
+            let this_stencil = if stencil_overflow {
+                parent_stencil
+            } else {
+                parent_stencil + 1
+            };
 
             let mut solid_backdrop_bind_group: Option<wgpu::BindGroup> = None;
             let mut gradient_backdrop_bind_group: Option<wgpu::BindGroup> = None;
@@ -1552,6 +1738,7 @@ pub(super) fn render_segments(
                             &backdrop_capture_texture.color_view,
                             &downsampled_capture_target.color_view,
                             bctx.effect_sampler,
+                            wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
                             "backdrop_capture_downsample",
                         );
                         downsampled_capture_texture = Some(downsampled_capture_target);
@@ -1583,8 +1770,8 @@ pub(super) fn render_segments(
                     );
 
                     let uses_gradient_backdrop = draw_command.has_gradient_fill();
-                    if let DrawCommand::CachedShape(cached_shape) = draw_command {
-                        if uses_gradient_backdrop {
+                    match draw_command {
+                        DrawCommand::CachedShape(cached_shape) if uses_gradient_backdrop => {
                             let gradient_backdrop_material_params_buffer = cached_shape
                                 .prepare_gradient_backdrop_material_params_buffer(
                                     bctx.device,
@@ -1608,7 +1795,8 @@ pub(super) fn render_segments(
                                     bctx.effect_sampler,
                                 )
                                 .cloned();
-                        } else {
+                        }
+                        _ => {
                             let solid_backdrop_material_params_buffer =
                                 effect::prepare_solid_backdrop_material_params_buffer(
                                     bctx.device,
@@ -1672,7 +1860,10 @@ pub(super) fn render_segments(
             }
 
             // Step 1: Stencil-only draw (IncrementClamp at parent_stencil).
-            if let Some(draw_command) = draw_tree.get_mut(backdrop_node_id) {
+            if let Some(draw_command) = draw_tree
+                .get_mut(backdrop_node_id)
+                .filter(|_| !stencil_overflow)
+            {
                 render_pass.set_pipeline(bctx.stencil_only_pipeline);
                 render_pass.set_bind_group(0, pipelines.and_bind_group, &[]);
                 render_pass.set_bind_group(
@@ -1716,6 +1907,9 @@ pub(super) fn render_segments(
             let backdrop_clips_children = draw_tree
                 .get(backdrop_node_id)
                 .is_none_or(|cmd| cmd.clips_children());
+            let backdrop_clips_single_axis = draw_tree
+                .get(backdrop_node_id)
+                .is_some_and(|cmd| cmd.overflow() != ShapeOverflow::Hidden);
 
             // Step 3: Color draw (Equal + Keep).
             if let Some(draw_command) = draw_tree.get_mut(backdrop_node_id) {
@@ -1791,7 +1985,11 @@ pub(super) fn render_segments(
 
                     // Step 4: Decrement stencil when no child traversal should inherit this
                     // node's stencil. Non-leaf clipping nodes keep `this_stencil` until Post.
-                    if backdrop_is_leaf || !backdrop_clips_children {
+                    if !stencil_overflow
+                        && (backdrop_is_leaf
+                            || !backdrop_clips_children
+                            || backdrop_clips_single_axis)
+                    {
                         render_pass.set_pipeline(pipelines.decrementing_pipeline);
                         render_pass.set_bind_group(0, pipelines.decrementing_bind_group, &[]);
                         render_pass.set_bind_group(
@@ -1819,6 +2017,55 @@ pub(super) fn render_segments(
             if backdrop_is_leaf {
                 // Leaf: skip both Pre and Post events.
                 event_idx += 2;
+            } else if backdrop_clips_children && stencil_overflow {
+                let current_scissor = scissor_stack.last().copied().unwrap_or(viewport_scissor);
+                let clipped = draw_tree
+                    .get(backdrop_node_id)
+                    .map(|draw_command| {
+                        intersect_scissor(
+                            current_scissor,
+                            stencil_overflow_scissor_rect(
+                                draw_command,
+                                scale_factor,
+                                physical_size,
+                            ),
+                        )
+                    })
+                    .unwrap_or(current_scissor);
+                scissor_stack.push(clipped);
+                stencil_stack.push(parent_stencil);
+                clip_kind_stack.push(ClipKind::Scissor);
+                event_idx += 1;
+            } else if backdrop_clips_children && backdrop_clips_single_axis {
+                // Single-axis overflow: the node's own stencil was released in step 4, so clip
+                // children to its band with a scissor, or with the band's stencil otherwise.
+                let band_scissor = draw_tree.get(backdrop_node_id).and_then(|draw_command| {
+                    try_scissor_for_rect(draw_command, scale_factor, physical_size)
+                });
+                if let Some(band_scissor) = band_scissor {
+                    let current_scissor = scissor_stack.last().copied().unwrap_or(viewport_scissor);
+                    scissor_stack.push(intersect_scissor(current_scissor, band_scissor));
+                    stencil_stack.push(parent_stencil);
+                    clip_kind_stack.push(ClipKind::Scissor);
+                    #[cfg(feature = "render_metrics")]
+                    currently_set_pipeline.record_scissor_clip();
+                } else {
+                    if let Some(DrawCommand::CachedShape(shape)) =
+                        draw_tree.get_mut(backdrop_node_id)
+                    {
+                        handle_increment_pass(
+                            &mut render_pass,
+                            &mut currently_set_pipeline,
+                            &mut bound_texture_state,
+                            stencil_stack,
+                            shape.stencil_clip_shape_mut(),
+                            pipelines,
+                            buffers,
+                        );
+                    }
+                    clip_kind_stack.push(ClipKind::Stencil);
+                }
+                event_idx += 1;
             } else if backdrop_clips_children {
                 // Non-leaf clipping node: children inherit the backdrop shape's stencil. The
                 // normal Post handler decrements after descendants render.
@@ -1844,9 +2091,15 @@ mod tests {
     use super::{
         capture_size_exceeds_budget, capture_size_exceeds_limits, inflate_logical_rect,
         logical_rect_to_physical_capture_rect, resolve_capture_region_to_viewport,
-        screen_point_to_capture_uv, transform_point_to_logical_screen,
+        screen_point_to_capture_uv, transform_point_to_logical_screen, try_batch_leaf,
+        PendingLeafBatch,
     };
+    use crate::shape::{CachedShapeDrawData, CachedShapeHandle};
+    use crate::util::PoolManager;
     use crate::vertex::InstanceTransform;
+    use crate::{Shape, ShapeDrawCommandOptions, Stroke, TextureSourceRect};
+    use lyon::tessellation::FillTessellator;
+    use std::num::NonZeroUsize;
 
     #[test]
     fn physical_capture_rect_preserves_requested_size_outside_viewport() {
@@ -1944,4 +2197,34 @@ mod tests {
         assert!((bottom_right_uv.0 - (119.5 / 140.0)).abs() < 1e-6);
         assert!((bottom_right_uv.1 - (119.5 / 140.0)).abs() < 1e-6);
     }
+
+    #[test]
+    fn leaves_sampling_different_atlas_regions_share_one_batch() {
+        let mut tessellator = FillTessellator::new();
+        let mut pool = PoolManager::new(NonZeroUsize::new(4).unwrap());
+        let shape_handle = CachedShapeHandle::new(
+            &Shape::rect([(0.0, 0.0), (10.0, 10.0)], Stroke::default()),
+            &mut tessellator,
+            &mut pool,
+            None,
+        );
+        let atlas_leaf = |source_rect, instance_index| {
+            let mut leaf = CachedShapeDrawData::new(
+                shape_handle.clone(),
+                &ShapeDrawCommandOptions::new()
+                    .background_texture_id(7)
+                    .background_texture_source_rect(source_rect),
+            );
+            leaf.index_buffer_range = Some((0, 6));
+            leaf.instance_index = Some(instance_index);
+            leaf
+        };
+        let first_icon = atlas_leaf(TextureSourceRect::Texels([(0, 0), (16, 16)]), 0);
+        let second_icon = atlas_leaf(TextureSourceRect::Uv([(0.5, 0.5), (1.0, 1.0)]), 1);
+
+        let mut batch = PendingLeafBatch::default();
+        assert!(try_batch_leaf(&mut batch, &first_icon, 0));
+        assert!(try_batch_leaf(&mut batch, &second_icon, 0));
+        assert_eq!(batch.instance_count, 2);
+    }
 }
@@ -0,0 +1,94 @@
+//! PNG encoding of rendered frames, behind the `png` feature.
+
+use std::io::Write;
+use std::path::Path;
+
+use super::*;
+use crate::renderer::types::ReadbackError;
+
+/// Logical pixels per inch at a scale factor of 1.0.
+const BASE_DPI: f64 = 96.0;
+const METERS_PER_INCH: f64 = 0.0254;
+
+/// Pixels per meter for the pHYs chunk of a frame rendered at `scale_factor`.
+fn pixels_per_meter(scale_factor: f64) -> u32 {
+    (BASE_DPI * scale_factor / METERS_PER_INCH).round() as u32
+}
+
+fn encode_png(
+    writer: impl Write,
+    (width, height): (u32, u32),
+    scale_factor: f64,
+    srgb: bool,
+    pixels: &[u8],
+) -> Result<(), png::EncodingError> {
+    let mut encoder = png::Encoder::new(writer, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    if srgb {
+        encoder.set_srgb(png::SrgbRenderingIntent::Perceptual);
+    }
+    let pixels_per_meter = pixels_per_meter(scale_factor);
+    encoder.set_pixel_dims(Some(png::PixelDimensions {
+        xppu: pixels_per_meter,
+        yppu: pixels_per_meter,
+        unit: png::Unit::Meter,
+    }));
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(pixels)?;
+    writer.finish()
+}
+
+impl Renderer<'_> {
+    /// Renders the draw queue and writes it to `writer` as a straight-alpha RGBA PNG. The pHYs
+    /// chunk records the scale factor as DPI, with 1.0 mapping to 96 DPI.
+    pub fn render_to_png(&mut self, writer: impl Write) -> Result<(), ReadbackError> {
+        let pixels = self.render_png_pixels()?;
+        encode_png(
+            writer,
+            self.physical_size,
+            self.scale_factor,
+            self.target_encoding().srgb_bytes(),
+            &pixels,
+        )?;
+        Ok(())
+    }
+
+    /// Renders the draw queue into a PNG file at `path`, like [`Self::render_to_png`]. The file
+    /// is only created once the frame has been read back.
+    pub fn save_frame(&mut self, path: impl AsRef<Path>) -> Result<(), ReadbackError> {
+        let pixels = self.render_png_pixels()?;
+        let file = std::fs::File::create(path).map_err(png::EncodingError::from)?;
+        let mut file_writer = std::io::BufWriter::new(file);
+        encode_png(
+            &mut file_writer,
+            self.physical_size,
+            self.scale_factor,
+            self.target_encoding().srgb_bytes(),
+            &pixels,
+        )?;
+        file_writer.flush().map_err(png::EncodingError::from)?;
+        Ok(())
+    }
+
+    fn render_png_pixels(&mut self) -> Result<Vec<u8>, ReadbackError> {
+        let options = ReadbackOptions::new()
+            .format(ReadbackFormat::Rgba8)
+            .alpha(ReadbackAlpha::Straight);
+        let mut pixels = Vec::new();
+        self.try_render_to_buffer(options, &mut pixels)?;
+        Ok(pixels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::pixels_per_meter;
+
+    #[test]
+    fn pixels_per_meter_maps_scale_factor_to_dpi() {
+        assert_eq!(pixels_per_meter(1.0), 3780);
+        assert_eq!(pixels_per_meter(2.0), 7559);
+    }
+}
@@ -2,11 +2,185 @@ use super::types::decide_buffer_sizing;
 use super::*;
 use crate::pipeline::create_buffer_init;
 use crate::vertex::CustomVertex;
+use crate::{
+    ShapeTextureFitMode, TextureAddressMode, YuvColorMatrix, YuvFormat, YuvOptions, YuvRange,
+};
+
+/// Per-fragment sampling behavior of a texture layer, matching the shader's mode bits.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) enum TextureSamplingMode {
+    /// Fit-space UVs outside `[0, 1]` repeat the region's edge texels.
+    #[default]
+    Clamped = 0,
+    /// Fit-space UVs outside `[0, 1]` leave the fill visible.
+    Masked = 1,
+    /// Fit-space UVs count tiles; `fit_params.xy` is the tile pitch over the tile size.
+    Tiled = 2,
+    /// Fit-space UVs measure from each edge in texture units; `fit_params` holds the
+    /// `[left, top, right, bottom]` insets over the texture size.
+    NineSlice = 3,
+}
+
+/// How one texture layer maps the shape's UVs onto texels.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct TextureLayerSampling {
+    /// `[scale_x, scale_y, offset_x, offset_y]` from the shape's UVs into fit space.
+    pub(crate) uv_transform: [f32; 4],
+    /// `[min_u, min_v, max_u, max_v]` of the sampled region in texture UVs.
+    pub(crate) region: [f32; 4],
+    pub(crate) mode: TextureSamplingMode,
+    pub(crate) fit_params: [f32; 4],
+    /// `[u, v]` address modes, emulated in the shader so they also wrap within regions.
+    pub(crate) address_modes: [TextureAddressMode; 2],
+    /// The layer is an R8 coverage mask multiplying the color beneath it.
+    pub(crate) is_mask: bool,
+    /// The layer holds packed YUV planes the shader converts to RGB.
+    pub(crate) yuv: Option<YuvOptions>,
+}
+
+impl TextureLayerSampling {
+    /// The layer's mode, address modes, mask bit, and YUV conversion packed as the shader's
+    /// per-layer flag bits.
+    fn flag_bits(&self) -> u32 {
+        let address_bits = |address_mode| match address_mode {
+            TextureAddressMode::ClampToEdge => 0,
+            TextureAddressMode::Repeat => 1,
+            TextureAddressMode::MirrorRepeat => 2,
+        };
+        self.mode as u32
+            | (address_bits(self.address_modes[0]) << 2)
+            | (address_bits(self.address_modes[1]) << 4)
+            | ((self.is_mask as u32) << 6)
+            | self.yuv.map_or(0, |yuv| {
+                let format_bits = match yuv.format {
+                    YuvFormat::Nv12 => 1,
+                    YuvFormat::I420 => 2,
+                };
+                (format_bits << 7)
+                    | (((yuv.matrix == YuvColorMatrix::Bt709) as u32) << 9)
+                    | (((yuv.range == YuvRange::Full) as u32) << 10)
+            })
+    }
+}
+
+impl Default for TextureLayerSampling {
+    /// Samples the whole texture once across the shape's bounds.
+    fn default() -> Self {
+        Self {
+            uv_transform: [1.0, 1.0, 0.0, 0.0],
+            region: [0.0, 0.0, 1.0, 1.0],
+            mode: TextureSamplingMode::Clamped,
+            fit_params: [0.0; 4],
+            address_modes: [TextureAddressMode::ClampToEdge; 2],
+            is_mask: false,
+            yuv: None,
+        }
+    }
+}
+
+/// Resolves `fit_mode` for a texture region `region_texels` large drawn across `shape_size`
+/// physical pixels. The returned sampling covers the whole region; callers narrow
+/// [`TextureLayerSampling::region`] for source rects and atlas entries. Size-dependent modes
+/// stretch while the region's size is unknown.
+pub(crate) fn fit_texture_layer(
+    fit_mode: ShapeTextureFitMode,
+    shape_size: [f32; 2],
+    region_texels: Option<[f32; 2]>,
+) -> TextureLayerSampling {
+    let stretched = TextureLayerSampling::default();
+    let Some(texels) = region_texels.filter(|texels| texels[0] > 0.0 && texels[1] > 0.0) else {
+        return stretched;
+    };
+
+    match fit_mode {
+        ShapeTextureFitMode::Stretch => stretched,
+        ShapeTextureFitMode::OriginalSize => TextureLayerSampling {
+            uv_transform: [
+                shape_size[0] / texels[0],
+                shape_size[1] / texels[1],
+                0.0,
+                0.0,
+            ],
+            ..stretched
+        },
+        ShapeTextureFitMode::Cover { alignment } | ShapeTextureFitMode::Contain { alignment } => {
+            let is_cover = matches!(fit_mode, ShapeTextureFitMode::Cover { .. });
+            let scale_x = shape_size[0] / texels[0];
+            let scale_y = shape_size[1] / texels[1];
+            let scale = if is_cover {
+                scale_x.max(scale_y)
+            } else {
+                scale_x.min(scale_y)
+            };
+            // Fraction of the scaled texture spanned by the shape on each axis.
+            let span_x = scale_x / scale;
+            let span_y = scale_y / scale;
+            let [align_x, align_y] = alignment.factors();
+            TextureLayerSampling {
+                uv_transform: [
+                    span_x,
+                    span_y,
+                    (1.0 - span_x) * align_x,
+                    (1.0 - span_y) * align_y,
+                ],
+                mode: if is_cover {
+                    TextureSamplingMode::Clamped
+                } else {
+                    TextureSamplingMode::Masked
+                },
+                ..stretched
+            }
+        }
+        ShapeTextureFitMode::Tile { spacing } => {
+            let pitch_x = texels[0] + spacing.0 as f32;
+            let pitch_y = texels[1] + spacing.1 as f32;
+            TextureLayerSampling {
+                uv_transform: [shape_size[0] / pitch_x, shape_size[1] / pitch_y, 0.0, 0.0],
+                mode: TextureSamplingMode::Tiled,
+                fit_params: [pitch_x / texels[0], pitch_y / texels[1], 0.0, 0.0],
+                ..stretched
+            }
+        }
+        ShapeTextureFitMode::NineSlice { insets } => {
+            // Opposing insets never overlap within the texture.
+            let left = (insets.left as f32).min(texels[0]);
+            let right = (insets.right as f32).min(texels[0] - left);
+            let top = (insets.top as f32).min(texels[1]);
+            let bottom = (insets.bottom as f32).min(texels[1] - top);
+            // Corners shrink together when the shape is shorter than both insets.
+            let corner_scale = |length: f32, insets: f32| {
+                if insets > 0.0 {
+                    (length / insets).min(1.0)
+                } else {
+                    1.0
+                }
+            };
+            let corner_scale_x = corner_scale(shape_size[0], left + right);
+            let corner_scale_y = corner_scale(shape_size[1], top + bottom);
+            TextureLayerSampling {
+                uv_transform: [
+                    shape_size[0] / (texels[0] * corner_scale_x),
+                    shape_size[1] / (texels[1] * corner_scale_y),
+                    0.0,
+                    0.0,
+                ],
+                mode: TextureSamplingMode::NineSlice,
+                fit_params: [
+                    left / texels[0],
+                    top / texels[1],
+                    right / texels[0],
+                    bottom / texels[1],
+                ],
+                ..stretched
+            }
+        }
+    }
+}
 
 #[derive(Copy, Clone)]
 pub(crate) struct InstanceTextureData {
     pub(crate) texture_presence: [bool; 2],
-    pub(crate) texture_uv_scales: [[f32; 2]; 2],
+    pub(crate) layers: [TextureLayerSampling; 2],
 }
 
 fn upsert_gpu_buffer(
@@ -97,12 +271,19 @@ pub(crate) fn append_instance_data(
         color: color_override.unwrap_or([0.0, 0.0, 0.0, 0.0]),
     });
     let texture_flags = (texture_data.texture_presence[0] as u32)
-        | ((texture_data.texture_presence[1] as u32) << 1);
+        | ((texture_data.texture_presence[1] as u32) << 1)
+        | (texture_data.layers[0].flag_bits() << 2)
+        | (texture_data.layers[1].flag_bits() << 13);
+    let [layer0, layer1] = texture_data.layers;
     temp_instance_metadata.push(InstanceMetadata {
         draw_order: instance_index as f32,
         texture_flags: texture_flags as f32,
-        texture_uv_scale_layer0: texture_data.texture_uv_scales[0],
-        texture_uv_scale_layer1: texture_data.texture_uv_scales[1],
+        texture_uv_transform_layer0: layer0.uv_transform,
+        texture_uv_transform_layer1: layer1.uv_transform,
+        texture_region_layer0: layer0.region,
+        texture_region_layer1: layer1.region,
+        texture_fit_params_layer0: layer0.fit_params,
+        texture_fit_params_layer1: layer1.fit_params,
     });
     instance_index
 }
@@ -224,3 +405,88 @@ impl<'a> Renderer<'a> {
         self.temp_instance_metadata.truncate(base_instance_count);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NineSliceInsets, TextureAlignment};
+
+    #[test]
+    fn cover_crops_the_long_axis_and_contain_masks_the_short_one() {
+        // A 2:1 shape showing a square texture.
+        let cover = fit_texture_layer(
+            ShapeTextureFitMode::Cover {
+                alignment: TextureAlignment::Center,
+            },
+            [200.0, 100.0],
+            Some([50.0, 50.0]),
+        );
+        assert_eq!(cover.uv_transform, [1.0, 0.5, 0.0, 0.25]);
+        assert_eq!(cover.mode, TextureSamplingMode::Clamped);
+
+        let contain = fit_texture_layer(
+            ShapeTextureFitMode::Contain {
+                alignment: TextureAlignment::Right,
+            },
+            [200.0, 100.0],
+            Some([50.0, 50.0]),
+        );
+        assert_eq!(contain.uv_transform, [2.0, 1.0, -1.0, 0.0]);
+        assert_eq!(contain.mode, TextureSamplingMode::Masked);
+    }
+
+    #[test]
+    fn tile_counts_pitches_and_nine_slice_shrinks_corners_that_do_not_fit() {
+        let tile = fit_texture_layer(
+            ShapeTextureFitMode::Tile { spacing: (10, 0) },
+            [120.0, 40.0],
+            Some([20.0, 20.0]),
+        );
+        assert_eq!(tile.uv_transform, [4.0, 2.0, 0.0, 0.0]);
+        assert_eq!(tile.fit_params, [1.5, 1.0, 0.0, 0.0]);
+
+        let nine_slice = fit_texture_layer(
+            ShapeTextureFitMode::NineSlice {
+                insets: NineSliceInsets::new(4, 8, 4, 8),
+            },
+            [100.0, 4.0],
+            Some([32.0, 16.0]),
+        );
+        assert_eq!(nine_slice.mode, TextureSamplingMode::NineSlice);
+        assert_eq!(nine_slice.fit_params, [0.25, 0.25, 0.25, 0.25]);
+        // Full-size corners horizontally; vertical insets squeezed into 4 pixels at half size.
+        assert_eq!(nine_slice.uv_transform, [3.125, 0.5, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn layer_flag_bits_pack_mode_address_modes_mask_and_yuv() {
+        let sampling = TextureLayerSampling {
+            mode: TextureSamplingMode::Masked,
+            address_modes: [TextureAddressMode::Repeat, TextureAddressMode::MirrorRepeat],
+            is_mask: true,
+            ..TextureLayerSampling::default()
+        };
+        assert_eq!(sampling.flag_bits(), 0b1_10_01_01);
+        assert_eq!(TextureLayerSampling::default().flag_bits(), 0);
+
+        let video = TextureLayerSampling {
+            yuv: Some(
+                YuvOptions::new(YuvFormat::I420)
+                    .matrix(YuvColorMatrix::Bt709)
+                    .range(YuvRange::Full),
+            ),
+            ..TextureLayerSampling::default()
+        };
+        assert_eq!(video.flag_bits(), (2 << 7) | (1 << 9) | (1 << 10));
+    }
+
+    #[test]
+    fn size_dependent_fit_modes_stretch_until_the_texture_is_loaded() {
+        let sampling = fit_texture_layer(
+            ShapeTextureFitMode::Tile { spacing: (0, 0) },
+            [100.0, 100.0],
+            None,
+        );
+        assert_eq!(sampling, TextureLayerSampling::default());
+    }
+}
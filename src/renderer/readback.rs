@@ -1,7 +1,18 @@
 use super::*;
-use crate::pipeline::create_argb_params_buffer;
+use crate::pipeline::{create_argb_params_buffer, encode_copy_texture_region_to_buffer};
 #[cfg(feature = "render_metrics")]
 use crate::renderer::metrics::PhaseTimings;
+use crate::renderer::types::ReadbackError;
+use crate::texture_manager::{linear_to_srgb_u8, srgb_to_linear_u8};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{Context, Poll, Waker};
+
+/// Idle async readback buffers kept for reuse; enough for triple buffering.
+const MAX_IDLE_ASYNC_READBACK_BUFFERS: usize = 3;
+
+pub(super) type AsyncReadbackBuffers = Arc<Mutex<Vec<wgpu::Buffer>>>;
 
 fn copy_padded_readback_rows(
     data: &[u8],
@@ -12,9 +23,24 @@ fn copy_padded_readback_rows(
 ) {
     let output_size = (unpadded_bytes_per_row * height) as usize;
     output.resize(output_size, 0);
+    copy_padded_readback_rows_into_slice(
+        data,
+        height,
+        unpadded_bytes_per_row,
+        padded_bytes_per_row,
+        output,
+    );
+}
 
+fn copy_padded_readback_rows_into_slice(
+    data: &[u8],
+    height: u32,
+    unpadded_bytes_per_row: u32,
+    padded_bytes_per_row: u32,
+    output: &mut [u8],
+) {
     if padded_bytes_per_row == unpadded_bytes_per_row {
-        output.copy_from_slice(data);
+        output.copy_from_slice(&data[..output.len()]);
         return;
     }
 
@@ -27,12 +53,479 @@ fn copy_padded_readback_rows(
     }
 }
 
+/// Sub-rectangle of the frame to read back, in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadbackRegion {
+    pub origin: (u32, u32),
+    pub size: (u32, u32),
+}
+
+impl ReadbackRegion {
+    pub fn new(origin: (u32, u32), size: (u32, u32)) -> Self {
+        Self { origin, size }
+    }
+}
+
+/// Pixel layout written by [`Renderer::try_render_to_buffer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReadbackFormat {
+    /// 4 bytes per pixel in R, G, B, A order, encoded like the render target (sRGB for sRGB
+    /// targets).
+    #[default]
+    Rgba8,
+    /// 4 bytes per pixel in B, G, R, A order, encoded like the render target (sRGB for sRGB
+    /// targets).
+    Bgra8,
+    /// 16 bytes per pixel: native-endian `f32` RGBA in linear light.
+    RgbaF32Linear,
+}
+
+impl ReadbackFormat {
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            ReadbackFormat::Rgba8 | ReadbackFormat::Bgra8 => 4,
+            ReadbackFormat::RgbaF32Linear => 16,
+        }
+    }
+}
+
+/// Whether read back colors are multiplied by alpha, as the renderer stores them, or not.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReadbackAlpha {
+    #[default]
+    Premultiplied,
+    /// Colors are divided by alpha in linear light; fully transparent pixels read as zero.
+    Straight,
+}
+
+/// What [`Renderer::try_render_to_buffer`] reads and how it lays the pixels out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ReadbackOptions {
+    /// The whole frame when `None`.
+    pub region: Option<ReadbackRegion>,
+    pub format: ReadbackFormat,
+    pub alpha: ReadbackAlpha,
+}
+
+impl ReadbackOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn region(mut self, origin: (u32, u32), size: (u32, u32)) -> Self {
+        self.region = Some(ReadbackRegion::new(origin, size));
+        self
+    }
+
+    pub fn format(mut self, format: ReadbackFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn alpha(mut self, alpha: ReadbackAlpha) -> Self {
+        self.alpha = alpha;
+        self
+    }
+}
+
+/// How the render target stores a pixel.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct TargetEncoding {
+    bgra: bool,
+    srgb: bool,
+    /// Linear `f16` RGBA, 8 bytes per texel.
+    half_float: bool,
+    /// Readbacks report alpha as opaque, for headless renderers created with
+    /// [`HeadlessConfig::transparent(false)`](super::HeadlessConfig::transparent).
+    opaque: bool,
+}
+
+impl TargetEncoding {
+    fn of(config: &wgpu::SurfaceConfiguration, opaque: bool) -> Self {
+        Self {
+            bgra: matches!(
+                config.format,
+                wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+            ),
+            srgb: config.format.is_srgb(),
+            half_float: config.format == wgpu::TextureFormat::Rgba16Float,
+            opaque,
+        }
+    }
+
+    fn bytes_per_texel(self) -> u32 {
+        if self.half_float {
+            8
+        } else {
+            4
+        }
+    }
+
+    /// Layout of [`Renderer::render_to_buffer`]: the target's own byte order.
+    fn native_format(self) -> ReadbackFormat {
+        if self.bgra {
+            ReadbackFormat::Bgra8
+        } else {
+            ReadbackFormat::Rgba8
+        }
+    }
+
+    /// Texel layout flags of the ARGB swizzle shader.
+    fn argb_swizzle_flags(self) -> u32 {
+        let rgba_order = !self.bgra && !self.half_float;
+        (rgba_order as u32) | (self.half_float as u32) << 1 | (self.opaque as u32) << 2
+    }
+
+    /// Whether 8-bit readbacks are sRGB encoded; half floats are encoded like an sRGB target.
+    #[cfg(feature = "png")]
+    pub(super) fn srgb_bytes(self) -> bool {
+        self.srgb || self.half_float
+    }
+}
+
+/// Converts tightly packed target pixels into `format` and `alpha`.
+fn convert_readback_pixels(
+    pixels: &mut Vec<u8>,
+    encoding: TargetEncoding,
+    format: ReadbackFormat,
+    alpha: ReadbackAlpha,
+) {
+    if encoding.half_float {
+        convert_half_float_pixels(pixels, encoding.opaque, format, alpha);
+        return;
+    }
+    let straight = alpha == ReadbackAlpha::Straight;
+    let to_linear: [f32; 256] = std::array::from_fn(|value| {
+        if encoding.srgb {
+            srgb_to_linear_u8(value as u8)
+        } else {
+            value as f32 / 255.0
+        }
+    });
+    let rgba_of = |pixel: &[u8]| {
+        if encoding.bgra {
+            [pixel[2], pixel[1], pixel[0], pixel[3]]
+        } else {
+            [pixel[0], pixel[1], pixel[2], pixel[3]]
+        }
+    };
+
+    if format == ReadbackFormat::RgbaF32Linear {
+        let packed = std::mem::take(pixels);
+        pixels.reserve(packed.len() * 4);
+        for pixel in packed.chunks_exact(4) {
+            let [r, g, b, mut a] = rgba_of(pixel);
+            if encoding.opaque {
+                a = 255;
+            }
+            let alpha = a as f32 / 255.0;
+            let mut rgba = [
+                to_linear[r as usize],
+                to_linear[g as usize],
+                to_linear[b as usize],
+                alpha,
+            ];
+            if straight {
+                for channel in &mut rgba[..3] {
+                    *channel = if a == 0 { 0.0 } else { *channel / alpha };
+                }
+            }
+            for channel in rgba {
+                pixels.extend_from_slice(&channel.to_ne_bytes());
+            }
+        }
+        return;
+    }
+
+    let unpremultiply = |channel: u8, a: u8| {
+        if a == 0 {
+            0
+        } else if a == 255 {
+            channel
+        } else if encoding.srgb {
+            linear_to_srgb_u8(to_linear[channel as usize] * 255.0 / a as f32)
+        } else {
+            ((channel as u32 * 255 + a as u32 / 2) / a as u32).min(255) as u8
+        }
+    };
+    let output_bgra = format == ReadbackFormat::Bgra8;
+    if !straight && !encoding.opaque && output_bgra == encoding.bgra {
+        return;
+    }
+    for pixel in pixels.chunks_exact_mut(4) {
+        let [mut r, mut g, mut b, mut a] = rgba_of(pixel);
+        if encoding.opaque {
+            a = 255;
+        }
+        if straight {
+            [r, g, b] = [r, g, b].map(|channel| unpremultiply(channel, a));
+        }
+        let converted = if output_bgra {
+            [b, g, r, a]
+        } else {
+            [r, g, b, a]
+        };
+        pixel.copy_from_slice(&converted);
+    }
+}
+
+/// Converts linear, premultiplied `f16` target pixels into `format` and `alpha`. 8-bit formats
+/// are sRGB encoded.
+fn convert_half_float_pixels(
+    pixels: &mut Vec<u8>,
+    opaque: bool,
+    format: ReadbackFormat,
+    alpha: ReadbackAlpha,
+) {
+    let packed = std::mem::take(pixels);
+    pixels.reserve(packed.len() / 8 * format.bytes_per_pixel());
+    for pixel in packed.chunks_exact(8) {
+        let [r, g, b, a] = std::array::from_fn(|channel| {
+            half::f16::from_le_bytes([pixel[channel * 2], pixel[channel * 2 + 1]]).to_f32()
+        });
+        let a = if opaque { 1.0 } else { a.clamp(0.0, 1.0) };
+        let rgb = match alpha {
+            ReadbackAlpha::Straight if a <= 0.0 => [0.0; 3],
+            ReadbackAlpha::Straight => [r / a, g / a, b / a],
+            ReadbackAlpha::Premultiplied => [r, g, b],
+        };
+        match format {
+            ReadbackFormat::RgbaF32Linear => {
+                for channel in [rgb[0], rgb[1], rgb[2], a] {
+                    pixels.extend_from_slice(&channel.to_ne_bytes());
+                }
+            }
+            ReadbackFormat::Rgba8 | ReadbackFormat::Bgra8 => {
+                let [r, g, b] = rgb.map(linear_to_srgb_u8);
+                let a = (a * 255.0).round() as u8;
+                if format == ReadbackFormat::Bgra8 {
+                    pixels.extend_from_slice(&[b, g, r, a]);
+                } else {
+                    pixels.extend_from_slice(&[r, g, b, a]);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct AsyncMapState {
+    result: Option<Result<(), wgpu::BufferAsyncError>>,
+    waker: Option<Waker>,
+}
+
+/// A frame submitted by [`Renderer::render_to_buffer_async`] or
+/// [`Renderer::render_to_argb32_async`] whose pixels are still on their way back from the GPU.
+///
+/// The copy completes whenever the device is polled: by later renders,
+/// [`Renderer::poll_readbacks`], [`Self::is_ready`], or polling the handle as a future.
+/// Nothing else drives the GPU, so an executor that only waits for a wake-up needs one of
+/// those to happen; [`Self::read_into`] blocks instead. Dropping the handle cancels the readback.
+#[must_use = "dropping a ReadbackHandle discards the frame"]
+pub struct ReadbackHandle {
+    device: Arc<wgpu::Device>,
+    submission: wgpu::SubmissionIndex,
+    buffer: Option<wgpu::Buffer>,
+    idle_buffers: AsyncReadbackBuffers,
+    state: Arc<Mutex<AsyncMapState>>,
+    size: (u32, u32),
+    unpadded_bytes_per_row: u32,
+    padded_bytes_per_row: u32,
+    /// How the frame's texels are stored; `None` for ARGB32 words, which need no conversion.
+    encoding: Option<TargetEncoding>,
+}
+
+impl ReadbackHandle {
+    fn new(
+        device: Arc<wgpu::Device>,
+        submission: wgpu::SubmissionIndex,
+        buffer: wgpu::Buffer,
+        idle_buffers: AsyncReadbackBuffers,
+        size: (u32, u32),
+        (unpadded_bytes_per_row, padded_bytes_per_row): (u32, u32),
+        encoding: Option<TargetEncoding>,
+    ) -> Self {
+        let state = Arc::new(Mutex::new(AsyncMapState::default()));
+        let callback_state = Arc::clone(&state);
+        buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let waker = {
+                    let mut state = callback_state.lock().unwrap();
+                    state.result = Some(result);
+                    state.waker.take()
+                };
+                if let Some(waker) = waker {
+                    waker.wake();
+                }
+            });
+
+        Self {
+            device,
+            submission,
+            buffer: Some(buffer),
+            idle_buffers,
+            state,
+            size,
+            unpadded_bytes_per_row,
+            padded_bytes_per_row,
+            encoding,
+        }
+    }
+
+    /// Physical size of the frame in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Returns `true` once the pixels can be read without blocking. Polls the device without waiting.
+    pub fn is_ready(&self) -> bool {
+        if self.map_result().is_none() {
+            let _ = self.device.poll(wgpu::PollType::Poll);
+        }
+        self.map_result().is_some()
+    }
+
+    /// Copies the frame into `buffer` as tightly packed rows in the layout of
+    /// [`Renderer::render_to_buffer`], waiting for the GPU if the frame isn't ready yet.
+    pub fn read_into(mut self, buffer: &mut Vec<u8>) -> Result<(), ReadbackError> {
+        self.wait()?;
+        self.take_pixels_into(buffer);
+        Ok(())
+    }
+
+    /// Copies a frame from [`Renderer::render_to_argb32_async`] into `out_pixels`,
+    /// waiting for the GPU if the frame isn't ready yet.
+    pub fn read_argb32_into(mut self, out_pixels: &mut [u32]) -> Result<(), ReadbackError> {
+        let (width, height) = self.size;
+        let needed_len = (width as usize) * (height as usize);
+        if out_pixels.len() < needed_len {
+            return Err(ReadbackError::OutputTooSmall {
+                required: needed_len,
+                actual: out_pixels.len(),
+            });
+        }
+
+        self.wait()?;
+        let (unpadded_bytes_per_row, padded_bytes_per_row) =
+            (self.unpadded_bytes_per_row, self.padded_bytes_per_row);
+        self.take_mapped(|data| {
+            copy_padded_readback_rows_into_slice(
+                data,
+                height,
+                unpadded_bytes_per_row,
+                padded_bytes_per_row,
+                bytemuck::cast_slice_mut(&mut out_pixels[..needed_len]),
+            )
+        });
+        Ok(())
+    }
+
+    fn map_result(&self) -> Option<Result<(), wgpu::BufferAsyncError>> {
+        self.state.lock().unwrap().result.clone()
+    }
+
+    fn wait(&self) -> Result<(), ReadbackError> {
+        if self.map_result().is_none() {
+            self.device.poll(wgpu::PollType::WaitForSubmissionIndex(
+                self.submission.clone(),
+            ))?;
+        }
+        Ok(self.map_result().unwrap_or(Err(wgpu::BufferAsyncError))?)
+    }
+
+    fn take_pixels_into(&mut self, pixels: &mut Vec<u8>) {
+        let (_, height) = self.size;
+        let (unpadded_bytes_per_row, padded_bytes_per_row) =
+            (self.unpadded_bytes_per_row, self.padded_bytes_per_row);
+        self.take_mapped(|data| {
+            copy_padded_readback_rows(
+                data,
+                height,
+                unpadded_bytes_per_row,
+                padded_bytes_per_row,
+                pixels,
+            )
+        });
+        if let Some(encoding) = self.encoding {
+            convert_readback_pixels(
+                pixels,
+                encoding,
+                encoding.native_format(),
+                ReadbackAlpha::Premultiplied,
+            );
+        }
+    }
+
+    fn take_mapped(&mut self, copy: impl FnOnce(&[u8])) {
+        let Some(buffer) = self.buffer.take() else {
+            return;
+        };
+        {
+            let mapped_range = buffer.slice(..).get_mapped_range();
+            copy(&mapped_range);
+        }
+        buffer.unmap();
+        self.recycle(buffer);
+    }
+
+    fn recycle(&self, buffer: wgpu::Buffer) {
+        let mut idle_buffers = self.idle_buffers.lock().unwrap();
+        if idle_buffers.len() < MAX_IDLE_ASYNC_READBACK_BUFFERS {
+            idle_buffers.push(buffer);
+        }
+    }
+}
+
+impl Future for ReadbackHandle {
+    type Output = Result<Vec<u8>, ReadbackError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        {
+            let mut state = this.state.lock().unwrap();
+            if state.result.is_none() {
+                state.waker = Some(cx.waker().clone());
+            }
+        }
+        if !this.is_ready() {
+            return Poll::Pending;
+        }
+        if let Some(Err(error)) = this.map_result() {
+            return Poll::Ready(Err(error.into()));
+        }
+
+        let mut pixels = Vec::new();
+        this.take_pixels_into(&mut pixels);
+        Poll::Ready(Ok(pixels))
+    }
+}
+
+impl Drop for ReadbackHandle {
+    fn drop(&mut self) {
+        let Some(buffer) = self.buffer.take() else {
+            return;
+        };
+        // Unmapping aborts a pending map; a failed map already left the buffer unmapped.
+        let map_failed = matches!(self.map_result(), Some(Err(_)));
+        if !map_failed {
+            buffer.unmap();
+        }
+        self.recycle(buffer);
+    }
+}
+
 impl<'a> Renderer<'a> {
+    /// How the render target stores a pixel, and whether readbacks force it opaque.
+    pub(super) fn target_encoding(&self) -> TargetEncoding {
+        TargetEncoding::of(&self.config, self.opaque_readback)
+    }
+
     fn map_readback_buffer_into(
         device: &wgpu::Device,
         buffer: &wgpu::Buffer,
         mapped_bytes: &mut Vec<u8>,
-    ) {
+    ) -> Result<(), ReadbackError> {
         mapped_bytes.clear();
 
         let buffer_slice = buffer.slice(..);
@@ -43,36 +536,30 @@ impl<'a> Renderer<'a> {
             }
         });
 
-        let _ = device.poll(wgpu::MaintainBase::Wait);
+        device.poll(wgpu::MaintainBase::Wait)?;
 
-        let map_result = match receiver.recv() {
-            Ok(result) => result,
-            Err(error) => {
-                warn!("Failed to receive mapped buffer result: {}", error);
-                return;
-            }
-        };
-
-        if let Err(error) = map_result {
-            warn!("Failed to map readback buffer: {:?}", error);
-            return;
-        }
+        // A dropped sender means the callback never ran, which wgpu only does on failure.
+        receiver.recv().unwrap_or(Err(wgpu::BufferAsyncError))?;
 
         let mapped_range = buffer_slice.get_mapped_range();
         mapped_bytes.extend_from_slice(&mapped_range);
         drop(mapped_range);
         buffer.unmap();
+        Ok(())
     }
 
-    pub fn render_to_buffer(&mut self, buffer: &mut Vec<u8>) {
-        #[cfg(feature = "render_metrics")]
-        let frame_render_loop_started_at = std::time::Instant::now();
-
-        self.prepare_render();
-
-        #[cfg(feature = "render_metrics")]
-        let after_prepare = std::time::Instant::now();
+    /// Takes an idle async readback buffer of exactly `size` bytes, or creates one.
+    fn acquire_async_readback_buffer(&self, size: u64) -> wgpu::Buffer {
+        let mut idle_buffers = self.async_readback_buffers.lock().unwrap();
+        idle_buffers.retain(|buffer| buffer.size() == size);
+        idle_buffers.pop().unwrap_or_else(|| {
+            create_readback_buffer(&self.device, Some("async_readback_buffer"), size)
+        })
+    }
 
+    /// Renders the prepared frame into the render-to-buffer offscreen texture.
+    /// Returns whether the physical size changed since the last call.
+    fn render_rtb_offscreen_texture(&mut self) -> bool {
         let (width, height) = self.physical_size;
 
         let size_changed = self.rtb_cached_width != width || self.rtb_cached_height != height;
@@ -99,101 +586,14 @@ impl<'a> Renderer<'a> {
         self.render_to_texture_view(&texture_view, output_texture.as_ref());
         self.rtb_offscreen_texture = output_texture;
 
-        let (unpadded_bytes_per_row, padded_bytes_per_row) = compute_padded_bytes_per_row(width, 4);
-
-        let buffer_size = (padded_bytes_per_row * height) as u64;
-        if size_changed
-            || self
-                .rtb_readback_buffer
-                .as_ref()
-                .map(|existing_buffer| existing_buffer.size() < buffer_size)
-                .unwrap_or(true)
-        {
-            self.rtb_readback_buffer = Some(create_readback_buffer(
-                &self.device,
-                Some("rtb_readback_buffer"),
-                buffer_size,
-            ));
-        }
-
-        let output_buffer = self.rtb_readback_buffer.as_ref().unwrap();
-
-        let mut encoder = self
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("copy_texture_encoder"),
-            });
-
-        encode_copy_texture_to_buffer(
-            &mut encoder,
-            self.rtb_offscreen_texture.as_ref().unwrap(),
-            output_buffer,
-            width,
-            height,
-            padded_bytes_per_row,
-        );
-
-        self.queue.submit(std::iter::once(encoder.finish()));
-
-        #[cfg(feature = "render_metrics")]
-        let after_submit = std::time::Instant::now();
-
-        let mut readback_bytes = std::mem::take(&mut self.scratch.readback_bytes);
-        Self::map_readback_buffer_into(&self.device, output_buffer, &mut readback_bytes);
-        let required_readback_len = (height as usize).saturating_mul(padded_bytes_per_row as usize);
-        if readback_bytes.is_empty() || readback_bytes.len() < required_readback_len {
-            self.scratch.readback_bytes = readback_bytes;
-            return;
-        }
-        copy_padded_readback_rows(
-            &readback_bytes,
-            height,
-            unpadded_bytes_per_row,
-            padded_bytes_per_row,
-            buffer,
-        );
-
-        self.scratch.readback_bytes = readback_bytes;
-
-        #[cfg(feature = "render_metrics")]
-        {
-            let frame_presented_at = std::time::Instant::now();
-            let prepare_dur = after_prepare.saturating_duration_since(frame_render_loop_started_at);
-            let encode_submit_dur = after_submit.saturating_duration_since(after_prepare);
-            let readback_dur = frame_presented_at.saturating_duration_since(after_submit);
-            let total_dur =
-                frame_presented_at.saturating_duration_since(frame_render_loop_started_at);
-            self.last_phase_timings = PhaseTimings {
-                prepare: prepare_dur,
-                encode_and_submit: encode_submit_dur,
-                present_or_readback: readback_dur,
-                gpu_wait: std::time::Duration::ZERO, // readback already includes GPU wait via poll
-                total: total_dur,
-            };
-            self.render_loop_metrics_tracker
-                .record_presented_frame(frame_render_loop_started_at, frame_presented_at);
-        }
+        size_changed
     }
 
-    pub fn render_to_argb32(&mut self, out_pixels: &mut [u32]) {
-        #[cfg(feature = "render_metrics")]
-        let frame_render_loop_started_at = std::time::Instant::now();
-
-        self.prepare_render();
-
-        #[cfg(feature = "render_metrics")]
-        let after_prepare = std::time::Instant::now();
-
+    /// Renders the prepared frame and submits the compute pass that swizzles it into
+    /// `argb_output_storage_buffer`. Returns the output size in bytes and whether the
+    /// physical size changed since the last call.
+    fn render_argb_output_storage_buffer(&mut self) -> (u64, bool) {
         let (width, height) = self.physical_size;
-        let needed_len = (width as usize) * (height as usize);
-        if out_pixels.len() < needed_len {
-            warn!(
-                "render_to_argb32: output slice too small: {} < {}",
-                out_pixels.len(),
-                needed_len
-            );
-            return;
-        }
 
         let size_changed = self.argb_cached_width != width || self.argb_cached_height != height;
         if size_changed {
@@ -219,7 +619,9 @@ impl<'a> Renderer<'a> {
         self.render_to_texture_view(&texture_view, output_texture.as_ref());
         self.argb_offscreen_texture = output_texture;
 
-        let (_, padded_bytes_per_row) = compute_padded_bytes_per_row(width, 4);
+        let encoding = self.target_encoding();
+        let (_, padded_bytes_per_row) =
+            compute_padded_bytes_per_row(width, encoding.bytes_per_texel());
         let input_buffer_size = (padded_bytes_per_row as u64) * (height as u64);
         if size_changed
             || self.argb_input_buffer.is_none()
@@ -260,11 +662,6 @@ impl<'a> Renderer<'a> {
                 output_buffer_size,
             ));
             self.argb_output_buffer_size = output_buffer_size;
-            self.argb_readback_buffer = Some(create_readback_buffer(
-                &self.device,
-                Some("argb_output_u32_readback"),
-                output_buffer_size,
-            ));
         }
 
         if self.argb_cs_pipeline.is_none() {
@@ -277,7 +674,7 @@ impl<'a> Renderer<'a> {
             width,
             height,
             padded_bpr: padded_bytes_per_row,
-            _pad: 0,
+            flags: encoding.argb_swizzle_flags(),
         };
         let needs_new_params = self.argb_params_buffer.is_none();
         if needs_new_params {
@@ -320,6 +717,219 @@ impl<'a> Renderer<'a> {
         }
         self.queue.submit(std::iter::once(compute_encoder.finish()));
 
+        (output_buffer_size, size_changed)
+    }
+
+    /// Renders the draw queue into `buffer` in the target format's byte order, with `Rgba16Float`
+    /// targets encoded as sRGB RGBA8. Targets with an opaque alpha mode read back with alpha 255.
+    /// Failures are logged; use [`Self::try_render_to_buffer`] to handle them.
+    pub fn render_to_buffer(&mut self, buffer: &mut Vec<u8>) {
+        let format = self.target_encoding().native_format();
+        if let Err(error) = self.try_render_to_buffer(ReadbackOptions::new().format(format), buffer)
+        {
+            warn!("render_to_buffer: {}", error);
+        }
+    }
+
+    /// Renders the draw queue and reads the pixels selected by `options` into `buffer`, as
+    /// tightly packed rows. An invalid region is rejected before the draw queue is rendered.
+    pub fn try_render_to_buffer(
+        &mut self,
+        options: ReadbackOptions,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), ReadbackError> {
+        let frame_size = self.physical_size;
+        let region = options
+            .region
+            .unwrap_or(ReadbackRegion::new((0, 0), frame_size));
+        let fits = |start: u32, length: u32, limit: u32| {
+            length > 0 && start.checked_add(length).is_some_and(|end| end <= limit)
+        };
+        if !fits(region.origin.0, region.size.0, frame_size.0)
+            || !fits(region.origin.1, region.size.1, frame_size.1)
+        {
+            return Err(ReadbackError::RegionOutOfBounds {
+                origin: region.origin,
+                size: region.size,
+                frame_size,
+            });
+        }
+
+        #[cfg(feature = "render_metrics")]
+        let frame_render_loop_started_at = std::time::Instant::now();
+
+        self.prepare_render();
+
+        #[cfg(feature = "render_metrics")]
+        let after_prepare = std::time::Instant::now();
+
+        let size_changed = self.render_rtb_offscreen_texture();
+
+        let encoding = self.target_encoding();
+        let (width, height) = region.size;
+        let (unpadded_bytes_per_row, padded_bytes_per_row) =
+            compute_padded_bytes_per_row(width, encoding.bytes_per_texel());
+
+        let buffer_size = (padded_bytes_per_row * height) as u64;
+        if size_changed
+            || self
+                .rtb_readback_buffer
+                .as_ref()
+                .map(|existing_buffer| existing_buffer.size() < buffer_size)
+                .unwrap_or(true)
+        {
+            self.rtb_readback_buffer = Some(create_readback_buffer(
+                &self.device,
+                Some("rtb_readback_buffer"),
+                buffer_size,
+            ));
+        }
+
+        let output_buffer = self.rtb_readback_buffer.as_ref().unwrap();
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("copy_texture_encoder"),
+            });
+
+        encode_copy_texture_region_to_buffer(
+            &mut encoder,
+            self.rtb_offscreen_texture.as_ref().unwrap(),
+            output_buffer,
+            region.origin,
+            region.size,
+            padded_bytes_per_row,
+        );
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        #[cfg(feature = "render_metrics")]
+        let after_submit = std::time::Instant::now();
+
+        let mut readback_bytes = std::mem::take(&mut self.scratch.readback_bytes);
+        let map_result =
+            Self::map_readback_buffer_into(&self.device, output_buffer, &mut readback_bytes);
+        if map_result.is_ok() {
+            copy_padded_readback_rows(
+                &readback_bytes,
+                height,
+                unpadded_bytes_per_row,
+                padded_bytes_per_row,
+                buffer,
+            );
+            convert_readback_pixels(buffer, encoding, options.format, options.alpha);
+        }
+        self.scratch.readback_bytes = readback_bytes;
+        map_result?;
+
+        #[cfg(feature = "render_metrics")]
+        {
+            let frame_presented_at = std::time::Instant::now();
+            let prepare_dur = after_prepare.saturating_duration_since(frame_render_loop_started_at);
+            let encode_submit_dur = after_submit.saturating_duration_since(after_prepare);
+            let readback_dur = frame_presented_at.saturating_duration_since(after_submit);
+            let total_dur =
+                frame_presented_at.saturating_duration_since(frame_render_loop_started_at);
+            self.last_phase_timings = PhaseTimings {
+                prepare: prepare_dur,
+                encode_and_submit: encode_submit_dur,
+                present_or_readback: readback_dur,
+                gpu_wait: std::time::Duration::ZERO, // readback already includes GPU wait via poll
+                total: total_dur,
+            };
+            self.render_loop_metrics_tracker
+                .record_presented_frame(frame_render_loop_started_at, frame_presented_at);
+        }
+
+        Ok(())
+    }
+
+    /// Like [`Self::render_to_buffer`], but returns as soon as the frame is submitted instead
+    /// of waiting for the GPU. Keep two or three handles in flight to encode the next frames
+    /// while earlier ones are still being mapped.
+    pub fn render_to_buffer_async(&mut self) -> ReadbackHandle {
+        self.prepare_render();
+
+        let (width, height) = self.physical_size;
+        self.render_rtb_offscreen_texture();
+
+        let encoding = self.target_encoding();
+        let bytes_per_row = compute_padded_bytes_per_row(width, encoding.bytes_per_texel());
+        let padded_bytes_per_row = bytes_per_row.1;
+        let readback_buffer =
+            self.acquire_async_readback_buffer((padded_bytes_per_row * height) as u64);
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("async_copy_texture_encoder"),
+            });
+
+        encode_copy_texture_to_buffer(
+            &mut encoder,
+            self.rtb_offscreen_texture.as_ref().unwrap(),
+            &readback_buffer,
+            width,
+            height,
+            padded_bytes_per_row,
+        );
+
+        let submission = self.queue.submit(std::iter::once(encoder.finish()));
+        ReadbackHandle::new(
+            Arc::clone(&self.device),
+            submission,
+            readback_buffer,
+            Arc::clone(&self.async_readback_buffers),
+            (width, height),
+            bytes_per_row,
+            Some(encoding),
+        )
+    }
+
+    /// Renders the draw queue into `out_pixels` as `0xAARRGGBB` words. Failures are logged;
+    /// use [`Self::try_render_to_argb32`] to handle them.
+    pub fn render_to_argb32(&mut self, out_pixels: &mut [u32]) {
+        if let Err(error) = self.try_render_to_argb32(out_pixels) {
+            warn!("render_to_argb32: {}", error);
+        }
+    }
+
+    /// Renders the draw queue into `out_pixels` as `0xAARRGGBB` words. A slice that is too
+    /// small is rejected before the draw queue is rendered.
+    pub fn try_render_to_argb32(&mut self, out_pixels: &mut [u32]) -> Result<(), ReadbackError> {
+        let (width, height) = self.physical_size;
+        let needed_len = (width as usize) * (height as usize);
+        if out_pixels.len() < needed_len {
+            return Err(ReadbackError::OutputTooSmall {
+                required: needed_len,
+                actual: out_pixels.len(),
+            });
+        }
+
+        #[cfg(feature = "render_metrics")]
+        let frame_render_loop_started_at = std::time::Instant::now();
+
+        self.prepare_render();
+
+        #[cfg(feature = "render_metrics")]
+        let after_prepare = std::time::Instant::now();
+
+        let (output_buffer_size, size_changed) = self.render_argb_output_storage_buffer();
+        if size_changed
+            || self
+                .argb_readback_buffer
+                .as_ref()
+                .map(|existing_buffer| existing_buffer.size() < output_buffer_size)
+                .unwrap_or(true)
+        {
+            self.argb_readback_buffer = Some(create_readback_buffer(
+                &self.device,
+                Some("argb_output_u32_readback"),
+                output_buffer_size,
+            ));
+        }
+
         let mut readback_encoder =
             self.device
                 .create_command_encoder(&wgpu::CommandEncoderDescriptor {
@@ -339,19 +949,17 @@ impl<'a> Renderer<'a> {
         let after_submit = std::time::Instant::now();
 
         let mut readback_bytes = std::mem::take(&mut self.scratch.readback_bytes);
-        Self::map_readback_buffer_into(
+        let map_result = Self::map_readback_buffer_into(
             &self.device,
             self.argb_readback_buffer.as_ref().unwrap(),
             &mut readback_bytes,
         );
-        if readback_bytes.is_empty() {
-            self.scratch.readback_bytes = readback_bytes;
-            return;
+        if map_result.is_ok() {
+            let src_words: &[u32] = bytemuck::cast_slice(&readback_bytes);
+            out_pixels[..needed_len].copy_from_slice(&src_words[..needed_len]);
         }
-
-        let src_words: &[u32] = bytemuck::cast_slice(&readback_bytes);
-        out_pixels[..needed_len].copy_from_slice(&src_words[..needed_len]);
         self.scratch.readback_bytes = readback_bytes;
+        map_result?;
 
         #[cfg(feature = "render_metrics")]
         {
@@ -371,12 +979,78 @@ impl<'a> Renderer<'a> {
             self.render_loop_metrics_tracker
                 .record_presented_frame(frame_render_loop_started_at, frame_presented_at);
         }
+
+        Ok(())
+    }
+
+    /// Like [`Self::render_to_argb32`], but returns as soon as the frame is submitted.
+    /// Read the pixels with [`ReadbackHandle::read_argb32_into`].
+    pub fn render_to_argb32_async(&mut self) -> ReadbackHandle {
+        self.prepare_render();
+
+        let (width, height) = self.physical_size;
+        let (output_buffer_size, _) = self.render_argb_output_storage_buffer();
+        let readback_buffer = self.acquire_async_readback_buffer(output_buffer_size);
+
+        let mut readback_encoder =
+            self.device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("async_argb_readback_copy_encoder"),
+                });
+        readback_encoder.copy_buffer_to_buffer(
+            self.argb_output_storage_buffer.as_ref().unwrap(),
+            0,
+            &readback_buffer,
+            0,
+            output_buffer_size,
+        );
+
+        let submission = self
+            .queue
+            .submit(std::iter::once(readback_encoder.finish()));
+        ReadbackHandle::new(
+            Arc::clone(&self.device),
+            submission,
+            readback_buffer,
+            Arc::clone(&self.async_readback_buffers),
+            (width, height),
+            (width * 4, width * 4),
+            None,
+        )
+    }
+
+    /// Completes any finished async readbacks without blocking.
+    pub fn poll_readbacks(&self) {
+        let _ = self.device.poll(wgpu::PollType::Poll);
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::copy_padded_readback_rows;
+    use super::{
+        convert_readback_pixels, copy_padded_readback_rows, ReadbackAlpha, ReadbackFormat,
+        TargetEncoding,
+    };
+    use crate::texture_manager::linear_to_srgb_u8;
+
+    const BGRA_SRGB: TargetEncoding = TargetEncoding {
+        bgra: true,
+        srgb: true,
+        half_float: false,
+        opaque: false,
+    };
+    const RGBA_UNORM: TargetEncoding = TargetEncoding {
+        bgra: false,
+        srgb: false,
+        half_float: false,
+        opaque: false,
+    };
+    const RGBA_HALF_FLOAT: TargetEncoding = TargetEncoding {
+        bgra: false,
+        srgb: false,
+        half_float: true,
+        opaque: false,
+    };
 
     #[test]
     fn copy_padded_readback_rows_handles_unpadded_data() {
@@ -395,4 +1069,128 @@ mod tests {
         copy_padded_readback_rows(&data, 2, 4, 8, &mut output);
         assert_eq!(output, vec![1, 2, 3, 4, 5, 6, 7, 8]);
     }
+
+    #[test]
+    fn convert_readback_pixels_swizzles_premultiplied_bytes() {
+        let mut pixels = vec![1, 2, 3, 4];
+        convert_readback_pixels(
+            &mut pixels,
+            BGRA_SRGB,
+            ReadbackFormat::Rgba8,
+            ReadbackAlpha::Premultiplied,
+        );
+        assert_eq!(pixels, vec![3, 2, 1, 4]);
+
+        convert_readback_pixels(
+            &mut pixels,
+            RGBA_UNORM,
+            ReadbackFormat::Rgba8,
+            ReadbackAlpha::Premultiplied,
+        );
+        assert_eq!(pixels, vec![3, 2, 1, 4]);
+    }
+
+    #[test]
+    fn convert_readback_pixels_unpremultiplies_in_linear_light() {
+        let half_red = linear_to_srgb_u8(128.0 / 255.0);
+        let mut pixels = vec![0, 0, half_red, 128, 9, 9, 9, 0];
+        convert_readback_pixels(
+            &mut pixels,
+            BGRA_SRGB,
+            ReadbackFormat::Bgra8,
+            ReadbackAlpha::Straight,
+        );
+        assert_eq!(pixels, vec![0, 0, 255, 128, 0, 0, 0, 0]);
+
+        let mut pixels = vec![100, 0, 0, 200];
+        convert_readback_pixels(
+            &mut pixels,
+            RGBA_UNORM,
+            ReadbackFormat::Rgba8,
+            ReadbackAlpha::Straight,
+        );
+        assert_eq!(pixels, vec![128, 0, 0, 200]);
+    }
+
+    #[test]
+    fn convert_readback_pixels_expands_to_linear_floats() {
+        let to_floats = |bytes: &[u8]| -> Vec<f32> {
+            bytes
+                .chunks_exact(4)
+                .map(|channel| f32::from_ne_bytes(channel.try_into().unwrap()))
+                .collect()
+        };
+
+        let mut pixels = vec![51, 0, 255, 255];
+        convert_readback_pixels(
+            &mut pixels,
+            RGBA_UNORM,
+            ReadbackFormat::RgbaF32Linear,
+            ReadbackAlpha::Premultiplied,
+        );
+        assert_eq!(to_floats(&pixels), vec![0.2, 0.0, 1.0, 1.0]);
+
+        let mut pixels = vec![51, 0, 0, 51, 7, 7, 7, 0];
+        convert_readback_pixels(
+            &mut pixels,
+            RGBA_UNORM,
+            ReadbackFormat::RgbaF32Linear,
+            ReadbackAlpha::Straight,
+        );
+        assert_eq!(
+            to_floats(&pixels),
+            vec![1.0, 0.0, 0.0, 0.2, 0.0, 0.0, 0.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn convert_readback_pixels_encodes_half_floats_as_srgb() {
+        let half_float_pixel = |rgba: [f32; 4]| -> Vec<u8> {
+            rgba.iter()
+                .flat_map(|channel| half::f16::from_f32(*channel).to_le_bytes())
+                .collect()
+        };
+        let mut pixels = [
+            half_float_pixel([0.25, 0.0, 0.5, 0.5]),
+            half_float_pixel([2.0, 0.0, 0.0, 1.0]),
+        ]
+        .concat();
+        convert_readback_pixels(
+            &mut pixels,
+            RGBA_HALF_FLOAT,
+            ReadbackFormat::Bgra8,
+            ReadbackAlpha::Straight,
+        );
+        let half_red = linear_to_srgb_u8(0.5);
+        assert_eq!(pixels, vec![255, 0, half_red, 128, 0, 0, 255, 255]);
+
+        let mut pixels = half_float_pixel([0.25, 0.0, 0.5, 0.5]);
+        convert_readback_pixels(
+            &mut pixels,
+            RGBA_HALF_FLOAT,
+            ReadbackFormat::RgbaF32Linear,
+            ReadbackAlpha::Premultiplied,
+        );
+        let floats: Vec<f32> = pixels
+            .chunks_exact(4)
+            .map(|channel| f32::from_ne_bytes(channel.try_into().unwrap()))
+            .collect();
+        assert_eq!(floats, vec![0.25, 0.0, 0.5, 0.5]);
+    }
+
+    #[test]
+    fn convert_readback_pixels_reports_opaque_targets_as_opaque() {
+        let opaque = TargetEncoding {
+            opaque: true,
+            ..BGRA_SRGB
+        };
+        let mut pixels = vec![1, 2, 3, 4, 0, 0, 0, 0];
+        convert_readback_pixels(
+            &mut pixels,
+            opaque,
+            ReadbackFormat::Bgra8,
+            ReadbackAlpha::Straight,
+        );
+        assert_eq!(pixels, vec![1, 2, 3, 255, 0, 0, 0, 255]);
+    }
 }
@@ -1,6 +1,7 @@
 use super::types::DrawCommand;
 use crate::effect::EffectInstance;
-use crate::vertex::InstanceTransform;
+use crate::vertex::{CustomVertex, InstanceTransform};
+use crate::ShapeOverflow;
 use ahash::HashMap;
 
 #[derive(Clone, Copy)]
@@ -46,7 +47,7 @@ pub(super) fn should_skip_visible_rect_draw(
         return false;
     }
 
-    if draw_command.texture_id(0).is_some() || draw_command.texture_id(1).is_some() {
+    if draw_command.has_managed_texture(0) || draw_command.has_managed_texture(1) {
         return false;
     }
 
@@ -133,14 +134,118 @@ pub(super) fn try_scissor_for_rect(
     if !draw_command.is_rect() {
         return None;
     }
+    let overflow = draw_command.overflow();
     let rect_bounds = draw_command.rect_bounds()?;
     let transform = draw_command.transform();
-    compute_scissor_rect(rect_bounds, transform, scale_factor, physical_size)
+    let scissor_rect = compute_scissor_rect(rect_bounds, transform, scale_factor, physical_size)?;
+    Some(extend_scissor_for_overflow(
+        scissor_rect,
+        overflow,
+        physical_size,
+    ))
+}
+
+/// Widen a scissor rect to the viewport along the axis that `overflow` leaves unclipped.
+pub(super) fn extend_scissor_for_overflow(
+    scissor_rect: (u32, u32, u32, u32),
+    overflow: ShapeOverflow,
+    physical_size: (u32, u32),
+) -> (u32, u32, u32, u32) {
+    let (x, y, width, height) = scissor_rect;
+    match overflow {
+        ShapeOverflow::ClipX => (x, 0, width, physical_size.1),
+        ShapeOverflow::ClipY => (0, y, physical_size.0, height),
+        ShapeOverflow::Hidden | ShapeOverflow::Visible => scissor_rect,
+    }
+}
+
+/// Local-space bounds of the logical screen rect `screen_rect` under `transform`.
+///
+/// Returns `None` when the transform is singular or part of the rect lies beyond the horizon
+/// of a perspective transform, where no finite local rect covers it.
+pub(super) fn screen_rect_to_local_bounds(
+    screen_rect: [(f32, f32); 2],
+    transform: Option<InstanceTransform>,
+) -> Option<[(f32, f32); 2]> {
+    let transform = transform.unwrap_or_else(InstanceTransform::identity);
+    // Rows of the homography mapping local (x, y, 1) to homogeneous screen (x, y, w).
+    let [a, b, _, g] = transform.col0;
+    let [c, d, _, h] = transform.col1;
+    let [e, f, _, i] = transform.col3;
+    let adjugate = [
+        [d * i - f * h, e * h - c * i, c * f - e * d],
+        [f * g - b * i, a * i - e * g, e * b - a * f],
+        [b * h - d * g, c * g - a * h, a * d - c * b],
+    ];
+    let determinant = a * adjugate[0][0] + c * adjugate[1][0] + e * adjugate[2][0];
+    if determinant == 0.0 || !determinant.is_finite() {
+        return None;
+    }
+
+    let corners = [
+        (screen_rect[0].0, screen_rect[0].1),
+        (screen_rect[1].0, screen_rect[0].1),
+        (screen_rect[1].0, screen_rect[1].1),
+        (screen_rect[0].0, screen_rect[1].1),
+    ];
+    let mut min = (f32::INFINITY, f32::INFINITY);
+    let mut max = (f32::NEG_INFINITY, f32::NEG_INFINITY);
+    for (x, y) in corners {
+        let [local_x, local_y, local_w] = adjugate.map(|row| row[0] * x + row[1] * y + row[2]);
+        // The adjugate yields `determinant / w` here, so its sign tells which side of the
+        // horizon the corner maps to.
+        if local_w * determinant <= 0.0 {
+            return None;
+        }
+        let point = (local_x / local_w, local_y / local_w);
+        min = (min.0.min(point.0), min.1.min(point.1));
+        max = (max.0.max(point.0), max.1.max(point.1));
+    }
+    let bounds = [min, max];
+    bounds
+        .iter()
+        .all(|(x, y)| x.is_finite() && y.is_finite())
+        .then_some(bounds)
+}
+
+/// Intervals that the triangles of `indices` cover along `axis` (0 for x, 1 for y), sorted
+/// and with overlapping intervals merged.
+pub(super) fn swept_intervals(
+    vertices: &[CustomVertex],
+    indices: &[u16],
+    axis: usize,
+) -> Vec<(f32, f32)> {
+    let mut intervals: Vec<(f32, f32)> = indices
+        .chunks_exact(3)
+        .filter_map(|triangle| {
+            let (start, end) = triangle.iter().fold(
+                (f32::INFINITY, f32::NEG_INFINITY),
+                |(start, end), &index| {
+                    let coordinate = vertices[index as usize].position[axis];
+                    (start.min(coordinate), end.max(coordinate))
+                },
+            );
+            (start < end).then_some((start, end))
+        })
+        .collect();
+    intervals.sort_by(|left, right| left.0.total_cmp(&right.0));
+
+    let mut merged: Vec<(f32, f32)> = Vec::with_capacity(intervals.len());
+    for (start, end) in intervals {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{compute_scissor_rect, should_skip_visible_rect_draw, try_scissor_for_rect};
+    use super::{
+        compute_scissor_rect, screen_rect_to_local_bounds, should_skip_visible_rect_draw,
+        swept_intervals, try_scissor_for_rect,
+    };
     use crate::effect::EffectInstance;
     use crate::gradient::types::{
         ColorInterpolation, Fill, Gradient, GradientStop, GradientStopOffset, LinearGradientDesc,
@@ -150,7 +255,8 @@ mod tests {
     use crate::shape::CachedShapeDrawData;
     use crate::util::PoolManager;
     use crate::{
-        CachedShapeHandle, Color, Shape, ShapeDrawCommandOptions, Stroke, TransformInstance,
+        BorderRadii, CachedShapeHandle, Color, Shape, ShapeDrawCommandOptions, ShapeOverflow,
+        Stroke, TransformInstance,
     };
     use ahash::{HashMap, HashMapExt};
     use lyon::tessellation::FillTessellator;
@@ -215,6 +321,51 @@ mod tests {
         assert!(try_scissor_for_rect(&draw_command, 1.0, (100, 100)).is_none());
     }
 
+    #[test]
+    fn single_axis_overflow_extends_scissor_across_the_free_axis() {
+        let clip_x = rect_draw_command_with_options(
+            ShapeDrawCommandOptions::new()
+                .overflow(ShapeOverflow::ClipX)
+                .transform(TransformInstance::translation(20.0, 30.0)),
+        );
+        let clip_y = rect_draw_command_with_options(
+            ShapeDrawCommandOptions::new()
+                .overflow(ShapeOverflow::ClipY)
+                .transform(TransformInstance::translation(20.0, 30.0)),
+        );
+
+        assert_eq!(
+            try_scissor_for_rect(&clip_x, 1.0, (100, 80)),
+            Some((20, 0, 10, 80))
+        );
+        assert_eq!(
+            try_scissor_for_rect(&clip_y, 1.0, (100, 80)),
+            Some((0, 30, 100, 10))
+        );
+    }
+
+    #[test]
+    fn single_axis_overflow_leaves_non_rect_shapes_to_the_stencil() {
+        let mut tessellator = FillTessellator::new();
+        let mut pool = PoolManager::new(NonZeroUsize::new(4).unwrap());
+        let shape_handle = CachedShapeHandle::new(
+            &Shape::rounded_rect(
+                [(10.0, 10.0), (40.0, 20.0)],
+                BorderRadii::new(4.0),
+                Stroke::default(),
+            ),
+            &mut tessellator,
+            &mut pool,
+            None,
+        );
+        let draw_command = DrawCommand::CachedShape(CachedShapeDrawData::new(
+            shape_handle,
+            &ShapeDrawCommandOptions::new().overflow(ShapeOverflow::ClipX),
+        ));
+
+        assert_eq!(try_scissor_for_rect(&draw_command, 1.0, (100, 80)), None);
+    }
+
     #[test]
     fn skip_visible_rect_draw_rejects_effect_nodes() {
         let draw_command = rect_draw_command();
@@ -320,4 +471,52 @@ mod tests {
             &HashMap::new(),
         ));
     }
+
+    #[test]
+    fn screen_rect_maps_back_through_scale_and_rejects_the_perspective_horizon() {
+        let transform = TransformInstance::scale(0.001, 0.001)
+            .then(&TransformInstance::translation(10.0, 20.0));
+        let bounds = screen_rect_to_local_bounds([(0.0, 0.0), (100.0, 100.0)], Some(transform))
+            .expect("scale transforms are invertible");
+        assert!((bounds[0].0 + 10_000.0).abs() < 1.0);
+        assert!((bounds[0].1 + 20_000.0).abs() < 1.0);
+        assert!((bounds[1].0 - 90_000.0).abs() < 1.0);
+        assert!((bounds[1].1 - 80_000.0).abs() < 1.0);
+
+        let mut perspective = TransformInstance::identity();
+        perspective.col0[3] = 0.02;
+        assert_eq!(
+            screen_rect_to_local_bounds([(0.0, 0.0), (100.0, 100.0)], Some(perspective)),
+            None
+        );
+    }
+
+    #[test]
+    fn swept_intervals_merge_touching_triangles_and_keep_gaps() {
+        let mut tessellator = FillTessellator::new();
+        let mut pool = PoolManager::new(NonZeroUsize::new(4).unwrap());
+        let shape = Shape::builder()
+            .begin((0.0, 0.0))
+            .line_to((10.0, 0.0))
+            .line_to((10.0, 5.0))
+            .line_to((0.0, 5.0))
+            .close()
+            .begin((20.0, 0.0))
+            .line_to((30.0, 0.0))
+            .line_to((30.0, 5.0))
+            .line_to((20.0, 5.0))
+            .close()
+            .build();
+        let shape_handle = CachedShapeHandle::new(&shape, &mut tessellator, &mut pool, None);
+        let vertex_buffers = shape_handle.vertex_buffers();
+
+        assert_eq!(
+            swept_intervals(&vertex_buffers.vertices, &vertex_buffers.indices, 0),
+            vec![(0.0, 10.0), (20.0, 30.0)]
+        );
+        assert_eq!(
+            swept_intervals(&vertex_buffers.vertices, &vertex_buffers.indices, 1),
+            vec![(0.0, 5.0)]
+        );
+    }
 }
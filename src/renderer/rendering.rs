@@ -1,10 +1,18 @@
+use super::types::{DrawCommandError, RenderToTextureError};
 use super::*;
+use crate::pipeline::BackdropSamplingUniform;
+use crate::renderer::layers::{CachedLayer, LayerReuse};
 #[cfg(feature = "render_metrics")]
-use crate::renderer::metrics::{PhaseTimings, PipelineSwitchCounts, ShapeEffectCacheMetrics};
-use crate::renderer::passes::{apply_effect_passes, render_segments, EffectPassRunConfig};
+use crate::renderer::metrics::{
+    LayerCacheMetrics, PhaseTimings, PipelineSwitchCounts, ShapeEffectCacheMetrics,
+};
+use crate::renderer::passes::{
+    apply_effect_passes, blit_texture_to_texture, render_segments, EffectPassRunConfig,
+};
 use crate::renderer::traversal::{
-    compute_node_depth, plan_traversal_in_place, subtree_has_backdrop_effects,
+    compute_node_depth, is_in_subtree, plan_traversal_in_place, subtree_has_backdrop_effects,
 };
+use crate::texture_manager::TextureManagerError;
 
 impl<'a> Renderer<'a> {
     pub(super) fn render_to_texture_view(
@@ -13,12 +21,44 @@ impl<'a> Renderer<'a> {
         output_texture: Option<&wgpu::Texture>,
     ) {
         let render_to_texture_view_started_at = std::time::Instant::now();
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Command Encoder"),
+            });
+        self.encode_frame(
+            &mut encoder,
+            texture_view,
+            output_texture,
+            wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+            None,
+        );
+        self.queue.submit(std::iter::once(encoder.finish()));
+        self.last_render_to_texture_view_cpu_time = render_to_texture_view_started_at.elapsed();
+    }
 
+    /// Records the prepared draw queue into `encoder`, drawing into `texture_view`.
+    ///
+    /// `output_texture` is the texture behind `texture_view`; backdrop effects capture from
+    /// it, so it is required when any are present. The first pass loads the target with
+    /// `color_load_op`.
+    ///
+    /// With `subtree_root`, only that node and its descendants are drawn, and per-frame cache
+    /// bookkeeping is left to the next full frame.
+    pub(super) fn encode_frame(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        texture_view: &wgpu::TextureView,
+        output_texture: Option<&wgpu::Texture>,
+        color_load_op: wgpu::LoadOp<wgpu::Color>,
+        subtree_root: Option<usize>,
+    ) {
         // Nothing to render when the draw queue is empty.
         if self.draw_tree.is_empty() {
             self.scratch.shape_effect_leaves.clear();
             let _collected_shape_effect_results = self.shape_effect_cache.end_frame();
             let _collected_shape_effect_masks = self.shape_effect_mask_cache.end_frame();
+            let _collected_layers = self.layer_cache.end_frame();
             #[cfg(feature = "render_metrics")]
             {
                 self.last_shape_effect_cache_metrics = ShapeEffectCacheMetrics {
@@ -26,9 +66,13 @@ impl<'a> Renderer<'a> {
                     collected_masks: _collected_shape_effect_masks as u64,
                     ..Default::default()
                 };
+                self.last_layer_cache_metrics = LayerCacheMetrics {
+                    collected: _collected_layers as u64,
+                    ..Default::default()
+                };
             }
             self.buffers_pool_manager.tessellation_cache.end_frame();
-            self.last_render_to_texture_view_cpu_time = render_to_texture_view_started_at.elapsed();
+            self.texture_manager.end_frame();
             return;
         }
 
@@ -47,13 +91,23 @@ impl<'a> Renderer<'a> {
         let has_group_effects = !self.group_effects.is_empty();
         let has_backdrop_effects = !self.backdrop_effects.is_empty();
         let has_shape_effects = !self.shape_effects.is_empty();
+        let has_soft_clips = !self.soft_clip_nodes.is_empty();
+        let has_layers = !self.layer_nodes.is_empty();
 
-        if has_group_effects || has_backdrop_effects {
+        if has_group_effects || has_backdrop_effects || has_layers {
             self.ensure_composite_pipeline();
         }
-        if has_group_effects || has_backdrop_effects || has_shape_effects {
+        if has_group_effects
+            || has_backdrop_effects
+            || has_shape_effects
+            || has_soft_clips
+            || has_layers
+        {
             self.ensure_effect_sampler();
         }
+        if has_soft_clips {
+            self.ensure_soft_clip_composite_pipeline();
+        }
         if has_backdrop_effects {
             self.ensure_texture_blit_pipeline();
             self.ensure_backdrop_layer_composite_pipeline();
@@ -71,16 +125,68 @@ impl<'a> Renderer<'a> {
         let mut frame_pipeline_counts = PipelineSwitchCounts::default();
         #[cfg(feature = "render_metrics")]
         let mut shape_effect_cache_metrics = ShapeEffectCacheMetrics::default();
-
-        let mut encoder = self
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("Render Command Encoder"),
+        #[cfg(feature = "render_metrics")]
+        let mut layer_cache_metrics = LayerCacheMetrics::default();
+
+        // Composite unchanged layers from their rasters; the rest are rasterized below
+        // alongside group effects.
+        let mut layer_misses = HashMap::new();
+        let mut reused_layer_roots = Vec::new();
+        for index in 0..self.layer_nodes.len() {
+            let node_id = self.layer_nodes[index];
+            if subtree_root.is_some_and(|root_id| !is_in_subtree(&self.draw_tree, node_id, root_id))
+            {
+                continue;
+            }
+            let Some(signature) = self.layer_signature(node_id) else {
+                continue;
+            };
+            let cached_layer = self.layer_cache.get(&signature.key);
+            let reuse = cached_layer.as_ref().and_then(|cached_layer| {
+                cached_layer.reuse_for(&signature, self.physical_size, self.scale_factor)
             });
+            match (cached_layer, reuse) {
+                (Some(cached_layer), Some(LayerReuse::Exact)) => {
+                    effect_results.insert(node_id, cached_layer.bind_group.clone());
+                    reused_layer_roots.push(node_id);
+                    #[cfg(feature = "render_metrics")]
+                    {
+                        layer_cache_metrics.hits += 1;
+                    }
+                }
+                (Some(cached_layer), Some(LayerReuse::Shifted { offset, region })) => {
+                    let shifted = self.encode_shifted_layer(encoder, &cached_layer, offset, region);
+                    effect_results.insert(
+                        node_id,
+                        effect::create_texture_sample_bind_group(
+                            &self.device,
+                            self.composite_bgl.as_ref().unwrap(),
+                            &shifted.color_view,
+                            self.effect_sampler.as_ref().unwrap(),
+                            Some("shifted_layer_bind_group"),
+                        ),
+                    );
+                    textures_to_recycle.push(shifted);
+                    reused_layer_roots.push(node_id);
+                    #[cfg(feature = "render_metrics")]
+                    {
+                        layer_cache_metrics.shifted_hits += 1;
+                    }
+                }
+                _ => {
+                    layer_misses.insert(node_id, signature);
+                }
+            }
+        }
+        let in_reused_layer = |draw_tree: &easy_tree::Tree<DrawCommand>, node_id: usize| {
+            reused_layer_roots
+                .iter()
+                .any(|&root_id| is_in_subtree(draw_tree, node_id, root_id))
+        };
 
         if has_shape_effects {
             self.resolve_shape_effects(
-                &mut encoder,
+                encoder,
                 &mut shape_effect_leaves,
                 &mut textures_to_recycle,
                 #[cfg(feature = "render_metrics")]
@@ -88,6 +194,17 @@ impl<'a> Renderer<'a> {
             );
         }
 
+        // Soft-clip subtrees are rendered at full viewport size, so every composite samples
+        // them with the same viewport-sized mapping.
+        let soft_clip_material_params_buffer = has_soft_clips.then(|| {
+            effect::prepare_solid_backdrop_material_params_buffer(
+                &self.device,
+                &self.queue,
+                &mut self.soft_clip_material_params_buffer,
+                BackdropSamplingUniform::new((0, 0), self.physical_size),
+            )
+        });
+
         let pipelines = types::Pipelines {
             and_pipeline: &self.and_pipeline,
             and_gradient_pipeline: &self.and_gradient_pipeline,
@@ -96,6 +213,7 @@ impl<'a> Renderer<'a> {
             decrementing_bind_group: &self.decrementing_bind_group,
             leaf_draw_pipeline: &self.leaf_draw_pipeline,
             leaf_draw_gradient_pipeline: &self.leaf_draw_gradient_pipeline,
+            soft_clip_composite_pipeline: self.soft_clip_composite_pipeline.as_ref(),
             shape_texture_bind_group_layout_background: &self
                 .shape_texture_bind_group_layout_background,
             shape_texture_bind_group_layout_foreground: &self
@@ -124,10 +242,36 @@ impl<'a> Renderer<'a> {
             aggregated_instance_metadata_buffer: self.aggregated_instance_metadata_buffer.as_ref(),
         };
 
-        if has_group_effects {
+        if has_group_effects || has_soft_clips || !layer_misses.is_empty() {
             effect_node_ids.clear();
             for &node_id in self.group_effects.keys() {
-                if self.draw_tree.get(node_id).is_some() {
+                if self.draw_tree.get(node_id).is_some()
+                    && subtree_root
+                        .is_none_or(|root_id| is_in_subtree(&self.draw_tree, node_id, root_id))
+                    && !in_reused_layer(&self.draw_tree, node_id)
+                {
+                    let depth = compute_node_depth(&self.draw_tree, node_id);
+                    effect_node_ids.push((node_id, depth));
+                }
+            }
+            for &node_id in &self.soft_clip_nodes {
+                if self.group_effects.contains_key(&node_id) {
+                    continue;
+                }
+                if self
+                    .draw_tree
+                    .get(node_id)
+                    .is_some_and(|draw_command| !draw_command.is_leaf())
+                    && subtree_root
+                        .is_none_or(|root_id| is_in_subtree(&self.draw_tree, node_id, root_id))
+                    && !in_reused_layer(&self.draw_tree, node_id)
+                {
+                    let depth = compute_node_depth(&self.draw_tree, node_id);
+                    effect_node_ids.push((node_id, depth));
+                }
+            }
+            for &node_id in layer_misses.keys() {
+                if !in_reused_layer(&self.draw_tree, node_id) {
                     let depth = compute_node_depth(&self.draw_tree, node_id);
                     effect_node_ids.push((node_id, depth));
                 }
@@ -139,12 +283,15 @@ impl<'a> Renderer<'a> {
             let scale_factor = self.scale_factor;
 
             for &(node_id, _depth) in &effect_node_ids {
-                let effect_instance = match self.group_effects.get(&node_id) {
-                    Some(instance) => instance,
-                    None => continue,
-                };
-                let effect_id = effect_instance.effect_id;
-                if !self.loaded_effects.contains_key(&effect_id) {
+                let effect_instance = self
+                    .group_effects
+                    .get(&node_id)
+                    .filter(|instance| self.loaded_effects.contains_key(&instance.effect_id));
+                let soft_clip = self.draw_tree.get(node_id).is_some_and(|draw_command| {
+                    draw_command.soft_clip() && !draw_command.is_leaf()
+                });
+                let layer_signature = layer_misses.remove(&node_id);
+                if effect_instance.is_none() && !soft_clip && layer_signature.is_none() {
                     continue;
                 }
 
@@ -198,7 +345,7 @@ impl<'a> Renderer<'a> {
                     );
                     render_segments(
                         &mut self.draw_tree,
-                        &mut encoder,
+                        encoder,
                         traversal_scratch.events(),
                         &effect_results,
                         &mut shape_effect_leaves,
@@ -208,7 +355,7 @@ impl<'a> Renderer<'a> {
                         behind_resolve_target,
                         &behind_depth_view,
                         None,
-                        true,
+                        wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
                         &pipelines,
                         &buffers,
                         &mut self.buffers_pool_manager.gradient_cache,
@@ -307,7 +454,7 @@ impl<'a> Renderer<'a> {
 
                 render_segments(
                     &mut self.draw_tree,
-                    &mut encoder,
+                    encoder,
                     traversal_scratch.events(),
                     &effect_results,
                     &mut shape_effect_leaves,
@@ -320,7 +467,7 @@ impl<'a> Renderer<'a> {
                         .as_ref()
                         .expect("subtree render targets must include a depth/stencil attachment"),
                     backdrop_source,
-                    true,
+                    wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
                     &pipelines,
                     &buffers,
                     &mut self.buffers_pool_manager.gradient_cache,
@@ -350,10 +497,53 @@ impl<'a> Renderer<'a> {
                     &subtree_texture.color_view
                 };
 
-                let loaded_effect = self.loaded_effects.get(&effect_id).unwrap();
+                if let Some(layer_signature) = layer_signature {
+                    let bind_group = effect::create_texture_sample_bind_group(
+                        &self.device,
+                        self.composite_bgl.as_ref().unwrap(),
+                        source_view,
+                        self.effect_sampler.as_ref().unwrap(),
+                        Some("cached_layer_bind_group"),
+                    );
+                    effect_results.insert(node_id, bind_group.clone());
+                    let key = layer_signature.key.clone();
+                    self.layer_cache.insert(
+                        key,
+                        Arc::new(CachedLayer::new(
+                            layer_signature,
+                            subtree_texture,
+                            bind_group,
+                        )),
+                    );
+                    #[cfg(feature = "render_metrics")]
+                    {
+                        layer_cache_metrics.misses += 1;
+                    }
+                    continue;
+                }
+
+                let Some(effect_instance) = effect_instance else {
+                    effect_results.insert(
+                        node_id,
+                        effect::create_backdrop_texture_sample_bind_group(
+                            &self.device,
+                            &self.backdrop_texture_bind_group_layout,
+                            soft_clip_material_params_buffer
+                                .as_ref()
+                                .expect("soft clips must prepare a sampling buffer"),
+                            source_view,
+                            self.effect_sampler.as_ref().unwrap(),
+                            Some("soft_clip_subtree_bind_group"),
+                        ),
+                    );
+                    textures_to_recycle.push(subtree_texture);
+                    continue;
+                };
+
+                let loaded_effect = self.loaded_effects.get(&effect_instance.effect_id).unwrap();
                 let effect_output = apply_effect_passes(
                     &self.device,
-                    &mut encoder,
+                    encoder,
                     &mut self.offscreen_texture_pool,
                     EffectPassRunConfig {
                         loaded_effect,
@@ -361,7 +551,7 @@ impl<'a> Renderer<'a> {
                         source_view,
                         effect_sampler: self.effect_sampler.as_ref().unwrap(),
                         composite_bind_group_layout: self.composite_bgl.as_ref().unwrap(),
-                        create_composite_bind_group: true,
+                        create_composite_bind_group: !soft_clip,
                         width,
                         height,
                         texture_format: self.config.format,
@@ -369,10 +559,25 @@ impl<'a> Renderer<'a> {
                     },
                 );
 
-                let composite_bind_group = effect_output
-                    .push_work_textures_into(&mut effect_output_textures)
-                    .expect("group effects must create a composite bind group");
-                effect_results.insert(node_id, composite_bind_group);
+                let result_bind_group = if soft_clip {
+                    let soft_clip_bind_group = effect::create_backdrop_texture_sample_bind_group(
+                        &self.device,
+                        &self.backdrop_texture_bind_group_layout,
+                        soft_clip_material_params_buffer
+                            .as_ref()
+                            .expect("soft clips must prepare a sampling buffer"),
+                        effect_output.final_output_view(),
+                        self.effect_sampler.as_ref().unwrap(),
+                        Some("soft_clip_effect_bind_group"),
+                    );
+                    effect_output.push_work_textures_into(&mut effect_output_textures);
+                    soft_clip_bind_group
+                } else {
+                    effect_output
+                        .push_work_textures_into(&mut effect_output_textures)
+                        .expect("group effects must create a composite bind group")
+                };
+                effect_results.insert(node_id, result_bind_group);
                 textures_to_recycle.push(subtree_texture);
             }
         }
@@ -385,7 +590,7 @@ impl<'a> Renderer<'a> {
                 &mut self.draw_tree,
                 &effect_results,
                 &shape_effect_leaves,
-                None,
+                subtree_root,
                 None,
                 &mut traversal_scratch,
             );
@@ -443,7 +648,7 @@ impl<'a> Renderer<'a> {
 
             render_segments(
                 &mut self.draw_tree,
-                &mut encoder,
+                encoder,
                 traversal_scratch.events(),
                 &effect_results,
                 &mut shape_effect_leaves,
@@ -453,7 +658,7 @@ impl<'a> Renderer<'a> {
                 phase2_resolve_target,
                 depth_texture_view,
                 backdrop_source,
-                true,
+                color_load_op,
                 &pipelines,
                 &buffers,
                 &mut self.buffers_pool_manager.gradient_cache,
@@ -473,10 +678,6 @@ impl<'a> Renderer<'a> {
             );
         }
 
-        self.queue.submit(std::iter::once(encoder.finish()));
-
-        self.last_render_to_texture_view_cpu_time = render_to_texture_view_started_at.elapsed();
-
         effect_output_textures.append(&mut backdrop_work_textures);
         textures_to_recycle.append(&mut effect_output_textures);
         self.offscreen_texture_pool
@@ -501,9 +702,14 @@ impl<'a> Renderer<'a> {
         self.scratch.scissor_stack = scissor_stack;
         self.scratch.clip_kind_stack = clip_kind_stack;
         self.scratch.backdrop_work_textures = backdrop_work_textures;
+        if subtree_root.is_some() {
+            return;
+        }
         let _collected_shape_effect_results = self.shape_effect_cache.end_frame();
         let _collected_shape_effect_masks = self.shape_effect_mask_cache.end_frame();
+        let _collected_layers = self.layer_cache.end_frame();
         self.buffers_pool_manager.tessellation_cache.end_frame();
+        self.texture_manager.end_frame();
 
         // println!("Tesselation cache size: {}", self.buffers_pool_manager.tessellation_cache.len());
 
@@ -513,9 +719,228 @@ impl<'a> Renderer<'a> {
             shape_effect_cache_metrics.collected_masks = _collected_shape_effect_masks as u64;
             self.last_pipeline_switch_counts = frame_pipeline_counts;
             self.last_shape_effect_cache_metrics = shape_effect_cache_metrics;
+            layer_cache_metrics.collected = _collected_layers as u64;
+            self.last_layer_cache_metrics = layer_cache_metrics;
         }
     }
 
+    /// Records the draw queue into a caller-owned `encoder`, drawing into `target_view`.
+    ///
+    /// Lets grafo draw inside an application's own frame graph, e.g. UI over a 3D scene with
+    /// [`wgpu::LoadOp::Load`], or into a texture the application samples later. Nothing is
+    /// submitted: submit `encoder` before the next render call on this renderer, since that
+    /// call rewrites uniforms the recorded passes read.
+    ///
+    /// `target_view` must be single-sampled, [`Renderer::size`] in size, and in
+    /// [`Renderer::target_format`]; change the format with [`Renderer::set_target_format`].
+    /// The view is only rendered to, so it needs no usages beyond `RENDER_ATTACHMENT`. Frames
+    /// with backdrop effects, or with MSAA and [`wgpu::LoadOp::Load`], are drawn offscreen and
+    /// then composited over the target; backdrop effects then blur only what grafo drew.
+    pub fn render_into(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        target_view: &wgpu::TextureView,
+        load_op: wgpu::LoadOp<wgpu::Color>,
+    ) {
+        let render_into_started_at = std::time::Instant::now();
+        self.prepare_render();
+
+        let loads_target = matches!(load_op, wgpu::LoadOp::Load);
+        let needs_offscreen_frame = !self.draw_tree.is_empty()
+            && (!self.backdrop_effects.is_empty() || (self.msaa_sample_count > 1 && loads_target));
+        if !needs_offscreen_frame {
+            if self.draw_tree.is_empty() && !loads_target {
+                let _clear_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("render_into_clear_pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: target_view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: load_op,
+                            store: wgpu::StoreOp::Store,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                    timestamp_writes: None,
+                    occlusion_query_set: None,
+                });
+            }
+            self.encode_frame(encoder, target_view, None, load_op, None);
+            self.last_render_to_texture_view_cpu_time = render_into_started_at.elapsed();
+            return;
+        }
+
+        self.ensure_composite_pipeline();
+        self.ensure_effect_sampler();
+        self.ensure_target_composite_pipeline();
+        let (width, height) = self.physical_size;
+        let frame_texture = self.offscreen_texture_pool.acquire_color_only(
+            &self.device,
+            width,
+            height,
+            self.config.format,
+            1,
+        );
+        self.encode_frame(
+            encoder,
+            &frame_texture.color_view,
+            Some(&frame_texture.color_texture),
+            wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+            None,
+        );
+        blit_texture_to_texture(
+            &self.device,
+            encoder,
+            self.target_composite_pipeline
+                .as_ref()
+                .expect("target composite pipeline was just ensured"),
+            self.composite_bgl
+                .as_ref()
+                .expect("composite bind group layout was just ensured"),
+            &frame_texture.color_view,
+            target_view,
+            self.effect_sampler
+                .as_ref()
+                .expect("effect sampler was just ensured"),
+            load_op,
+            "render_into_composite",
+        );
+        self.scratch.textures_to_recycle.push(frame_texture);
+        self.offscreen_texture_pool
+            .recycle(&mut self.scratch.textures_to_recycle);
+        self.last_render_to_texture_view_cpu_time = render_into_started_at.elapsed();
+    }
+
+    /// Renders node `node_id` and its descendants into texture `texture_id`, which other
+    /// shapes can then draw as a background or foreground texture without a CPU round trip.
+    ///
+    /// The subtree is drawn with its effects, in canvas coordinates, into a `physical_size`
+    /// texture at `scale_factor`; a smaller scale factor fits more of the canvas, e.g. for
+    /// thumbnails. Backdrop effects inside the subtree only see the subtree. The texture is
+    /// reused when it is already a render target of this size, otherwise replaced, and the
+    /// subtree must not draw it. The work is submitted right away and the draw queue is kept.
+    pub fn render_subtree_to_texture(
+        &mut self,
+        node_id: usize,
+        texture_id: u64,
+        physical_size: (u32, u32),
+        scale_factor: f64,
+    ) -> Result<(), RenderToTextureError> {
+        if self.draw_tree.get(node_id).is_none() {
+            return Err(DrawCommandError::InvalidShapeId(node_id).into());
+        }
+        let max_texture_dimension = self.device.limits().max_texture_dimension_2d;
+        if physical_size.0 == 0
+            || physical_size.1 == 0
+            || physical_size.0 > max_texture_dimension
+            || physical_size.1 > max_texture_dimension
+        {
+            return Err(RenderToTextureError::InvalidSize(physical_size));
+        }
+        if !scale_factor.is_finite() || scale_factor <= 0.0 {
+            return Err(RenderToTextureError::InvalidScaleFactor(scale_factor));
+        }
+
+        let target_texture = self.subtree_target_texture(texture_id, physical_size)?;
+        let target_view = target_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let canvas_metrics = (self.physical_size, self.scale_factor);
+        let changes_canvas_metrics = canvas_metrics != (physical_size, scale_factor);
+        // Attachments for another size come from the pool, so previews refreshed every frame
+        // neither reallocate them nor the renderer's own.
+        let subtree_attachments = (physical_size != canvas_metrics.0).then(|| {
+            self.offscreen_texture_pool.acquire_with_depth(
+                &self.device,
+                physical_size.0,
+                physical_size.1,
+                self.config.format,
+                self.msaa_sample_count,
+            )
+        });
+        let own_attachments = subtree_attachments.as_ref().map(|attachments| {
+            let msaa_color_view =
+                (attachments.sample_count > 1).then(|| attachments.color_view.clone());
+            (
+                std::mem::replace(&mut self.msaa_color_texture_view, msaa_color_view),
+                std::mem::replace(
+                    &mut self.depth_stencil_view,
+                    attachments.depth_stencil_view.clone(),
+                ),
+            )
+        });
+        if changes_canvas_metrics {
+            self.use_canvas_uniforms(physical_size, scale_factor);
+        }
+
+        self.prepare_render();
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("render_subtree_to_texture_encoder"),
+            });
+        self.encode_frame(
+            &mut encoder,
+            &target_view,
+            Some(&target_texture),
+            wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+            Some(node_id),
+        );
+        self.queue.submit(std::iter::once(encoder.finish()));
+        self.texture_manager.mark_texture_modified(texture_id);
+
+        if changes_canvas_metrics {
+            self.use_canvas_uniforms(canvas_metrics.0, canvas_metrics.1);
+        }
+        if let Some((msaa_color_view, depth_stencil_view)) = own_attachments {
+            self.msaa_color_texture_view = msaa_color_view;
+            self.depth_stencil_view = depth_stencil_view;
+        }
+        self.scratch.textures_to_recycle.extend(subtree_attachments);
+        self.offscreen_texture_pool
+            .recycle(&mut self.scratch.textures_to_recycle);
+        Ok(())
+    }
+
+    /// The texture behind `texture_id` when it can be rendered to at `physical_size`, or a
+    /// new one registered under that id.
+    fn subtree_target_texture(
+        &self,
+        texture_id: u64,
+        physical_size: (u32, u32),
+    ) -> Result<wgpu::Texture, TextureManagerError> {
+        let usage = wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::RENDER_ATTACHMENT
+            | wgpu::TextureUsages::COPY_SRC
+            | wgpu::TextureUsages::COPY_DST;
+        if let Some(texture) = self.texture_manager.wgpu_texture(texture_id) {
+            if (texture.width(), texture.height()) == physical_size
+                && texture.format() == self.config.format
+                && texture.usage().contains(usage)
+                && texture.mip_level_count() == 1
+            {
+                return Ok(texture);
+            }
+        }
+
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("subtree_texture"),
+            size: wgpu::Extent3d {
+                width: physical_size.0,
+                height: physical_size.1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.config.format,
+            usage,
+            view_formats: &[],
+        });
+        self.texture_manager
+            .insert_wgpu_texture(texture_id, texture.clone())?;
+        Ok(texture)
+    }
+
     pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
         #[cfg(feature = "render_metrics")]
         let frame_render_loop_started_at = std::time::Instant::now();
@@ -419,7 +419,7 @@ impl<'a> Renderer<'a> {
             let Some(draw_command) = self.draw_tree.get(node_id) else {
                 continue;
             };
-            let DrawCommand::CachedShape(source_shape) = draw_command else {
+            let Some(source_shape) = draw_command.shape_geometry() else {
                 continue;
             };
             let local_bounds = source_shape.cached_shape.tessellation.local_bounds;
@@ -491,7 +491,7 @@ impl<'a> Renderer<'a> {
                 None,
                 InstanceTextureData {
                     texture_presence: [true, false],
-                    texture_uv_scales: [[1.0, 1.0]; 2],
+                    layers: Default::default(),
                 },
             ));
             self.scratch.shape_effect_leaves.insert(node_id, leaf);
@@ -519,7 +519,11 @@ impl<'a> Renderer<'a> {
             if !shape_effect_leaves.contains_key(&node_id) {
                 continue;
             }
-            let Some(DrawCommand::CachedShape(cached_shape)) = self.draw_tree.get(node_id) else {
+            let Some(cached_shape) = self
+                .draw_tree
+                .get(node_id)
+                .and_then(DrawCommand::shape_geometry)
+            else {
                 continue;
             };
             let Some(index_buffer_range) = cached_shape.index_buffer_range else {
@@ -1,3 +1,5 @@
+use super::config::is_supported_target_format;
+use super::types::RenderTargetError;
 use super::*;
 
 impl<'a> Renderer<'a> {
@@ -33,7 +35,41 @@ impl<'a> Renderer<'a> {
         self.config.width = new_physical_size.0;
         self.config.height = new_physical_size.1;
 
-        let logical_size = to_logical(new_physical_size, self.scale_factor);
+        self.write_canvas_uniforms();
+
+        if let Some(surface) = &self.surface {
+            surface.configure(&self.device, &self.config);
+        }
+        self.recreate_msaa_texture();
+        self.recreate_depth_stencil_texture();
+
+        self.offscreen_texture_pool.trim(
+            new_physical_size.0,
+            new_physical_size.1,
+            self.msaa_sample_count,
+        );
+        self.trim_scratch_on_resize_or_policy();
+    }
+
+    /// Draws at another canvas size and scale factor without touching the surface, e.g. for
+    /// offscreen renders. Restore the previous values the same way afterwards.
+    pub(super) fn use_canvas_metrics(&mut self, physical_size: (u32, u32), scale_factor: f64) {
+        self.use_canvas_uniforms(physical_size, scale_factor);
+        self.create_msaa_color_attachment();
+        self.recreate_depth_stencil_texture();
+    }
+
+    /// Like [`Self::use_canvas_metrics`], but keeps the renderer's own MSAA and depth/stencil
+    /// attachments, for callers that swap in attachments of `physical_size` themselves.
+    pub(super) fn use_canvas_uniforms(&mut self, physical_size: (u32, u32), scale_factor: f64) {
+        self.physical_size = physical_size;
+        self.scale_factor = scale_factor;
+        self.write_canvas_uniforms();
+    }
+
+    /// Writes the canvas size, scale factor, and fringe width into the shape uniforms.
+    fn write_canvas_uniforms(&mut self) {
+        let logical_size = to_logical(self.physical_size, self.scale_factor);
         self.and_uniforms.canvas_size = [logical_size.0, logical_size.1];
         self.and_uniforms.scale_factor = self.scale_factor as f32;
         self.and_uniforms.fringe_width = self.fringe_width;
@@ -52,19 +88,61 @@ impl<'a> Renderer<'a> {
             0,
             bytemuck::cast_slice(&[self.decrementing_uniforms]),
         );
+    }
 
+    /// Color format of the surface and of every texture the renderer draws into.
+    ///
+    /// Views passed to [`Renderer::render_into`] must use this format.
+    pub fn target_format(&self) -> wgpu::TextureFormat {
+        self.config.format
+    }
+
+    /// Switches the color format the renderer draws in, recompiling pipelines and loaded effects.
+    ///
+    /// The format must be an 8-bit RGBA or BGRA format or `Rgba16Float`, which readback
+    /// relies on, and support the current MSAA sample count. With a surface, it must also be
+    /// one of the surface's supported formats. [`Renderer::render_to_buffer`] returns texels in
+    /// this format's byte order, or as sRGB RGBA8 for `Rgba16Float`.
+    pub fn set_target_format(
+        &mut self,
+        format: wgpu::TextureFormat,
+    ) -> Result<(), RenderTargetError> {
+        if format == self.config.format {
+            return Ok(());
+        }
+
+        let adapter = &self.context.inner.adapter;
+        let format_features = adapter.get_texture_format_features(format);
+        if !is_supported_target_format(format, format_features) {
+            return Err(RenderTargetError::UnsupportedFormat(format));
+        }
+        if self.msaa_sample_count > 1
+            && !format_features.flags.contains(
+                wgpu::TextureFormatFeatureFlags::MULTISAMPLE_X4
+                    | wgpu::TextureFormatFeatureFlags::MULTISAMPLE_RESOLVE,
+            )
+        {
+            return Err(RenderTargetError::UnsupportedSampleCount(format));
+        }
+        if let Some(surface) = &self.surface {
+            if !surface.get_capabilities(adapter).formats.contains(&format) {
+                return Err(RenderTargetError::UnsupportedSurfaceFormat(format));
+            }
+        }
+        let recompiled_effects = self.compile_loaded_effects_for_format(format)?;
+
+        self.config.format = format;
         if let Some(surface) = &self.surface {
             surface.configure(&self.device, &self.config);
         }
+        self.offscreen_texture_pool.clear();
+        self.rtb_offscreen_texture = None;
+        self.recreate_pipelines();
         self.recreate_msaa_texture();
-        self.recreate_depth_stencil_texture();
-
-        self.offscreen_texture_pool.trim(
-            new_physical_size.0,
-            new_physical_size.1,
-            self.msaa_sample_count,
-        );
-        self.trim_scratch_on_resize_or_policy();
+        for (effect_id, loaded_effect) in recompiled_effects {
+            self.install_loaded_effect(effect_id, loaded_effect);
+        }
+        Ok(())
     }
 
     pub fn msaa_samples(&self) -> u32 {
@@ -98,6 +176,24 @@ impl<'a> Renderer<'a> {
     }
 
     pub(super) fn recreate_msaa_texture(&mut self) {
+        self.create_msaa_color_attachment();
+
+        self.texture_blit_pipeline = None;
+        self.target_composite_pipeline = None;
+        self.stencil_only_pipeline = None;
+        self.backdrop_color_pipeline = None;
+        self.backdrop_color_gradient_pipeline = None;
+        self.soft_clip_composite_pipeline = None;
+
+        self.offscreen_texture_pool.trim(
+            self.physical_size.0,
+            self.physical_size.1,
+            self.msaa_sample_count,
+        );
+        self.trim_scratch_on_resize_or_policy();
+    }
+
+    fn create_msaa_color_attachment(&mut self) {
         if self.msaa_sample_count > 1 {
             let texture = create_msaa_color_texture(
                 &self.device,
@@ -112,18 +208,6 @@ impl<'a> Renderer<'a> {
             self.msaa_color_texture = None;
             self.msaa_color_texture_view = None;
         }
-
-        self.texture_blit_pipeline = None;
-        self.stencil_only_pipeline = None;
-        self.backdrop_color_pipeline = None;
-        self.backdrop_color_gradient_pipeline = None;
-
-        self.offscreen_texture_pool.trim(
-            self.physical_size.0,
-            self.physical_size.1,
-            self.msaa_sample_count,
-        );
-        self.trim_scratch_on_resize_or_policy();
     }
 
     /// Recreate the cached depth/stencil texture to match current physical size and MSAA settings.
@@ -0,0 +1,373 @@
+//! Tiled rendering for exports larger than the GPU texture size limit.
+
+use std::io::Write;
+
+use super::*;
+use crate::renderer::types::ReadbackError;
+
+/// Edge length of a tile in physical pixels, padding excluded.
+const DEFAULT_TILE_SIZE: u32 = 2048;
+/// Physical pixels rendered around each tile so effects sample across the seams.
+const DEFAULT_TILE_PADDING: u32 = 64;
+
+/// How [`Renderer::render_tiled_to_buffer`] splits the export into tiles and lays out pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TiledRenderOptions {
+    /// Edge length of a tile in physical pixels, padding excluded.
+    pub tile_size: u32,
+    /// Physical pixels rendered around each tile and then discarded. Must cover the widest
+    /// blur or backdrop sample distance in the scene for tiles to join without seams.
+    pub padding: u32,
+    pub format: ReadbackFormat,
+    pub alpha: ReadbackAlpha,
+}
+
+impl Default for TiledRenderOptions {
+    fn default() -> Self {
+        Self {
+            tile_size: DEFAULT_TILE_SIZE,
+            padding: DEFAULT_TILE_PADDING,
+            format: ReadbackFormat::default(),
+            alpha: ReadbackAlpha::default(),
+        }
+    }
+}
+
+impl TiledRenderOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn tile_size(mut self, tile_size: u32) -> Self {
+        self.tile_size = tile_size;
+        self
+    }
+
+    pub fn padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn format(mut self, format: ReadbackFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn alpha(mut self, alpha: ReadbackAlpha) -> Self {
+        self.alpha = alpha;
+        self
+    }
+}
+
+/// Screen-space state of the draw queue as queued, restored once the tiles are rendered.
+struct QueuedTransforms {
+    instances: Vec<InstanceTransform>,
+    nodes: Vec<Option<InstanceTransform>>,
+    /// Backdrop capture rects given in logical screen space, by node.
+    capture_rects: Vec<(usize, [(f32, f32); 2])>,
+}
+
+/// One tile of the export and the padding rendered around it, in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Tile {
+    origin: (u32, u32),
+    size: (u32, u32),
+    padding_before: (u32, u32),
+    padding_after: (u32, u32),
+}
+
+impl Tile {
+    /// Padding is clamped at the export edges, so content outside the export is never
+    /// sampled, just like in a single frame of the full size.
+    fn new(origin: (u32, u32), export_size: (u32, u32), tile_size: u32, padding: u32) -> Self {
+        let size = (
+            tile_size.min(export_size.0 - origin.0),
+            tile_size.min(export_size.1 - origin.1),
+        );
+        Self {
+            origin,
+            size,
+            padding_before: (padding.min(origin.0), padding.min(origin.1)),
+            padding_after: (
+                padding.min(export_size.0 - origin.0 - size.0),
+                padding.min(export_size.1 - origin.1 - size.1),
+            ),
+        }
+    }
+
+    fn canvas_size(&self) -> (u32, u32) {
+        (
+            self.padding_before.0 + self.size.0 + self.padding_after.0,
+            self.padding_before.1 + self.size.1 + self.padding_after.1,
+        )
+    }
+
+    /// Top-left corner of the rendered canvas within the export.
+    fn canvas_origin(&self) -> (u32, u32) {
+        (
+            self.origin.0 - self.padding_before.0,
+            self.origin.1 - self.padding_before.1,
+        )
+    }
+}
+
+impl Renderer<'_> {
+    /// Renders the draw queue at `physical_size` and `scale_factor` tile by tile, so the export
+    /// may exceed the GPU's texture size limit. `buffer` receives tightly packed rows of the
+    /// whole export. The renderer's own size and scale factor are left untouched.
+    ///
+    /// Effects run on each padded tile, so effect parameters derived from the canvas size do
+    /// not carry over; shaders should take texel sizes from their input texture instead.
+    pub fn render_tiled_to_buffer(
+        &mut self,
+        physical_size: (u32, u32),
+        scale_factor: f64,
+        options: TiledRenderOptions,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), ReadbackError> {
+        let export_bytes = (physical_size.0 as usize)
+            .checked_mul(physical_size.1 as usize)
+            .and_then(|pixels| pixels.checked_mul(options.format.bytes_per_pixel()))
+            .ok_or(ReadbackError::InvalidExportSize(physical_size))?;
+        buffer.clear();
+        buffer.reserve(export_bytes);
+        self.render_tiles(physical_size, scale_factor, options, |band| {
+            buffer.extend_from_slice(band);
+            Ok(())
+        })
+    }
+
+    /// Like [`Self::render_tiled_to_buffer`], but streams the rows to `writer` one band of
+    /// tiles at a time, so only a single band is held in memory.
+    pub fn render_tiled_to_writer(
+        &mut self,
+        physical_size: (u32, u32),
+        scale_factor: f64,
+        options: TiledRenderOptions,
+        mut writer: impl Write,
+    ) -> Result<(), ReadbackError> {
+        self.render_tiles(physical_size, scale_factor, options, |band| {
+            writer.write_all(band)?;
+            Ok(())
+        })?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Renders every row of tiles and hands `write_band` the stitched rows of each, top to
+    /// bottom. Transforms and canvas metrics are restored even when a tile fails.
+    fn render_tiles(
+        &mut self,
+        physical_size: (u32, u32),
+        scale_factor: f64,
+        options: TiledRenderOptions,
+        write_band: impl FnMut(&[u8]) -> Result<(), ReadbackError>,
+    ) -> Result<(), ReadbackError> {
+        if physical_size.0 == 0 || physical_size.1 == 0 {
+            return Err(ReadbackError::InvalidExportSize(physical_size));
+        }
+        if !scale_factor.is_finite() || scale_factor <= 0.0 {
+            return Err(ReadbackError::InvalidScaleFactor(scale_factor));
+        }
+        let max_texture_dimension = self.device.limits().max_texture_dimension_2d;
+        let padded_tile_size = options
+            .padding
+            .checked_mul(2)
+            .and_then(|padding| padding.checked_add(options.tile_size));
+        if options.tile_size == 0
+            || padded_tile_size.is_none_or(|size| size > max_texture_dimension)
+        {
+            return Err(ReadbackError::InvalidTileSize {
+                tile_size: options.tile_size,
+                padding: options.padding,
+                max_texture_dimension,
+            });
+        }
+
+        let canvas_metrics = (self.physical_size, self.scale_factor);
+        let queued_transforms = self.queued_transforms();
+        let result = self.render_tile_rows(
+            physical_size,
+            scale_factor,
+            options,
+            &queued_transforms,
+            write_band,
+        );
+
+        self.restore_queued_transforms(&queued_transforms);
+        if (self.physical_size, self.scale_factor) != canvas_metrics {
+            self.use_canvas_metrics(canvas_metrics.0, canvas_metrics.1);
+        }
+        self.offscreen_texture_pool.trim(
+            canvas_metrics.0 .0,
+            canvas_metrics.0 .1,
+            self.msaa_sample_count,
+        );
+        result
+    }
+
+    fn render_tile_rows(
+        &mut self,
+        physical_size: (u32, u32),
+        scale_factor: f64,
+        options: TiledRenderOptions,
+        queued_transforms: &QueuedTransforms,
+        mut write_band: impl FnMut(&[u8]) -> Result<(), ReadbackError>,
+    ) -> Result<(), ReadbackError> {
+        let bytes_per_pixel = options.format.bytes_per_pixel();
+        let export_row_bytes = physical_size.0 as usize * bytes_per_pixel;
+        let readback_options = ReadbackOptions::new()
+            .format(options.format)
+            .alpha(options.alpha);
+        let mut band = Vec::new();
+        let mut tile_pixels = Vec::new();
+
+        for band_y in (0..physical_size.1).step_by(options.tile_size as usize) {
+            let band_height = options.tile_size.min(physical_size.1 - band_y);
+            band.clear();
+            band.resize(export_row_bytes * band_height as usize, 0);
+
+            for tile_x in (0..physical_size.0).step_by(options.tile_size as usize) {
+                let tile = Tile::new(
+                    (tile_x, band_y),
+                    physical_size,
+                    options.tile_size,
+                    options.padding,
+                );
+                if (self.physical_size, self.scale_factor) != (tile.canvas_size(), scale_factor) {
+                    self.use_canvas_metrics(tile.canvas_size(), scale_factor);
+                }
+                self.apply_tile_camera(queued_transforms, tile.canvas_origin(), scale_factor);
+                self.try_render_to_buffer(
+                    readback_options.region(tile.padding_before, tile.size),
+                    &mut tile_pixels,
+                )?;
+
+                let tile_row_bytes = tile.size.0 as usize * bytes_per_pixel;
+                let tile_offset = tile.origin.0 as usize * bytes_per_pixel;
+                for (band_row, tile_row) in band
+                    .chunks_exact_mut(export_row_bytes)
+                    .zip(tile_pixels.chunks_exact(tile_row_bytes))
+                {
+                    band_row[tile_offset..tile_offset + tile_row_bytes].copy_from_slice(tile_row);
+                }
+            }
+
+            write_band(&band)?;
+        }
+        Ok(())
+    }
+
+    fn queued_transforms(&mut self) -> QueuedTransforms {
+        QueuedTransforms {
+            instances: self.temp_instance_transforms.clone(),
+            nodes: self
+                .draw_tree
+                .iter_mut()
+                .flat_map(|(_, draw_command)| draw_command.transforms_mut().map(|t| *t))
+                .collect(),
+            capture_rects: self
+                .backdrop_effects
+                .iter()
+                .filter_map(
+                    |(&node_id, instance)| match instance.backdrop_config?.capture_area {
+                        effect::BackdropCaptureArea::ScreenRect(rect) => Some((node_id, rect)),
+                        _ => None,
+                    },
+                )
+                .collect(),
+        }
+    }
+
+    /// Moves the whole draw queue so the physical pixel at `canvas_origin` of the export lands
+    /// on the canvas origin.
+    fn apply_tile_camera(
+        &mut self,
+        queued_transforms: &QueuedTransforms,
+        canvas_origin: (u32, u32),
+        scale_factor: f64,
+    ) {
+        let offset = (
+            (-(canvas_origin.0 as f64) / scale_factor) as f32,
+            (-(canvas_origin.1 as f64) / scale_factor) as f32,
+        );
+        let camera = InstanceTransform::translation(offset.0, offset.1);
+        for (transform, queued) in self
+            .temp_instance_transforms
+            .iter_mut()
+            .zip(&queued_transforms.instances)
+        {
+            *transform = queued.then(&camera);
+        }
+        let node_transforms = self
+            .draw_tree
+            .iter_mut()
+            .flat_map(|(_, draw_command)| draw_command.transforms_mut());
+        for (transform, queued) in node_transforms.zip(&queued_transforms.nodes) {
+            *transform = Some(
+                queued
+                    .unwrap_or_else(InstanceTransform::identity)
+                    .then(&camera),
+            );
+        }
+        for &(node_id, [(min_x, min_y), (max_x, max_y)]) in &queued_transforms.capture_rects {
+            self.set_backdrop_capture_rect(
+                node_id,
+                [
+                    (min_x + offset.0, min_y + offset.1),
+                    (max_x + offset.0, max_y + offset.1),
+                ],
+            );
+        }
+    }
+
+    fn set_backdrop_capture_rect(&mut self, node_id: usize, rect: [(f32, f32); 2]) {
+        if let Some(config) = self
+            .backdrop_effects
+            .get_mut(&node_id)
+            .and_then(|instance| instance.backdrop_config.as_mut())
+        {
+            config.capture_area = effect::BackdropCaptureArea::ScreenRect(rect);
+        }
+    }
+
+    fn restore_queued_transforms(&mut self, queued_transforms: &QueuedTransforms) {
+        self.temp_instance_transforms
+            .clone_from(&queued_transforms.instances);
+        let node_transforms = self
+            .draw_tree
+            .iter_mut()
+            .flat_map(|(_, draw_command)| draw_command.transforms_mut());
+        for (transform, queued) in node_transforms.zip(&queued_transforms.nodes) {
+            *transform = *queued;
+        }
+        for &(node_id, rect) in &queued_transforms.capture_rects {
+            self.set_backdrop_capture_rect(node_id, rect);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Tile;
+
+    #[test]
+    fn tile_padding_is_clamped_at_export_edges() {
+        let export_size = (250, 100);
+
+        let first = Tile::new((0, 0), export_size, 100, 16);
+        assert_eq!(first.size, (100, 100));
+        assert_eq!(first.canvas_size(), (116, 100));
+        assert_eq!(first.canvas_origin(), (0, 0));
+
+        let middle = Tile::new((100, 0), export_size, 100, 16);
+        assert_eq!(middle.canvas_size(), (132, 100));
+        assert_eq!(middle.canvas_origin(), (84, 0));
+
+        let last = Tile::new((200, 0), export_size, 100, 16);
+        assert_eq!(last.size, (50, 100));
+        assert_eq!(last.padding_before, (16, 0));
+        assert_eq!(last.padding_after, (0, 0));
+        assert_eq!(last.canvas_origin(), (184, 0));
+    }
+}
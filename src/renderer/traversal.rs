@@ -133,6 +133,24 @@ pub(super) fn plan_traversal_in_place(
     }
 }
 
+/// Whether `node_id` is `root_id` or one of its descendants.
+pub(super) fn is_in_subtree(
+    tree: &easy_tree::Tree<DrawCommand>,
+    node_id: usize,
+    root_id: usize,
+) -> bool {
+    let mut current = node_id;
+    loop {
+        if current == root_id {
+            return true;
+        }
+        match tree.parent_index_unchecked(current) {
+            Some(parent) => current = parent,
+            None => return false,
+        }
+    }
+}
+
 pub(super) fn compute_node_depth(tree: &easy_tree::Tree<DrawCommand>, node_id: usize) -> usize {
     let mut depth = 0;
     let mut current = node_id;
@@ -148,7 +166,8 @@ pub(super) fn compute_node_depth(tree: &easy_tree::Tree<DrawCommand>, node_id: u
 #[cfg(test)]
 mod tests {
     use super::{
-        compute_node_depth, plan_traversal_in_place, subtree_has_backdrop_effects, TraversalScratch,
+        compute_node_depth, is_in_subtree, plan_traversal_in_place, subtree_has_backdrop_effects,
+        TraversalScratch,
     };
     use crate::cache::CachedTessellation;
     use crate::effect::EffectInstance;
@@ -184,6 +203,21 @@ mod tests {
         assert_eq!(compute_node_depth(&tree, root), 0);
     }
 
+    #[test]
+    fn is_in_subtree_walks_ancestors() {
+        let mut tree = easy_tree::Tree::new();
+        let root = tree.add_node(DrawCommand::CachedShape(cached_draw_data()));
+        let child = tree.add_child(root, DrawCommand::CachedShape(cached_draw_data()));
+        let grandchild = tree.add_child(child, DrawCommand::CachedShape(cached_draw_data()));
+        let sibling = tree.add_child(root, DrawCommand::CachedShape(cached_draw_data()));
+
+        assert!(is_in_subtree(&tree, grandchild, child));
+        assert!(is_in_subtree(&tree, child, child));
+        assert!(is_in_subtree(&tree, sibling, root));
+        assert!(!is_in_subtree(&tree, sibling, child));
+        assert!(!is_in_subtree(&tree, root, child));
+    }
+
     #[test]
     fn plan_traversal_produces_balanced_events() {
         let mut tree = easy_tree::Tree::new();
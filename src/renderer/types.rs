@@ -2,13 +2,17 @@
 use super::metrics::PipelineSwitchCounts;
 use super::traversal::TraversalScratch;
 use crate::effect::{self, LoadedEffect};
-use crate::shape::{CachedShapeDrawData, DrawShapeCommand, ShapeTextureBinding};
+use crate::shape::{BorderRadii, CachedShapeDrawData, DrawShapeCommand, ShapeTextureBinding};
 use crate::texture_manager::TextureManager;
 use crate::util::GradientCache;
 use crate::vertex::InstanceTransform;
+use crate::ShapeOverflow;
 use ahash::{HashMap, HashMapExt};
 use std::sync::Arc;
 
+/// Deepest stencil reference representable by the 8-bit stencil aspect of `Depth24PlusStencil8`.
+pub(super) const MAX_STENCIL_DEPTH: u32 = u8::MAX as u32;
+
 // TODO: probably some parts of it also can be cached, so we don't need to copy it all the time.
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
@@ -20,24 +24,41 @@ pub(super) enum DrawCommand {
 #[derive(Debug)]
 pub(super) struct ClipRectDrawData {
     pub(super) rect_bounds: [(f32, f32); 2],
+    pub(super) border_radii: BorderRadii,
     pub(super) transform: Option<InstanceTransform>,
     pub(super) is_leaf: bool,
     pub(super) clips_children: bool,
+    /// Invisible geometry synthesized for stencil clipping when the rect is rounded or its
+    /// transform does not preserve axis alignment, so it cannot be clipped with a scissor.
+    /// Also synthesized on demand when an effect is attached, since effects draw the node.
+    pub(super) stencil_geometry: Option<CachedShapeDrawData>,
 }
 
 impl ClipRectDrawData {
     pub(super) fn new(
         rect_bounds: [(f32, f32); 2],
+        border_radii: BorderRadii,
         transform: Option<InstanceTransform>,
         clips_children: bool,
+        stencil_geometry: Option<CachedShapeDrawData>,
     ) -> Self {
         Self {
             rect_bounds,
+            border_radii,
             transform,
             clips_children,
             is_leaf: true,
+            stencil_geometry,
         }
     }
+
+    /// Whether this clip rect has rounded corners.
+    pub(super) fn is_rounded(&self) -> bool {
+        self.border_radii.top_left > 0.0
+            || self.border_radii.top_right > 0.0
+            || self.border_radii.bottom_left > 0.0
+            || self.border_radii.bottom_right > 0.0
+    }
 }
 
 impl DrawCommand {
@@ -60,6 +81,19 @@ impl DrawCommand {
     pub(super) fn is_clip_rect(&self) -> bool {
         matches!(self, DrawCommand::ClipRect(_))
     }
+
+    /// Whether this node has geometry that stencil passes can draw.
+    pub(super) fn has_stencil_geometry(&self) -> bool {
+        self.shape_geometry().is_some()
+    }
+
+    /// Drawable geometry for this node: the shape itself, or a clip rect's synthesized geometry.
+    pub(super) fn shape_geometry(&self) -> Option<&CachedShapeDrawData> {
+        match self {
+            DrawCommand::CachedShape(cached_shape) => Some(cached_shape),
+            DrawCommand::ClipRect(clip_rect) => clip_rect.stencil_geometry.as_ref(),
+        }
+    }
 }
 
 impl DrawCommand {
@@ -70,13 +104,13 @@ impl DrawCommand {
         }
     }
 
-    pub(super) fn texture_id(&self, layer: usize) -> Option<u64> {
+    pub(super) fn has_managed_texture(&self, layer: usize) -> bool {
         match self {
             DrawCommand::CachedShape(cached_shape) => cached_shape
                 .texture_bindings()
                 .get(layer)
-                .and_then(ShapeTextureBinding::managed_texture_id),
-            DrawCommand::ClipRect(_) => None,
+                .is_some_and(ShapeTextureBinding::is_managed),
+            DrawCommand::ClipRect(_) => false,
         }
     }
 
@@ -137,13 +171,46 @@ impl DrawCommand {
         }
     }
 
+    pub(super) fn overflow(&self) -> ShapeOverflow {
+        match self {
+            DrawCommand::CachedShape(cached_shape) => cached_shape.overflow(),
+            DrawCommand::ClipRect(clip_rect) if clip_rect.clips_children => ShapeOverflow::Hidden,
+            DrawCommand::ClipRect(_) => ShapeOverflow::Visible,
+        }
+    }
+
+    /// Whether children are composited through this node's AA coverage.
+    pub(super) fn soft_clip(&self) -> bool {
+        match self {
+            DrawCommand::CachedShape(cached_shape) => cached_shape.soft_clip(),
+            DrawCommand::ClipRect(_) => false,
+        }
+    }
+
     pub(super) fn is_rect(&self) -> bool {
         match self {
             DrawCommand::CachedShape(cached_shape) => cached_shape.is_rect(),
-            DrawCommand::ClipRect(_) => true,
+            DrawCommand::ClipRect(clip_rect) => !clip_rect.is_rounded(),
         }
     }
 
+    /// The node's transform followed by the transform of its synthesized stencil geometry.
+    pub(super) fn transforms_mut(
+        &mut self,
+    ) -> impl Iterator<Item = &mut Option<InstanceTransform>> {
+        let (transform, synthesized) = match self {
+            DrawCommand::CachedShape(cached_shape) => (
+                &mut cached_shape.transform,
+                cached_shape.axis_clip_geometry.as_deref_mut(),
+            ),
+            DrawCommand::ClipRect(clip_rect) => (
+                &mut clip_rect.transform,
+                clip_rect.stencil_geometry.as_mut(),
+            ),
+        };
+        std::iter::once(transform).chain(synthesized.map(|geometry| &mut geometry.transform))
+    }
+
     pub(super) fn rect_bounds(&self) -> Option<[(f32, f32); 2]> {
         match self {
             DrawCommand::CachedShape(cached_shape) => cached_shape.rect_bounds(),
@@ -157,8 +224,15 @@ impl DrawCommand {
                 // Geometry ranges are stable across frames until the draw queue is rebuilt.
                 // Clearing them here makes the next frame silently skip the shape.
                 cached_shape.stencil_ref = None;
+                if let Some(axis_clip_geometry) = cached_shape.axis_clip_geometry.as_mut() {
+                    axis_clip_geometry.stencil_ref = None;
+                }
+            }
+            DrawCommand::ClipRect(clip_rect) => {
+                if let Some(stencil_geometry) = clip_rect.stencil_geometry.as_mut() {
+                    stencil_geometry.stencil_ref = None;
+                }
             }
-            DrawCommand::ClipRect(_) => {}
         }
     }
 }
@@ -172,12 +246,75 @@ pub enum DrawCommandError {
     ShapeNotLoaded(u64),
     #[error("Texture layer {0} is invalid; expected 0 or 1.")]
     InvalidTextureLayer(usize),
+    #[deprecated(note = "clip rects accept any transform and are never rejected for it")]
     #[error("Clip rect node only supports axis-aligned transforms.")]
     UnsupportedClipRectTransform,
     #[error("Clip rect node {0} does not support {1}.")]
     UnsupportedClipRectOperation(usize, &'static str),
 }
 
+/// Why [`Renderer::set_target_format`](super::Renderer::set_target_format) rejected a format.
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum RenderTargetError {
+    #[error("Texture format {0:?} is not a supported 8-bit RGBA or BGRA or Rgba16Float render target format.")]
+    UnsupportedFormat(wgpu::TextureFormat),
+    #[error("Texture format {0:?} does not support the renderer's MSAA sample count.")]
+    UnsupportedSampleCount(wgpu::TextureFormat),
+    #[error("The renderer's surface cannot be configured with texture format {0:?}.")]
+    UnsupportedSurfaceFormat(wgpu::TextureFormat),
+    #[error("Failed to recompile an effect for the new format: {0}")]
+    EffectRecompilation(#[from] crate::effect::EffectError),
+}
+
+/// Why [`Renderer::render_subtree_to_texture`](super::Renderer::render_subtree_to_texture)
+/// rendered nothing.
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum RenderToTextureError {
+    #[error(transparent)]
+    DrawCommand(#[from] DrawCommandError),
+    #[error(transparent)]
+    Texture(#[from] crate::texture_manager::TextureManagerError),
+    #[error("Invalid render target size {0:?} (must be non-zero and within the GPU's texture size limit)")]
+    InvalidSize((u32, u32)),
+    #[error("Invalid scale factor: {0} (must be finite and > 0.0)")]
+    InvalidScaleFactor(f64),
+}
+
+/// Why a readback produced no pixels.
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum ReadbackError {
+    #[error("Readback region at {origin:?} with size {size:?} is empty or exceeds the {frame_size:?} frame")]
+    RegionOutOfBounds {
+        origin: (u32, u32),
+        size: (u32, u32),
+        frame_size: (u32, u32),
+    },
+    #[error("Output is too small: {actual} < {required}")]
+    OutputTooSmall { required: usize, actual: usize },
+    #[error("Failed to map the readback buffer: {0}")]
+    Map(#[from] wgpu::BufferAsyncError),
+    #[error("Failed to wait for the GPU: {0}")]
+    Poll(#[from] wgpu::PollError),
+    #[error("Invalid export size {0:?} (must be non-zero and fit in memory)")]
+    InvalidExportSize((u32, u32)),
+    #[error("Invalid scale factor: {0} (must be finite and > 0.0)")]
+    InvalidScaleFactor(f64),
+    #[error("Tile size {tile_size} with padding {padding} is empty or exceeds the GPU's texture size limit of {max_texture_dimension}")]
+    InvalidTileSize {
+        tile_size: u32,
+        padding: u32,
+        max_texture_dimension: u32,
+    },
+    #[error("Failed to write pixels: {0}")]
+    Io(#[from] std::io::Error),
+    #[cfg(feature = "png")]
+    #[error("Failed to write PNG: {0}")]
+    PngEncoding(#[from] png::EncodingError),
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(super) enum TraversalEvent {
     PreparedLeaf(usize),
@@ -254,6 +391,12 @@ impl PipelineTracker {
         self.counts.scissor_clips += 1;
     }
 
+    /// Record a stencil clip that fell back to a bounds scissor at the stencil depth limit.
+    #[cfg(feature = "render_metrics")]
+    pub(super) fn record_stencil_overflow_fallback(&mut self) {
+        self.counts.stencil_overflow_fallbacks += 1;
+    }
+
     /// Record one draw pass that modifies the stencil buffer.
     #[cfg(feature = "render_metrics")]
     pub(super) fn record_stencil_pass(&mut self) {
@@ -314,6 +457,8 @@ pub(super) struct Pipelines<'a> {
     pub(super) decrementing_bind_group: &'a wgpu::BindGroup,
     pub(super) leaf_draw_pipeline: &'a wgpu::RenderPipeline,
     pub(super) leaf_draw_gradient_pipeline: &'a wgpu::RenderPipeline,
+    /// Composites a pre-rendered soft-clip subtree through its parent's geometry.
+    pub(super) soft_clip_composite_pipeline: Option<&'a wgpu::RenderPipeline>,
     pub(super) shape_texture_bind_group_layout_background: &'a wgpu::BindGroupLayout,
     pub(super) shape_texture_bind_group_layout_foreground: &'a wgpu::BindGroupLayout,
     pub(super) default_shape_texture_bind_groups: &'a [Arc<wgpu::BindGroup>; 2],
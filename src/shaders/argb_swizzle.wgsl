@@ -1,11 +1,15 @@
-// Swizzle BGRA8 bytes (with padded row stride) into packed ARGB32 u32 pixels.
+// Swizzle BGRA8, RGBA8, or RGBA16F texels (with padded row stride) into packed ARGB32 u32 pixels.
 // Input buffer layout matches wgpu copy_texture_to_buffer with bytes_per_row alignment.
 
+const RGBA_ORDER: u32 = 1u;  // texels are RGBA8 instead of BGRA8
+const HALF_FLOAT: u32 = 2u;  // texels are linear RGBA16F, written out sRGB encoded
+const OPAQUE: u32 = 4u;      // alpha is written as 255
+
 struct Params {
     width: u32,
     height: u32,
     padded_bpr: u32, // bytes per row including padding; guaranteed multiple of 256 and 4
-    _pad: u32,       // std140 alignment padding
+    flags: u32,
 };
 
 @group(0) @binding(0)
@@ -17,6 +21,12 @@ var<storage, read_write> output_argb: array<u32>;
 @group(0) @binding(2)
 var<uniform> params: Params;
 
+fn linear_to_srgb_byte(value: f32) -> u32 {
+    let x = clamp(value, 0.0, 1.0);
+    let encoded = select(1.055 * pow(x, 1.0 / 2.4) - 0.055, x * 12.92, x <= 0.0031308);
+    return u32(floor(encoded * 255.0 + 0.5));
+}
+
 @compute @workgroup_size(16, 16, 1)
 fn cs_main(@builtin(global_invocation_id) gid: vec3<u32>) {
     if (gid.x >= params.width || gid.y >= params.height) {
@@ -24,13 +34,33 @@ fn cs_main(@builtin(global_invocation_id) gid: vec3<u32>) {
     }
 
     let row_words = (gid.y * params.padded_bpr) / 4u;
-    let word_index = row_words + gid.x; // 1 word per pixel (4 bytes)
-    let px = input_words[word_index];
-
-    let b = px & 0xffu;
-    let g = (px >> 8u) & 0xffu;
-    let r = (px >> 16u) & 0xffu;
-    let a = (px >> 24u) & 0xffu;
+    var r: u32;
+    var g: u32;
+    var b: u32;
+    var a: u32;
+    if ((params.flags & HALF_FLOAT) != 0u) {
+        let word_index = row_words + gid.x * 2u; // 2 words per pixel (8 bytes)
+        let rg = unpack2x16float(input_words[word_index]);
+        let ba = unpack2x16float(input_words[word_index + 1u]);
+        r = linear_to_srgb_byte(rg.x);
+        g = linear_to_srgb_byte(rg.y);
+        b = linear_to_srgb_byte(ba.x);
+        a = u32(floor(clamp(ba.y, 0.0, 1.0) * 255.0 + 0.5));
+    } else {
+        let px = input_words[row_words + gid.x]; // 1 word per pixel (4 bytes)
+        b = px & 0xffu;
+        g = (px >> 8u) & 0xffu;
+        r = (px >> 16u) & 0xffu;
+        a = (px >> 24u) & 0xffu;
+        if ((params.flags & RGBA_ORDER) != 0u) {
+            let red = b;
+            b = r;
+            r = red;
+        }
+    }
+    if ((params.flags & OPAQUE) != 0u) {
+        a = 255u;
+    }
 
     let out_val = (a << 24u) | (r << 16u) | (g << 8u) | b;
     let out_index = gid.y * params.width + gid.x;
@@ -10,40 +10,55 @@ struct VertexInput {
     @location(4) t_col1: vec4<f32>,
     @location(5) t_col2: vec4<f32>,
     @location(6) t_col3: vec4<f32>,
-    // Per-instance draw order for Z-fighting resolution
-    @location(7) draw_order: f32,
+    // Per-instance draw order for Z-fighting resolution (x) and texture flags (y): bits 0-1
+    // mark active layers, bits 2-12 and 13-23 hold each layer's flags (see resolve_layer_uv).
+    @location(7) draw_order_and_texture_flags: vec2<f32>,
     // AA: outward boundary normal in model space
     @location(8) normal: vec2<f32>,
     // AA: coverage factor (1.0 = interior, 0.0 = outer fringe)
     @location(9) coverage: f32,
-    // Per-instance bitmask: bit 0 = layer 0 active, bit 1 = layer 1 active.
-    // 0 = solid fill only (skip all texture samples).
-    @location(10) texture_flags: f32,
-    // Per-layer UV scale computed on the CPU from fit mode and texture dimensions.
-    @location(11) texture_uv_scale_layer0: vec2<f32>,
-    @location(12) texture_uv_scale_layer1: vec2<f32>,
+    // Per-layer UV mapping computed on the CPU from fit mode and texture dimensions:
+    // xy scales and zw offsets the shape's texture coordinates into fit space.
+    @location(11) texture_uv_transform_layer0: vec4<f32>,
+    @location(12) texture_uv_transform_layer1: vec4<f32>,
+    // Per-layer [min_u, min_v, max_u, max_v] of the sampled region (source rect or atlas
+    // entry). Fit-space UVs are mapped into it and clamped to its edge texels.
+    @location(13) texture_region_layer0: vec4<f32>,
+    @location(14) texture_region_layer1: vec4<f32>,
+    // Per-layer sampling mode parameters: tile pitch or nine-slice insets.
+    @location(10) texture_fit_params_layer0: vec4<f32>,
+    @location(15) texture_fit_params_layer1: vec4<f32>,
 };
 
 struct VertexOutput {
     @invariant @builtin(position) position: vec4<f32>,
     @location(0) color: vec4<f32>,
-    @location(1) layer0_tex_coords: vec2<f32>,
-    @location(2) layer1_tex_coords: vec2<f32>,
+    // Fit-space coordinates in xy and, for nine-slice, the distance to the far edge in zw.
+    @location(1) layer0_tex_coords: vec4<f32>,
+    @location(2) layer1_tex_coords: vec4<f32>,
     @location(3) coverage: f32,
     @location(4) @interpolate(flat) texture_flags: f32,
+    @location(5) @interpolate(flat) layer0_region: vec4<f32>,
+    @location(6) @interpolate(flat) layer1_region: vec4<f32>,
+    @location(7) @interpolate(flat) layer0_fit_params: vec4<f32>,
+    @location(8) @interpolate(flat) layer1_fit_params: vec4<f32>,
 };
 
 struct GradientVertexOutput {
     @invariant @builtin(position) position: vec4<f32>,
     @location(0) color: vec4<f32>,
-    @location(1) layer0_tex_coords: vec2<f32>,
-    @location(2) layer1_tex_coords: vec2<f32>,
+    @location(1) layer0_tex_coords: vec4<f32>,
+    @location(2) layer1_tex_coords: vec4<f32>,
     @location(3) coverage: f32,
     @location(4) @interpolate(flat) texture_flags: f32,
     // Model-space position for gradient evaluation (before transform)
     @location(5) model_pos: vec2<f32>,
     // Screen-space position (pixel coordinates, after transform)
     @location(6) screen_pos: vec2<f32>,
+    @location(7) @interpolate(flat) layer0_region: vec4<f32>,
+    @location(8) @interpolate(flat) layer1_region: vec4<f32>,
+    @location(9) @interpolate(flat) layer0_fit_params: vec4<f32>,
+    @location(10) @interpolate(flat) layer1_fit_params: vec4<f32>,
 };
 
 // This is a struct that will be used for position normalization
@@ -296,7 +311,7 @@ fn compute_vertex_position(input: VertexInput) -> vec4<f32> {
 
     // Apply a tiny depth bias based on draw order to resolve Z-fighting for coplanar shapes.
     // Later shapes (higher draw_order) get a smaller depth value (closer to camera).
-    let bias = input.draw_order * 0.00001;
+    let bias = input.draw_order_and_texture_flags.x * 0.00001;
     let biased_depth = clamp(depth - bias, 0.0, 1.0);
 
     // Biased depth here is a remnant of old code that used to actually do z sorting. I needed to add some transparency
@@ -311,15 +326,28 @@ fn compute_vertex_position(input: VertexInput) -> vec4<f32> {
     return vec4<f32>(ndc_x, ndc_y, biased_depth, 1.0);
 }
 
+// Maps the shape's texture coordinates into a layer's fit space (xy). zw measures the same
+// coordinates from the far edge, which nine-slice needs to pin the right and bottom insets.
+fn fit_tex_coords(tex_coords: vec2<f32>, uv_transform: vec4<f32>) -> vec4<f32> {
+    return vec4<f32>(
+        tex_coords * uv_transform.xy + uv_transform.zw,
+        (1.0 - tex_coords) * uv_transform.xy,
+    );
+}
+
 @vertex
 fn vs_main(input: VertexInput) -> VertexOutput {
     var output: VertexOutput;
     output.position = compute_vertex_position(input);
     output.color = input.color;
-    output.layer0_tex_coords = input.tex_coords * input.texture_uv_scale_layer0;
-    output.layer1_tex_coords = input.tex_coords * input.texture_uv_scale_layer1;
+    output.layer0_tex_coords = fit_tex_coords(input.tex_coords, input.texture_uv_transform_layer0);
+    output.layer1_tex_coords = fit_tex_coords(input.tex_coords, input.texture_uv_transform_layer1);
     output.coverage = input.coverage;
-    output.texture_flags = input.texture_flags;
+    output.texture_flags = input.draw_order_and_texture_flags.y;
+    output.layer0_region = input.texture_region_layer0;
+    output.layer1_region = input.texture_region_layer1;
+    output.layer0_fit_params = input.texture_fit_params_layer0;
+    output.layer1_fit_params = input.texture_fit_params_layer1;
     return output;
 }
 
@@ -328,10 +356,14 @@ fn vs_main_gradient(input: VertexInput) -> GradientVertexOutput {
     var output: GradientVertexOutput;
     output.position = compute_vertex_position(input);
     output.color = input.color;
-    output.layer0_tex_coords = input.tex_coords * input.texture_uv_scale_layer0;
-    output.layer1_tex_coords = input.tex_coords * input.texture_uv_scale_layer1;
+    output.layer0_tex_coords = fit_tex_coords(input.tex_coords, input.texture_uv_transform_layer0);
+    output.layer1_tex_coords = fit_tex_coords(input.tex_coords, input.texture_uv_transform_layer1);
     output.coverage = input.coverage;
-    output.texture_flags = input.texture_flags;
+    output.texture_flags = input.draw_order_and_texture_flags.y;
+    output.layer0_region = input.texture_region_layer0;
+    output.layer1_region = input.texture_region_layer1;
+    output.layer0_fit_params = input.texture_fit_params_layer0;
+    output.layer1_fit_params = input.texture_fit_params_layer1;
     output.model_pos = input.position;
 
     let model: mat4x4<f32> = mat4x4<f32>(input.t_col0, input.t_col1, input.t_col2, input.t_col3);
@@ -363,12 +395,170 @@ fn vs_main_gradient(input: VertexInput) -> GradientVertexOutput {
     return output;
 }
 
+// Per-fragment inputs of one texture layer, as produced by fit_tex_coords and the CPU.
+struct TextureLayerInputs {
+    tex_coords: vec4<f32>,
+    region: vec4<f32>,
+    fit_params: vec4<f32>,
+    // Screen-space derivatives of tex_coords.xy, for mip selection.
+    tex_coords_dx: vec2<f32>,
+    tex_coords_dy: vec2<f32>,
+}
+
+// Derivatives need uniform control flow, so entry points build layer inputs up front.
+fn texture_layer_inputs(tex_coords: vec4<f32>, region: vec4<f32>, fit_params: vec4<f32>) -> TextureLayerInputs {
+    return TextureLayerInputs(tex_coords, region, fit_params, dpdx(tex_coords.xy), dpdy(tex_coords.xy));
+}
+
+// Resolves a layer's fit-space coordinates into its texture region. Returns the UV in xy
+// and the sample weight in z, which is 0 where Contain or Tile leave the fill visible.
+// layer_flags holds the sampling mode in bits 0-1 (0 = clamp to edge, 1 = masked outside
+// [0, 1], 2 = tiled, 3 = nine-slice), the u and v address modes in bits 2-3 and 4-5
+// (0 = clamp to edge, 1 = repeat, 2 = mirror repeat), the R8 mask bit in bit 6, and the YUV
+// format in bits 7-8 (0 = none, 1 = NV12, 2 = I420) with BT.709 in bit 9 and full range in
+// bit 10.
+fn resolve_layer_uv(layer: TextureLayerInputs, layer_flags: u32, texture_size: vec2<u32>) -> vec3<f32> {
+    let mode = layer_flags & 3u;
+    let address = vec2<u32>((layer_flags >> 2u) & 3u, (layer_flags >> 4u) & 3u);
+    // Address modes apply to the fit modes that map the texture once; emulating them here
+    // keeps repetition within source rects and atlas entries.
+    let wraps = (address != vec2<u32>(0u)) & vec2<bool>(mode <= 1u);
+    var uv = layer.tex_coords.xy;
+    var weight = 1.0;
+    if (any(wraps)) {
+        let repeated = fract(uv);
+        let mirrored = 1.0 - abs(fract(uv * 0.5) * 2.0 - 1.0);
+        uv = select(select(uv, repeated, address == vec2<u32>(1u)), mirrored, address == vec2<u32>(2u));
+    }
+    if (mode == 1u) {
+        if (any(uv < vec2<f32>(0.0)) || any(uv > vec2<f32>(1.0))) {
+            weight = 0.0;
+        }
+    } else if (mode == 2u) {
+        // fit_params.xy is the tile pitch over the tile size; the excess is spacing.
+        uv = fract(uv) * layer.fit_params.xy;
+        if (any(uv > vec2<f32>(1.0))) {
+            weight = 0.0;
+        }
+    } else if (mode == 3u) {
+        // Coordinates are in texture-size units from each edge, so the corners map 1:1
+        // and the middle stretches over whatever length is left.
+        let start_inset = layer.fit_params.xy;
+        let end_inset = layer.fit_params.zw;
+        let from_start = layer.tex_coords.xy;
+        let from_end = layer.tex_coords.zw;
+        let middle_length = max(from_start + from_end - start_inset - end_inset, vec2<f32>(1e-6));
+        let middle = start_inset
+            + (from_start - start_inset) * (1.0 - start_inset - end_inset) / middle_length;
+        uv = select(select(middle, 1.0 - from_end, from_end <= end_inset), from_start, from_start < start_inset);
+    }
+    let half_texel = 0.5 / vec2<f32>(texture_size);
+    let region_uv = mix(layer.region.xy, layer.region.zw, uv);
+    let clamped = clamp(region_uv, layer.region.xy + half_texel, layer.region.zw - half_texel);
+    // A wrapped axis spanning the whole texture lets the sampler filter across the seam.
+    let spans_texture = abs(layer.region.zw - layer.region.xy) >= vec2<f32>(1.0);
+    return vec3<f32>(select(clamped, region_uv, wraps & spans_texture), weight);
+}
+
+// Scales fit-space derivatives into texture UVs, so mipmapped textures pick the level
+// matching their on-screen size. Textures without mips always sample level 0.
+fn layer_uv_gradient_scale(layer: TextureLayerInputs, layer_flags: u32) -> vec2<f32> {
+    let tile_scale = select(vec2<f32>(1.0), layer.fit_params.xy, (layer_flags & 3u) == 2u);
+    return abs(layer.region.zw - layer.region.xy) * tile_scale;
+}
+
+// Converts a packed YUV texel to linear premultiplied RGB. The luma plane fills the top
+// two thirds of the texture and is sampled by the caller; the half-resolution chroma planes
+// below it are fetched and bilinearly interpolated here.
+fn yuv_to_linear_pma(t: texture_2d<f32>, uv: vec2<f32>, luma: f32, layer_flags: u32) -> vec4<f32> {
+    let size = vec2<i32>(textureDimensions(t));
+    let luma_height = size.y * 2 / 3;
+    let chroma_size = vec2<i32>(size.x / 2, size.y - luma_height);
+    // NV12 interleaves U and V across the full width; I420 places V to the right of U.
+    let is_nv12 = ((layer_flags >> 7u) & 3u) == 1u;
+    let chroma_step = select(1, 2, is_nv12);
+    let u_origin = vec2<i32>(0, luma_height);
+    let v_origin = select(vec2<i32>(chroma_size.x, luma_height), vec2<i32>(1, luma_height), is_nv12);
+
+    let chroma_pos = uv * vec2<f32>(size) * 0.5 - 0.5;
+    let chroma_base = floor(chroma_pos);
+    let chroma_fract = chroma_pos - chroma_base;
+    var chroma = vec2<f32>(0.0);
+    for (var i = 0; i < 4; i++) {
+        let offset = vec2<i32>(i & 1, i >> 1);
+        let texel = clamp(vec2<i32>(chroma_base) + offset, vec2<i32>(0), chroma_size - 1);
+        let weights = select(1.0 - chroma_fract, chroma_fract, offset == vec2<i32>(1));
+        let column = vec2<i32>(texel.x * chroma_step, texel.y);
+        chroma += vec2<f32>(
+            textureLoad(t, u_origin + column, 0).r,
+            textureLoad(t, v_origin + column, 0).r,
+        ) * weights.x * weights.y;
+    }
+
+    var y = luma;
+    var c = chroma - vec2<f32>(128.0 / 255.0);
+    if ((layer_flags & 1024u) == 0u) {
+        y = (luma - 16.0 / 255.0) * (255.0 / 219.0);
+        c = c * (255.0 / 224.0);
+    }
+    var rgb: vec3<f32>;
+    if ((layer_flags & 512u) != 0u) {
+        rgb = vec3<f32>(y + 1.5748 * c.y, y - 0.187324 * c.x - 0.468124 * c.y, y + 1.8556 * c.x);
+    } else {
+        rgb = vec3<f32>(y + 1.402 * c.y, y - 0.344136 * c.x - 0.714136 * c.y, y + 1.772 * c.x);
+    }
+    return vec4<f32>(to_linear(clamp(rgb, vec3<f32>(0.0), vec3<f32>(1.0))), 1.0);
+}
+
+fn sample_shape_layer0(layer: TextureLayerInputs, flags: u32) -> vec4<f32> {
+    let layer_flags = (flags >> 2u) & 2047u;
+    let uv = resolve_layer_uv(layer, layer_flags, textureDimensions(t_shape_layer0));
+    let gradient_scale = layer_uv_gradient_scale(layer, layer_flags);
+    let color = textureSampleGrad(
+        t_shape_layer0,
+        s_shape_layer0,
+        uv.xy,
+        layer.tex_coords_dx * gradient_scale,
+        layer.tex_coords_dy * gradient_scale,
+    );
+    if ((layer_flags & 384u) != 0u) {
+        return yuv_to_linear_pma(t_shape_layer0, uv.xy, color.r, layer_flags) * uv.z;
+    }
+    return color * uv.z;
+}
+
+fn sample_shape_layer1(layer: TextureLayerInputs, flags: u32) -> vec4<f32> {
+    let layer_flags = (flags >> 13u) & 2047u;
+    let uv = resolve_layer_uv(layer, layer_flags, textureDimensions(t_shape_layer1));
+    let gradient_scale = layer_uv_gradient_scale(layer, layer_flags);
+    let color = textureSampleGrad(
+        t_shape_layer1,
+        s_shape_layer1,
+        uv.xy,
+        layer.tex_coords_dx * gradient_scale,
+        layer.tex_coords_dy * gradient_scale,
+    );
+    if ((layer_flags & 384u) != 0u) {
+        return yuv_to_linear_pma(t_shape_layer1, uv.xy, color.r, layer_flags) * uv.z;
+    }
+    return color * uv.z;
+}
+
+// Composites a sampled layer over the color beneath it. R8 mask layers (bit 6 of the
+// layer's flags) instead tint: they scale the color beneath by their coverage.
+fn apply_texture_layer(layer_pma: vec4<f32>, below_pma: vec4<f32>, layer_flags: u32) -> vec4<f32> {
+    if ((layer_flags & 64u) != 0u) {
+        return below_pma * layer_pma.r;
+    }
+    return layer_pma + below_pma * (1.0 - layer_pma.a);
+}
+
 // Computes the final premultiplied color for a fragment given fill color, texture
 // coordinates, and AA coverage.
 fn compute_fragment_color(
     color: vec4<f32>,
-    layer0_tex_coords: vec2<f32>,
-    layer1_tex_coords: vec2<f32>,
+    layer0: TextureLayerInputs,
+    layer1: TextureLayerInputs,
     coverage: f32,
     texture_flags: f32,
 ) -> vec4<f32> {
@@ -379,27 +569,26 @@ fn compute_fragment_color(
 
     // Fast path: no textures bound — solid fill only. Skip both texture samples.
     let flags = u32(texture_flags);
-    if (flags == 0u) {
+    if ((flags & 3u) == 0u) {
         return fill_pma * coverage;
     }
 
     // At least one texture layer is active.
-    // Use textureSampleLevel (explicit LOD 0) instead of textureSample so that
-    // sampling is valid inside non-uniform control flow. Our textures are created
-    // without mipmaps (mip_level_count = 1), so LOD 0 is always correct.
+    // Use textureSampleGrad with derivatives taken in the entry point instead of
+    // textureSample so that sampling is valid inside non-uniform control flow.
     // Data is premultiplied (Rgba8UnormSrgb -> linear automatically).
 
     // Compose: base = texture layer 0 over shape fill, then layer 1 over result.
     var base_pma = fill_pma;
     if ((flags & 1u) != 0u) {
-            let layer0_pma = textureSampleLevel(t_shape_layer0, s_shape_layer0, layer0_tex_coords, 0.0);
-            base_pma = layer0_pma + fill_pma * (1.0 - layer0_pma.a);
+            let layer0_pma = sample_shape_layer0(layer0, flags);
+            base_pma = apply_texture_layer(layer0_pma, fill_pma, flags >> 2u);
     }
 
     var final_pma = base_pma;
     if ((flags & 2u) != 0u) {
-            let layer1_pma = textureSampleLevel(t_shape_layer1, s_shape_layer1, layer1_tex_coords, 0.0);
-            final_pma = layer1_pma + base_pma * (1.0 - layer1_pma.a);
+            let layer1_pma = sample_shape_layer1(layer1, flags);
+            final_pma = apply_texture_layer(layer1_pma, base_pma, flags >> 13u);
     }
 
     // Apply AA coverage: scale premultiplied color by coverage factor.
@@ -409,8 +598,8 @@ fn compute_fragment_color(
 }
 
 fn compute_gradient_fragment_color(
-    layer0_tex_coords: vec2<f32>,
-    layer1_tex_coords: vec2<f32>,
+    layer0: TextureLayerInputs,
+    layer1: TextureLayerInputs,
     coverage: f32,
     texture_flags: f32,
     model_pos: vec2<f32>,
@@ -423,20 +612,20 @@ fn compute_gradient_fragment_color(
     );
 
     let flags = u32(texture_flags);
-    if (flags == 0u) {
+    if ((flags & 3u) == 0u) {
         return fill_pma * coverage;
     }
 
     var base_pma = fill_pma;
     if ((flags & 1u) != 0u) {
-            let layer0_pma = textureSampleLevel(t_shape_layer0, s_shape_layer0, layer0_tex_coords, 0.0);
-            base_pma = layer0_pma + fill_pma * (1.0 - layer0_pma.a);
+            let layer0_pma = sample_shape_layer0(layer0, flags);
+            base_pma = apply_texture_layer(layer0_pma, fill_pma, flags >> 2u);
     }
 
     var final_pma = base_pma;
     if ((flags & 2u) != 0u) {
-            let layer1_pma = textureSampleLevel(t_shape_layer1, s_shape_layer1, layer1_tex_coords, 0.0);
-            final_pma = layer1_pma + base_pma * (1.0 - layer1_pma.a);
+            let layer1_pma = sample_shape_layer1(layer1, flags);
+            final_pma = apply_texture_layer(layer1_pma, base_pma, flags >> 13u);
     }
 
     return final_pma * coverage;
@@ -445,8 +634,8 @@ fn compute_gradient_fragment_color(
 fn compute_fragment_color_with_backdrop(
     fragment_position: vec4<f32>,
     color: vec4<f32>,
-    layer0_tex_coords: vec2<f32>,
-    layer1_tex_coords: vec2<f32>,
+    layer0: TextureLayerInputs,
+    layer1: TextureLayerInputs,
     coverage: f32,
     texture_flags: f32,
 ) -> vec4<f32> {
@@ -458,14 +647,14 @@ fn compute_fragment_color_with_backdrop(
     let flags = u32(texture_flags);
     var base_pma = fill_pma + backdrop_pma * (1.0 - fill_pma.a);
     if ((flags & 1u) != 0u) {
-            let layer0_pma = textureSampleLevel(t_shape_layer0, s_shape_layer0, layer0_tex_coords, 0.0);
-            base_pma = layer0_pma + base_pma * (1.0 - layer0_pma.a);
+            let layer0_pma = sample_shape_layer0(layer0, flags);
+            base_pma = apply_texture_layer(layer0_pma, base_pma, flags >> 2u);
     }
 
     var final_pma = base_pma;
     if ((flags & 2u) != 0u) {
-            let layer1_pma = textureSampleLevel(t_shape_layer1, s_shape_layer1, layer1_tex_coords, 0.0);
-            final_pma = layer1_pma + base_pma * (1.0 - layer1_pma.a);
+            let layer1_pma = sample_shape_layer1(layer1, flags);
+            final_pma = apply_texture_layer(layer1_pma, base_pma, flags >> 13u);
     }
 
     return final_pma * coverage;
@@ -473,8 +662,8 @@ fn compute_fragment_color_with_backdrop(
 
 fn compute_gradient_fragment_color_with_backdrop(
     fragment_position: vec4<f32>,
-    layer0_tex_coords: vec2<f32>,
-    layer1_tex_coords: vec2<f32>,
+    layer0: TextureLayerInputs,
+    layer1: TextureLayerInputs,
     coverage: f32,
     texture_flags: f32,
     model_pos: vec2<f32>,
@@ -491,14 +680,14 @@ fn compute_gradient_fragment_color_with_backdrop(
     let flags = u32(texture_flags);
     var base_pma = fill_pma + backdrop_pma * (1.0 - fill_pma.a);
     if ((flags & 1u) != 0u) {
-            let layer0_pma = textureSampleLevel(t_shape_layer0, s_shape_layer0, layer0_tex_coords, 0.0);
-            base_pma = layer0_pma + base_pma * (1.0 - layer0_pma.a);
+            let layer0_pma = sample_shape_layer0(layer0, flags);
+            base_pma = apply_texture_layer(layer0_pma, base_pma, flags >> 2u);
     }
 
     var final_pma = base_pma;
     if ((flags & 2u) != 0u) {
-            let layer1_pma = textureSampleLevel(t_shape_layer1, s_shape_layer1, layer1_tex_coords, 0.0);
-            final_pma = layer1_pma + base_pma * (1.0 - layer1_pma.a);
+            let layer1_pma = sample_shape_layer1(layer1, flags);
+            final_pma = apply_texture_layer(layer1_pma, base_pma, flags >> 13u);
     }
 
     return final_pma * coverage;
@@ -507,15 +696,19 @@ fn compute_gradient_fragment_color_with_backdrop(
 @fragment
 fn fs_main(
     @location(0) color: vec4<f32>,
-    @location(1) layer0_tex_coords: vec2<f32>,
-    @location(2) layer1_tex_coords: vec2<f32>,
+    @location(1) layer0_tex_coords: vec4<f32>,
+    @location(2) layer1_tex_coords: vec4<f32>,
     @location(3) coverage: f32,
     @location(4) @interpolate(flat) texture_flags: f32,
+    @location(5) @interpolate(flat) layer0_region: vec4<f32>,
+    @location(6) @interpolate(flat) layer1_region: vec4<f32>,
+    @location(7) @interpolate(flat) layer0_fit_params: vec4<f32>,
+    @location(8) @interpolate(flat) layer1_fit_params: vec4<f32>,
 ) -> @location(0) vec4<f32> {
     return compute_fragment_color(
         color,
-        layer0_tex_coords,
-        layer1_tex_coords,
+        texture_layer_inputs(layer0_tex_coords, layer0_region, layer0_fit_params),
+        texture_layer_inputs(layer1_tex_coords, layer1_region, layer1_fit_params),
         coverage,
         texture_flags,
     );
@@ -525,16 +718,20 @@ fn fs_main(
 fn fs_main_gradient(
     @builtin(position) fragment_position: vec4<f32>,
     @location(0) color: vec4<f32>,
-    @location(1) layer0_tex_coords: vec2<f32>,
-    @location(2) layer1_tex_coords: vec2<f32>,
+    @location(1) layer0_tex_coords: vec4<f32>,
+    @location(2) layer1_tex_coords: vec4<f32>,
     @location(3) coverage: f32,
     @location(4) @interpolate(flat) texture_flags: f32,
     @location(5) model_pos: vec2<f32>,
     @location(6) screen_pos: vec2<f32>,
+    @location(7) @interpolate(flat) layer0_region: vec4<f32>,
+    @location(8) @interpolate(flat) layer1_region: vec4<f32>,
+    @location(9) @interpolate(flat) layer0_fit_params: vec4<f32>,
+    @location(10) @interpolate(flat) layer1_fit_params: vec4<f32>,
 ) -> @location(0) vec4<f32> {
     return compute_gradient_fragment_color(
-        layer0_tex_coords,
-        layer1_tex_coords,
+        texture_layer_inputs(layer0_tex_coords, layer0_region, layer0_fit_params),
+        texture_layer_inputs(layer1_tex_coords, layer1_region, layer1_fit_params),
         coverage,
         texture_flags,
         model_pos,
@@ -557,15 +754,19 @@ fn fs_stencil_only() -> @location(0) vec4<f32> {
 @fragment
 fn fs_passthrough(
     @location(0) color: vec4<f32>,
-    @location(1) layer0_tex_coords: vec2<f32>,
-    @location(2) layer1_tex_coords: vec2<f32>,
+    @location(1) layer0_tex_coords: vec4<f32>,
+    @location(2) layer1_tex_coords: vec4<f32>,
     @location(3) coverage: f32,
     @location(4) @interpolate(flat) texture_flags: f32,
+    @location(5) @interpolate(flat) layer0_region: vec4<f32>,
+    @location(6) @interpolate(flat) layer1_region: vec4<f32>,
+    @location(7) @interpolate(flat) layer0_fit_params: vec4<f32>,
+    @location(8) @interpolate(flat) layer1_fit_params: vec4<f32>,
 ) -> @location(0) vec4<f32> {
     return compute_fragment_color(
         color,
-        layer0_tex_coords,
-        layer1_tex_coords,
+        texture_layer_inputs(layer0_tex_coords, layer0_region, layer0_fit_params),
+        texture_layer_inputs(layer1_tex_coords, layer1_region, layer1_fit_params),
         coverage,
         texture_flags,
     );
@@ -575,16 +776,20 @@ fn fs_passthrough(
 fn fs_passthrough_gradient(
     @builtin(position) fragment_position: vec4<f32>,
     @location(0) color: vec4<f32>,
-    @location(1) layer0_tex_coords: vec2<f32>,
-    @location(2) layer1_tex_coords: vec2<f32>,
+    @location(1) layer0_tex_coords: vec4<f32>,
+    @location(2) layer1_tex_coords: vec4<f32>,
     @location(3) coverage: f32,
     @location(4) @interpolate(flat) texture_flags: f32,
     @location(5) model_pos: vec2<f32>,
     @location(6) screen_pos: vec2<f32>,
+    @location(7) @interpolate(flat) layer0_region: vec4<f32>,
+    @location(8) @interpolate(flat) layer1_region: vec4<f32>,
+    @location(9) @interpolate(flat) layer0_fit_params: vec4<f32>,
+    @location(10) @interpolate(flat) layer1_fit_params: vec4<f32>,
 ) -> @location(0) vec4<f32> {
     return compute_gradient_fragment_color(
-        layer0_tex_coords,
-        layer1_tex_coords,
+        texture_layer_inputs(layer0_tex_coords, layer0_region, layer0_fit_params),
+        texture_layer_inputs(layer1_tex_coords, layer1_region, layer1_fit_params),
         coverage,
         texture_flags,
         model_pos,
@@ -593,20 +798,38 @@ fn fs_passthrough_gradient(
     );
 }
 
+// Composites a pre-rendered subtree through the clipping parent's geometry. The subtree
+// texture is sampled at the fragment position and scaled by the parent's AA coverage,
+// which softens the clip edge that the stencil alone would leave aliased.
+@fragment
+fn fs_soft_clip_composite(
+    @builtin(position) fragment_position: vec4<f32>,
+    @location(3) coverage: f32,
+) -> @location(0) vec4<f32> {
+    let subtree_uv = (fragment_position.xy - material_params.backdrop_sampling.capture_origin)
+        * material_params.backdrop_sampling.inverse_capture_size;
+    let subtree_pma = textureSampleLevel(t_backdrop_layer, s_backdrop_layer, subtree_uv, 0.0);
+    return subtree_pma * coverage;
+}
+
 @fragment
 fn fs_backdrop_passthrough(
     @builtin(position) fragment_position: vec4<f32>,
     @location(0) color: vec4<f32>,
-    @location(1) layer0_tex_coords: vec2<f32>,
-    @location(2) layer1_tex_coords: vec2<f32>,
+    @location(1) layer0_tex_coords: vec4<f32>,
+    @location(2) layer1_tex_coords: vec4<f32>,
     @location(3) coverage: f32,
     @location(4) @interpolate(flat) texture_flags: f32,
+    @location(5) @interpolate(flat) layer0_region: vec4<f32>,
+    @location(6) @interpolate(flat) layer1_region: vec4<f32>,
+    @location(7) @interpolate(flat) layer0_fit_params: vec4<f32>,
+    @location(8) @interpolate(flat) layer1_fit_params: vec4<f32>,
 ) -> @location(0) vec4<f32> {
     return compute_fragment_color_with_backdrop(
         fragment_position,
         color,
-        layer0_tex_coords,
-        layer1_tex_coords,
+        texture_layer_inputs(layer0_tex_coords, layer0_region, layer0_fit_params),
+        texture_layer_inputs(layer1_tex_coords, layer1_region, layer1_fit_params),
         coverage,
         texture_flags,
     );
@@ -616,17 +839,21 @@ fn fs_backdrop_passthrough(
 fn fs_backdrop_passthrough_gradient(
     @builtin(position) fragment_position: vec4<f32>,
     @location(0) color: vec4<f32>,
-    @location(1) layer0_tex_coords: vec2<f32>,
-    @location(2) layer1_tex_coords: vec2<f32>,
+    @location(1) layer0_tex_coords: vec4<f32>,
+    @location(2) layer1_tex_coords: vec4<f32>,
     @location(3) coverage: f32,
     @location(4) @interpolate(flat) texture_flags: f32,
     @location(5) model_pos: vec2<f32>,
     @location(6) screen_pos: vec2<f32>,
+    @location(7) @interpolate(flat) layer0_region: vec4<f32>,
+    @location(8) @interpolate(flat) layer1_region: vec4<f32>,
+    @location(9) @interpolate(flat) layer0_fit_params: vec4<f32>,
+    @location(10) @interpolate(flat) layer1_fit_params: vec4<f32>,
 ) -> @location(0) vec4<f32> {
     return compute_gradient_fragment_color_with_backdrop(
         fragment_position,
-        layer0_tex_coords,
-        layer1_tex_coords,
+        texture_layer_inputs(layer0_tex_coords, layer0_region, layer0_fit_params),
+        texture_layer_inputs(layer1_tex_coords, layer1_region, layer1_fit_params),
         coverage,
         texture_flags,
         model_pos,
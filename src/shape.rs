@@ -40,7 +40,7 @@ use crate::gradient::types::Fill;
 use crate::pipeline::{create_buffer_init, BackdropSamplingUniform};
 use crate::util::{GradientCache, PoolManager};
 use crate::vertex::{CustomVertex, InstanceTransform};
-use crate::{Color, Stroke};
+use crate::{Color, SamplerOptions, ShapeOverflow, Stroke};
 use ahash::AHashMap;
 use lyon::lyon_tessellation::{
     BuffersBuilder, FillOptions, FillTessellator, FillVertex, VertexBuffers,
@@ -65,7 +65,16 @@ pub struct CachedShapeHandle {
 pub(crate) enum ShapeTextureBinding {
     #[default]
     None,
-    Managed(u64),
+    Managed {
+        texture_id: u64,
+        sampler: SamplerOptions,
+    },
+    /// Atlas page resolved from a managed texture id when the shape was added; entries on
+    /// the same page share this binding so their draws batch.
+    AtlasPage {
+        page_id: u64,
+        sampler: SamplerOptions,
+    },
     Direct {
         texture_id: u64,
         bind_group: Arc<wgpu::BindGroup>,
@@ -77,11 +86,8 @@ impl ShapeTextureBinding {
         !matches!(self, Self::None)
     }
 
-    pub(crate) fn managed_texture_id(&self) -> Option<u64> {
-        match self {
-            Self::Managed(texture_id) => Some(*texture_id),
-            Self::None | Self::Direct { .. } => None,
-        }
+    pub(crate) fn is_managed(&self) -> bool {
+        matches!(self, Self::Managed { .. } | Self::AtlasPage { .. })
     }
 }
 
@@ -89,7 +95,26 @@ impl PartialEq for ShapeTextureBinding {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Self::None, Self::None) => true,
-            (Self::Managed(left), Self::Managed(right)) => left == right,
+            (
+                Self::Managed {
+                    texture_id: left,
+                    sampler: left_sampler,
+                },
+                Self::Managed {
+                    texture_id: right,
+                    sampler: right_sampler,
+                },
+            ) => left == right && left_sampler == right_sampler,
+            (
+                Self::AtlasPage {
+                    page_id: left,
+                    sampler: left_sampler,
+                },
+                Self::AtlasPage {
+                    page_id: right,
+                    sampler: right_sampler,
+                },
+            ) => left == right && left_sampler == right_sampler,
             (
                 Self::Direct {
                     texture_id: left, ..
@@ -1106,6 +1131,10 @@ impl PathShape {
 }
 
 /// Controls how bound textures are fit into a shape's local texture-coordinate space.
+///
+/// Modes that depend on the texture's size fall back to [`ShapeTextureFitMode::Stretch`]
+/// while the texture is not loaded. With a source rect, the rect is fit instead of the whole
+/// texture.
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
 pub enum ShapeTextureFitMode {
     /// Normalize the shape bounds to `[0, 1]` and stretch the texture to cover them fully.
@@ -1117,12 +1146,153 @@ pub enum ShapeTextureFitMode {
     /// not crop to transparency; the unclipped area instead shows the underlying fill/background
     /// according to normal compositing.
     OriginalSize,
+    /// Scale the texture uniformly until it covers the shape bounds, cropping the overflow.
+    /// `alignment` picks which part of the texture stays visible.
+    Cover { alignment: TextureAlignment },
+    /// Scale the texture uniformly until it fits inside the shape bounds. `alignment` places
+    /// it within the leftover space, which shows the shape's fill.
+    Contain { alignment: TextureAlignment },
+    /// Repeat the texture at one texel per physical pixel from the shape's top-left corner,
+    /// leaving `spacing` physical pixels of fill between tiles on each axis.
+    Tile { spacing: (u32, u32) },
+    /// Keep the texture's corners at one texel per physical pixel and stretch its edges and
+    /// center to fill the shape, as for scalable panels and buttons. Corners shrink
+    /// proportionally when the shape is smaller than the opposing insets combined.
+    NineSlice { insets: NineSliceInsets },
+}
+
+/// Placement of a [`ShapeTextureFitMode::Cover`] or [`ShapeTextureFitMode::Contain`] texture
+/// relative to the shape bounds.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum TextureAlignment {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    #[default]
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl TextureAlignment {
+    /// Fraction of the leftover space placed before the texture on each axis.
+    pub(crate) fn factors(self) -> [f32; 2] {
+        match self {
+            Self::TopLeft => [0.0, 0.0],
+            Self::Top => [0.5, 0.0],
+            Self::TopRight => [1.0, 0.0],
+            Self::Left => [0.0, 0.5],
+            Self::Center => [0.5, 0.5],
+            Self::Right => [1.0, 0.5],
+            Self::BottomLeft => [0.0, 1.0],
+            Self::Bottom => [0.5, 1.0],
+            Self::BottomRight => [1.0, 1.0],
+        }
+    }
+}
+
+/// Texel distances from each texture edge that split a
+/// [`ShapeTextureFitMode::NineSlice`] texture into corners, edges, and center.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct NineSliceInsets {
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+    pub left: u32,
+}
+
+impl NineSliceInsets {
+    pub fn new(top: u32, right: u32, bottom: u32, left: u32) -> Self {
+        Self {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+
+    /// The same inset on all four edges.
+    pub fn uniform(inset: u32) -> Self {
+        Self::new(inset, inset, inset, inset)
+    }
+}
+
+/// Sub-rectangle of a texture sampled by a shape layer, used for atlases and sprite sheets.
+///
+/// Shapes that sample different regions of the same texture share its bind group, so they
+/// still batch together. Sampling is clamped to the region's edge texels, so neighbouring
+/// atlas entries never bleed in.
+#[derive(Copy, Clone, Debug)]
+pub enum TextureSourceRect {
+    /// `[min, max]` corners in normalized texture coordinates.
+    Uv([(f32, f32); 2]),
+    /// `[min, max]` corners in texels. Resolved against the texture's dimensions when the
+    /// shape is queued; the whole texture is sampled if the texture is not loaded yet.
+    Texels([(u32, u32); 2]),
+}
+
+impl TextureSourceRect {
+    /// Normalized `[min, max]` corners, or `None` when texel corners cannot be resolved.
+    pub(crate) fn uv_rect(self, texture_dimensions: Option<(u32, u32)>) -> Option<[(f32, f32); 2]> {
+        match self {
+            TextureSourceRect::Uv(uv_rect) => Some(uv_rect),
+            TextureSourceRect::Texels([(min_x, min_y), (max_x, max_y)]) => {
+                let (texture_width, texture_height) = texture_dimensions?;
+                let texture_width = texture_width.max(1) as f32;
+                let texture_height = texture_height.max(1) as f32;
+                Some([
+                    (min_x as f32 / texture_width, min_y as f32 / texture_height),
+                    (max_x as f32 / texture_width, max_y as f32 / texture_height),
+                ])
+            }
+        }
+    }
+
+    fn bit_pattern(self) -> (u8, [u32; 4]) {
+        match self {
+            TextureSourceRect::Uv([(min_x, min_y), (max_x, max_y)]) => (
+                0,
+                [
+                    min_x.to_bits(),
+                    min_y.to_bits(),
+                    max_x.to_bits(),
+                    max_y.to_bits(),
+                ],
+            ),
+            TextureSourceRect::Texels([(min_x, min_y), (max_x, max_y)]) => {
+                (1, [min_x, min_y, max_x, max_y])
+            }
+        }
+    }
+}
+
+impl PartialEq for TextureSourceRect {
+    fn eq(&self, other: &Self) -> bool {
+        self.bit_pattern() == other.bit_pattern()
+    }
+}
+
+impl Eq for TextureSourceRect {}
+
+impl std::hash::Hash for TextureSourceRect {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.bit_pattern().hash(state);
+    }
 }
 
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
 pub struct ShapeTextureOptions {
     pub texture_id: Option<u64>,
     pub fit_mode: ShapeTextureFitMode,
+    /// Region of the texture to sample; `None` samples the whole texture.
+    pub source_rect: Option<TextureSourceRect>,
+    /// Sampler for this draw; `None` uses the texture's
+    /// [`TextureManager::set_texture_sampler`](crate::TextureManager::set_texture_sampler)
+    /// default.
+    pub sampler: Option<SamplerOptions>,
 }
 
 impl ShapeTextureOptions {
@@ -1130,6 +1300,8 @@ impl ShapeTextureOptions {
         Self {
             texture_id: Some(texture_id),
             fit_mode: ShapeTextureFitMode::Stretch,
+            source_rect: None,
+            sampler: None,
         }
     }
 
@@ -1137,12 +1309,36 @@ impl ShapeTextureOptions {
         self.fit_mode = fit_mode;
         self
     }
+
+    pub fn source_rect(mut self, source_rect: TextureSourceRect) -> Self {
+        self.source_rect = Some(source_rect);
+        self
+    }
+
+    /// Overrides the texture's sampler for this draw, e.g. nearest filtering for pixel art.
+    pub fn sampler(mut self, sampler: SamplerOptions) -> Self {
+        self.sampler = Some(sampler);
+        self
+    }
+
+    /// Binding before the texture manager resolves atlas placement and sampler defaults.
+    fn initial_binding(&self) -> ShapeTextureBinding {
+        self.texture_id
+            .map_or(ShapeTextureBinding::None, |texture_id| {
+                ShapeTextureBinding::Managed {
+                    texture_id,
+                    sampler: self.sampler.unwrap_or_default(),
+                }
+            })
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct ShapeDrawCommandOptions {
     pub transform: Option<InstanceTransform>,
-    pub clips_children: bool,
+    /// How descendants may overflow this shape; `clips_children` is shorthand for it.
+    pub overflow: ShapeOverflow,
+    pub soft_clip: bool,
     pub background_texture: ShapeTextureOptions,
     pub foreground_texture: ShapeTextureOptions,
     pub fill: Option<Fill>,
@@ -1152,7 +1348,8 @@ impl Default for ShapeDrawCommandOptions {
     fn default() -> Self {
         Self {
             transform: None,
-            clips_children: true,
+            overflow: ShapeOverflow::Hidden,
+            soft_clip: false,
             background_texture: ShapeTextureOptions::default(),
             foreground_texture: ShapeTextureOptions::default(),
             fill: None,
@@ -1170,8 +1367,34 @@ impl ShapeDrawCommandOptions {
         self
     }
 
+    /// Shorthand for [`Self::overflow`]: `true` sets [`ShapeOverflow::Hidden`] and `false` sets
+    /// [`ShapeOverflow::Visible`]. Whichever of the two is called last wins.
     pub fn clips_children(mut self, clips_children: bool) -> Self {
-        self.clips_children = clips_children;
+        self.overflow = if clips_children {
+            ShapeOverflow::Hidden
+        } else {
+            ShapeOverflow::Visible
+        };
+        self
+    }
+
+    /// Sets how descendants may overflow this shape.
+    ///
+    /// [`ShapeOverflow::ClipX`] and [`ShapeOverflow::ClipY`] clip along one axis only: children
+    /// are clipped to the band this shape's geometry sweeps along the other axis. `Visible` is
+    /// the same as `clips_children(false)`.
+    pub fn overflow(mut self, overflow: ShapeOverflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Clips children to this shape's anti-aliased coverage instead of its hard stencil edge.
+    ///
+    /// The subtree is rendered into an offscreen texture and composited through this shape's
+    /// geometry, so it costs one extra offscreen pass per soft-clipping parent. Only applies
+    /// when children are clipped on both axes.
+    pub fn soft_clip(mut self, soft_clip: bool) -> Self {
+        self.soft_clip = soft_clip;
         self
     }
 
@@ -1217,6 +1440,16 @@ impl ShapeDrawCommandOptions {
         self
     }
 
+    pub fn background_texture_source_rect(mut self, source_rect: TextureSourceRect) -> Self {
+        self.background_texture.source_rect = Some(source_rect);
+        self
+    }
+
+    pub fn foreground_texture_source_rect(mut self, source_rect: TextureSourceRect) -> Self {
+        self.foreground_texture.source_rect = Some(source_rect);
+        self
+    }
+
     pub fn fill(mut self, fill: Fill) -> Self {
         self.fill = Some(fill);
         self
@@ -1255,9 +1488,14 @@ pub(crate) struct CachedShapeDrawData {
     pub(crate) backdrop_gradient_texture_id: Option<u64>,
     /// Whether this node is a leaf in the draw tree (no children).
     pub(crate) is_leaf: bool,
-    /// When `false`, skip stencil increment/decrement for this parent
-    /// (children render without being clipped to this shape).
-    pub(crate) clips_children: bool,
+    /// How children are clipped; `Visible` skips stencil increment/decrement for this parent
+    /// and `ClipX`/`ClipY` clip to a band along one axis.
+    pub(crate) overflow: ShapeOverflow,
+    /// Band geometry used as the stencil clip for single-axis overflow when the transform
+    /// rules out a scissor.
+    pub(crate) axis_clip_geometry: Option<Box<CachedShapeDrawData>>,
+    /// When `true`, children are composited through this shape's AA coverage.
+    pub(crate) soft_clip: bool,
 }
 
 impl CachedShapeDrawData {
@@ -1270,16 +1508,12 @@ impl CachedShapeDrawData {
             // Data from options
             transform: options.transform,
             texture_bindings: [
-                options
-                    .background_texture
-                    .texture_id
-                    .map_or(ShapeTextureBinding::None, ShapeTextureBinding::Managed),
-                options
-                    .foreground_texture
-                    .texture_id
-                    .map_or(ShapeTextureBinding::None, ShapeTextureBinding::Managed),
+                options.background_texture.initial_binding(),
+                options.foreground_texture.initial_binding(),
             ],
-            clips_children: options.clips_children,
+            overflow: options.overflow,
+            axis_clip_geometry: None,
+            soft_clip: options.soft_clip && options.overflow == ShapeOverflow::Hidden,
             color_override: match options.fill.as_ref() {
                 Some(Fill::Solid(color)) => Some(color.normalize()),
                 _ => None,
@@ -1297,6 +1531,15 @@ impl CachedShapeDrawData {
         }
     }
 
+    /// Geometry whose stencil footprint clips this shape's children: the single-axis band
+    /// when one was synthesized, otherwise the shape itself.
+    pub(crate) fn stencil_clip_shape_mut(&mut self) -> &mut CachedShapeDrawData {
+        match self.axis_clip_geometry {
+            Some(ref mut axis_clip_geometry) => axis_clip_geometry,
+            None => self,
+        }
+    }
+
     pub fn refresh_gradient_bind_group(
         &mut self,
         gradient_cache: &mut GradientCache,
@@ -1719,6 +1962,8 @@ pub(crate) trait DrawShapeCommand {
     fn has_gradient_fill(&self) -> bool;
     fn gradient_bind_group(&self) -> Option<&std::sync::Arc<wgpu::BindGroup>>;
     fn clips_children(&self) -> bool;
+    fn overflow(&self) -> ShapeOverflow;
+    fn soft_clip(&self) -> bool;
     fn is_rect(&self) -> bool;
     fn rect_bounds(&self) -> Option<[(f32, f32); 2]>;
 }
@@ -1781,7 +2026,17 @@ impl DrawShapeCommand for CachedShapeDrawData {
 
     #[inline]
     fn clips_children(&self) -> bool {
-        self.clips_children
+        self.overflow != ShapeOverflow::Visible
+    }
+
+    #[inline]
+    fn overflow(&self) -> ShapeOverflow {
+        self.overflow
+    }
+
+    #[inline]
+    fn soft_clip(&self) -> bool {
+        self.soft_clip
     }
 
     #[inline]
@@ -1799,7 +2054,7 @@ impl DrawShapeCommand for CachedShapeDrawData {
 mod tests {
     use super::{
         find_boundary_edges, generate_aa_fringe, AaFringeScratch, BoundaryVertexKey, CustomVertex,
-        RectShape, Shape,
+        RectShape, Shape, ShapeDrawCommandOptions, ShapeOverflow, TextureSourceRect,
     };
     use crate::{util::PoolManager, Stroke};
     use lyon::lyon_tessellation::FillTessellator;
@@ -1916,4 +2171,39 @@ mod tests {
         assert_eq!(vertices.len(), 3);
         assert_eq!(indices.len(), 3);
     }
+
+    #[test]
+    fn texel_source_rect_resolves_against_texture_dimensions() {
+        let source_rect = TextureSourceRect::Texels([(16, 0), (48, 32)]);
+
+        assert_eq!(
+            source_rect.uv_rect(Some((64, 32))),
+            Some([(0.25, 0.0), (0.75, 1.0)])
+        );
+        assert_eq!(source_rect.uv_rect(None), None);
+        assert_eq!(
+            TextureSourceRect::Uv([(0.1, 0.2), (0.3, 0.4)]).uv_rect(None),
+            Some([(0.1, 0.2), (0.3, 0.4)])
+        );
+    }
+
+    #[test]
+    fn clips_children_and_overflow_set_the_same_property() {
+        let clip_x_then_visible = ShapeDrawCommandOptions::new()
+            .overflow(ShapeOverflow::ClipX)
+            .clips_children(false);
+        let visible_then_clip_x = ShapeDrawCommandOptions::new()
+            .clips_children(false)
+            .overflow(ShapeOverflow::ClipX);
+
+        assert_eq!(clip_x_then_visible.overflow, ShapeOverflow::Visible);
+        assert_eq!(visible_then_clip_x.overflow, ShapeOverflow::ClipX);
+        assert_eq!(
+            ShapeDrawCommandOptions::new()
+                .clips_children(false)
+                .clips_children(true)
+                .overflow,
+            ShapeOverflow::Hidden
+        );
+    }
 }
@@ -1,10 +1,76 @@
+mod atlas;
+mod budget;
+mod format;
+#[cfg(feature = "image")]
+mod image_loading;
+mod mipmap;
+mod sampler;
+mod yuv;
+
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
+pub(crate) use atlas::AtlasPlacement;
+use atlas::{AtlasEntry, AtlasPage, RepackPlan, ShelfPacker, TextureAtlas, ATLAS_PAGE_SIZE};
+use budget::TextureUsage;
+pub use budget::{TextureMemoryReport, TextureMemoryUsage};
+pub use format::{TextureFormat, TextureOptions};
+#[cfg(feature = "image")]
+pub use image_loading::DecodedImage;
+use mipmap::MipmapGenerator;
+pub use sampler::{SamplerOptions, TextureAddressMode, TextureFilter};
+use yuv::YuvLayout;
+pub use yuv::{YuvColorMatrix, YuvFormat, YuvOptions, YuvPlane, YuvRange};
+
 #[derive(Debug, thiserror::Error)]
 pub enum TextureManagerError {
     #[error("Texture {0} not found")]
     TextureNotFound(u64),
+    #[error("Texture {0} has a zero width or height")]
+    EmptyTexture(u64),
+    #[error("Texture {texture_id} data is {actual} bytes, expected {expected} for its dimensions")]
+    DataSizeMismatch {
+        texture_id: u64,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("Texture {0} does not fit into an empty atlas page")]
+    AtlasEntryTooLarge(u64),
+    #[error("Region at {origin:?} of size {size:?} is outside texture {texture_id}")]
+    RegionOutOfBounds {
+        texture_id: u64,
+        origin: (u32, u32),
+        size: (u32, u32),
+    },
+    #[error("Texture {texture_id} rows need at least {minimum} bytes, got {bytes_per_row}")]
+    BytesPerRowTooSmall {
+        texture_id: u64,
+        bytes_per_row: u32,
+        minimum: u32,
+    },
+    #[error("Texture {0} is not a YUV texture")]
+    NotYuvTexture(u64),
+    #[error("YUV texture {texture_id} takes {expected} planes, got {actual}")]
+    YuvPlaneCountMismatch {
+        texture_id: u64,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("Texture {texture_id} has format {format:?}, which shapes cannot sample")]
+    UnsupportedTextureFormat {
+        texture_id: u64,
+        format: wgpu::TextureFormat,
+    },
+    #[error("Texture {texture_id} lacks the {usage:?} usage")]
+    MissingTextureUsage {
+        texture_id: u64,
+        usage: wgpu::TextureUsages,
+    },
+    #[error("Texture {0} is not a single-sampled 2D texture with one layer")]
+    UnsupportedTextureLayout(u64),
+    #[cfg(feature = "image")]
+    #[error("Failed to decode image: {0}")]
+    ImageDecode(#[from] image::ImageError),
 }
 
 /// A manager for textures providing granular control over texture handling.
@@ -12,7 +78,20 @@ pub enum TextureManagerError {
 /// This manager allows for:
 /// - Loading textures from different threads while keeping usage safe in the rendering thread.
 /// - Allocating textures and subsequently loading image data into them.
-/// - Updating the data in an existing texture.
+/// - Updating the data in an existing texture, in whole or in sub-rectangles with
+///   [`TextureManager::update_texture_region`].
+/// - Packing many small images into shared atlas pages with
+///   [`TextureManager::insert_into_atlas`], so shapes using them batch into one draw call.
+/// - Choosing filtering and address modes per texture with [`TextureManager::set_texture_sampler`].
+/// - Building mip chains for textures drawn smaller than their native size with
+///   [`TextureManager::allocate_texture_with_mipmaps`].
+/// - Allocating R8 masks, HDR, and BGRA8 textures with
+///   [`TextureManager::allocate_texture_with_options`].
+/// - Drawing NV12 and I420 video frames with [`TextureManager::allocate_yuv_texture`].
+/// - Capping GPU memory with [`TextureManager::set_memory_budget`], which evicts the least
+///   recently drawn textures.
+/// - Decoding PNG, JPEG, and WebP files with `TextureManager::load_image_bytes` and
+///   `TextureManager::load_image_path` (requires the `image` feature).
 ///
 /// # Examples
 ///
@@ -65,30 +144,79 @@ pub enum TextureManagerError {
 pub struct TextureManager {
     device: Arc<wgpu::Device>,
     queue: Arc<wgpu::Queue>,
-    sampler: Arc<wgpu::Sampler>,
+    /// One sampler per distinct [`SamplerOptions`] in use.
+    samplers: Arc<RwLock<HashMap<SamplerOptions, Arc<wgpu::Sampler>>>>,
+    /// Per-texture sampler defaults set by [`TextureManager::set_texture_sampler`].
+    texture_samplers: Arc<RwLock<HashMap<u64, SamplerOptions>>>,
     /// Textures is raw image data, without any screen position information
     texture_storage: Arc<RwLock<HashMap<u64, wgpu::Texture>>>,
-    /// Cache for shape texture bind groups keyed by (texture_id, layout_epoch)
+    /// Cache for shape texture bind groups keyed by (texture_id, layout_epoch, sampler)
     shape_bind_group_cache: Arc<RwLock<BindGroupCache>>,
+    /// Small textures packed into shared pages by [`TextureManager::insert_into_atlas`].
+    atlas: Arc<RwLock<TextureAtlas>>,
+    /// Plane layouts of textures allocated by [`TextureManager::allocate_yuv_texture`].
+    yuv_layouts: Arc<RwLock<HashMap<u64, YuvLayout>>>,
+    /// Downsample pipelines per format, created the first time a mipmapped texture of that
+    /// format is loaded.
+    mipmap_generators: Arc<RwLock<HashMap<wgpu::TextureFormat, Arc<MipmapGenerator>>>>,
+    /// Frame of last use per texture, and the memory budget enforced at the end of each frame.
+    usage: Arc<RwLock<TextureUsage>>,
+    /// Bumped whenever a texture's or atlas page's contents change, so cached renders that
+    /// sampled it can tell they are stale.
+    revisions: Arc<RwLock<ContentRevisions>>,
+}
+
+type BindGroupCache = HashMap<(u64, u64, SamplerOptions), Arc<wgpu::BindGroup>>;
+
+#[derive(Default)]
+struct ContentRevisions {
+    latest: u64,
+    textures: HashMap<u64, u64>,
+    atlas_pages: HashMap<u64, u64>,
 }
 
-type BindGroupCache = HashMap<(u64, u64), Arc<wgpu::BindGroup>>;
+impl ContentRevisions {
+    fn bump_texture(&mut self, texture_id: u64) {
+        self.latest += 1;
+        self.textures.insert(texture_id, self.latest);
+    }
+
+    fn bump_atlas_page(&mut self, page_id: u64) {
+        self.latest += 1;
+        self.atlas_pages.insert(page_id, self.latest);
+    }
+}
 
 impl TextureManager {
     pub(crate) fn new(device: Arc<wgpu::Device>, queue: Arc<wgpu::Queue>) -> Self {
-        let sampler = Self::create_sampler(&device);
         Self {
             device,
             queue,
-            sampler: Arc::new(sampler),
+            samplers: Arc::new(RwLock::new(HashMap::new())),
+            texture_samplers: Arc::new(RwLock::new(HashMap::new())),
             texture_storage: Arc::new(RwLock::new(HashMap::new())),
             shape_bind_group_cache: Arc::new(RwLock::new(HashMap::new())),
+            atlas: Arc::new(RwLock::new(TextureAtlas::default())),
+            yuv_layouts: Arc::new(RwLock::new(HashMap::new())),
+            mipmap_generators: Arc::new(RwLock::new(HashMap::new())),
+            usage: Arc::new(RwLock::new(TextureUsage::default())),
+            revisions: Arc::new(RwLock::new(ContentRevisions::default())),
         }
     }
 
     pub fn clear(&self) {
         self.texture_storage.write().unwrap().clear();
         self.shape_bind_group_cache.write().unwrap().clear();
+        self.texture_samplers.write().unwrap().clear();
+        self.yuv_layouts.write().unwrap().clear();
+        let mut usage = self.usage.write().unwrap();
+        usage.last_used.clear();
+        usage.external.clear();
+        drop(usage);
+        *self.atlas.write().unwrap() = TextureAtlas::default();
+        let mut revisions = self.revisions.write().unwrap();
+        revisions.textures.clear();
+        revisions.atlas_pages.clear();
     }
 
     pub fn size(&self) -> (usize, usize) {
@@ -98,16 +226,145 @@ impl TextureManager {
         )
     }
 
-    fn create_sampler(device: &wgpu::Device) -> wgpu::Sampler {
-        device.create_sampler(&wgpu::SamplerDescriptor {
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Linear,
-            min_filter: wgpu::FilterMode::Linear,
-            mipmap_filter: wgpu::FilterMode::Linear,
-            ..Default::default()
-        })
+    /// Caps the GPU memory held by textures at `budget_bytes`, or lifts the cap with `None`.
+    ///
+    /// The budget is enforced at the end of each rendered frame: while over budget, standalone
+    /// textures not drawn in that frame are removed, least recently drawn first, and reported to
+    /// the callback set with [`TextureManager::set_eviction_callback`]. Atlas pages count
+    /// towards the budget but are never evicted, and neither are textures registered with
+    /// [`TextureManager::insert_wgpu_texture`] or rendered by
+    /// [`Renderer::render_subtree_to_texture`](crate::Renderer::render_subtree_to_texture),
+    /// which the manager cannot recreate. Textures drawn in the frame are kept even when that
+    /// leaves the manager over budget.
+    pub fn set_memory_budget(&self, budget_bytes: Option<u64>) {
+        self.usage.write().unwrap().budget_bytes = budget_bytes;
+    }
+
+    pub fn memory_budget(&self) -> Option<u64> {
+        self.usage.read().unwrap().budget_bytes
+    }
+
+    /// Sets a callback invoked with the id of each texture evicted by the memory budget, so the
+    /// app can reload it before it is drawn again. It runs on the rendering thread after the
+    /// texture is removed; shapes referencing an evicted texture draw without it.
+    pub fn set_eviction_callback(&self, callback: impl Fn(u64) + Send + Sync + 'static) {
+        self.usage.write().unwrap().on_evict = Some(Arc::new(callback));
+    }
+
+    /// Returns the bytes held by every texture and atlas page, including mip chains.
+    pub fn memory_report(&self) -> TextureMemoryReport {
+        let usage = self.usage.read().unwrap();
+        let mut textures: Vec<TextureMemoryUsage> = self
+            .texture_storage
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(&texture_id, texture)| TextureMemoryUsage {
+                texture_id,
+                bytes: budget::texture_bytes(texture),
+                frames_since_use: usage.frames_since_use(texture_id),
+            })
+            .collect();
+        textures
+            .sort_unstable_by(|a, b| b.bytes.cmp(&a.bytes).then(a.texture_id.cmp(&b.texture_id)));
+        let atlas_bytes = self.atlas_bytes();
+        TextureMemoryReport {
+            total_bytes: atlas_bytes + textures.iter().map(|texture| texture.bytes).sum::<u64>(),
+            atlas_bytes,
+            budget_bytes: usage.budget_bytes,
+            textures,
+        }
+    }
+
+    fn atlas_bytes(&self) -> u64 {
+        self.atlas
+            .read()
+            .unwrap()
+            .pages
+            .values()
+            .map(|page| budget::texture_bytes(&page.texture))
+            .sum()
+    }
+
+    /// Records that `texture_id` is drawn in the current frame.
+    pub(crate) fn mark_texture_used(&self, texture_id: u64) {
+        let mut usage = self.usage.write().unwrap();
+        let frame = usage.frame;
+        if let Some(last_used) = usage.last_used.get_mut(&texture_id) {
+            *last_used = frame;
+        }
+    }
+
+    /// Finishes the current frame, evicting textures if the memory budget is exceeded.
+    pub(crate) fn end_frame(&self) {
+        let (evicted, on_evict) = {
+            let mut usage = self.usage.write().unwrap();
+            let frame = usage.frame;
+            usage.frame += 1;
+            let Some(budget_bytes) = usage.budget_bytes else {
+                return;
+            };
+            let storage = self.texture_storage.read().unwrap();
+            let total_bytes =
+                self.atlas_bytes() + storage.values().map(budget::texture_bytes).sum::<u64>();
+            if total_bytes <= budget_bytes {
+                return;
+            }
+            let mut candidates: Vec<(u64, u64, u64)> = storage
+                .iter()
+                .filter(|(&texture_id, _)| usage.is_evictable(texture_id))
+                .map(|(&texture_id, texture)| {
+                    let last_used = usage.last_used.get(&texture_id).copied().unwrap_or(0);
+                    (texture_id, budget::texture_bytes(texture), last_used)
+                })
+                .collect();
+            (
+                budget::plan_evictions(&mut candidates, total_bytes, budget_bytes, frame),
+                usage.on_evict.clone(),
+            )
+        };
+        for &texture_id in &evicted {
+            self.remove_texture(texture_id);
+        }
+        if let Some(on_evict) = on_evict {
+            for texture_id in evicted {
+                on_evict(texture_id);
+            }
+        }
+    }
+
+    /// Sets the sampler used whenever `texture_id` is drawn without a per-draw
+    /// [`ShapeTextureOptions::sampler`](crate::ShapeTextureOptions::sampler) override.
+    ///
+    /// Like atlas placement, the sampler is resolved when a shape is added to the draw queue.
+    /// The setting persists across reallocations of the texture until it is removed.
+    pub fn set_texture_sampler(&self, texture_id: u64, sampler: SamplerOptions) {
+        self.texture_samplers
+            .write()
+            .unwrap()
+            .insert(texture_id, sampler);
+    }
+
+    /// Returns the sampler `texture_id` uses by default.
+    pub fn texture_sampler(&self, texture_id: u64) -> SamplerOptions {
+        self.texture_samplers
+            .read()
+            .unwrap()
+            .get(&texture_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn get_or_create_sampler(&self, options: SamplerOptions) -> Arc<wgpu::Sampler> {
+        if let Some(sampler) = self.samplers.read().unwrap().get(&options) {
+            return sampler.clone();
+        }
+        self.samplers
+            .write()
+            .unwrap()
+            .entry(options)
+            .or_insert_with(|| Arc::new(options.create_sampler(&self.device)))
+            .clone()
     }
 
     /// Allocates a new RGBA8 texture with the given dimensions without providing any data.
@@ -120,11 +377,32 @@ impl TextureManager {
     /// - `texture_id`: Unique identifier for the texture.
     /// - `texture_dimensions`: A tuple `(width, height)` representing the dimensions of the texture.
     pub fn allocate_texture(&self, texture_id: u64, texture_dimensions: (u32, u32)) {
-        let mut bind_group_cache = self.shape_bind_group_cache.write().unwrap();
-        // If the binding cache contains entries for this texture_id, remove them
-        // as the texture is being re-allocated, and the old bind groups are no longer valid.
-        bind_group_cache
-            .retain(|(cached_texture_id, _shape_id), _bind_group| *cached_texture_id != texture_id);
+        self.allocate_texture_with_options(texture_id, texture_dimensions, TextureOptions::new());
+    }
+
+    /// Allocates a new texture in `options.format`, optionally with a mip chain, without
+    /// providing any data.
+    ///
+    /// [`TextureManager::load_data_into_texture`] then expects bytes in that format, e.g. one
+    /// coverage byte per texel for [`TextureFormat::R8Mask`] or BGRA8 straight from a capture
+    /// API for [`TextureFormat::Bgra8Srgb`].
+    ///
+    /// # Parameters
+    /// - `texture_id`: Unique identifier for the texture.
+    /// - `texture_dimensions`: A tuple `(width, height)` representing the dimensions of the texture.
+    /// - `options`: The texel format and whether to build mipmaps.
+    pub fn allocate_texture_with_options(
+        &self,
+        texture_id: u64,
+        texture_dimensions: (u32, u32),
+        options: TextureOptions,
+    ) {
+        let mip_level_count = if options.mipmaps {
+            mipmap::full_mip_level_count(texture_dimensions)
+        } else {
+            1
+        };
+        self.release_texture_contents(texture_id);
 
         let texture_extent = wgpu::Extent3d {
             width: texture_dimensions.0,
@@ -135,13 +413,19 @@ impl TextureManager {
         let texture = self.device.create_texture(&wgpu::TextureDescriptor {
             label: None,
             size: texture_extent,
-            mip_level_count: 1,
+            mip_level_count,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
-            // sRGBA, as we're going to work with RGBA images
-            format: wgpu::TextureFormat::Rgba8UnormSrgb,
-            // TEXTURE_BINDING to use texture in the shader, COPY_DST to copy data to the texture
-            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            format: options.format.to_wgpu(),
+            // TEXTURE_BINDING to use texture in the shader, COPY_DST to copy data to the texture,
+            // RENDER_ATTACHMENT to render the mip chain
+            usage: if mip_level_count > 1 {
+                wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::COPY_DST
+                    | wgpu::TextureUsages::RENDER_ATTACHMENT
+            } else {
+                wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST
+            },
             view_formats: &[],
         });
 
@@ -149,6 +433,93 @@ impl TextureManager {
             .write()
             .unwrap()
             .insert(texture_id, texture);
+        self.usage.write().unwrap().track(texture_id, false);
+    }
+
+    /// Registers a texture created on the renderer's device, e.g. by an engine sharing it
+    /// through [`RendererContext::from_wgpu`](crate::RendererContext::from_wgpu), so shapes can
+    /// draw it by `texture_id` without a CPU copy. Replaces any texture with that id.
+    ///
+    /// The texture must be a single-sampled 2D texture in one of the [`TextureFormat`]s, with
+    /// `TEXTURE_BINDING` usage. Its contents are expected to use premultiplied alpha. Loading
+    /// data into it later additionally needs `COPY_DST`, and regenerating its mips
+    /// `RENDER_ATTACHMENT`.
+    pub fn insert_wgpu_texture(
+        &self,
+        texture_id: u64,
+        texture: wgpu::Texture,
+    ) -> Result<(), TextureManagerError> {
+        if TextureFormat::from_wgpu(texture.format()).is_none() {
+            return Err(TextureManagerError::UnsupportedTextureFormat {
+                texture_id,
+                format: texture.format(),
+            });
+        }
+        if !texture
+            .usage()
+            .contains(wgpu::TextureUsages::TEXTURE_BINDING)
+        {
+            return Err(TextureManagerError::MissingTextureUsage {
+                texture_id,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            });
+        }
+        if texture.dimension() != wgpu::TextureDimension::D2
+            || texture.depth_or_array_layers() != 1
+            || texture.sample_count() != 1
+        {
+            return Err(TextureManagerError::UnsupportedTextureLayout(texture_id));
+        }
+
+        self.release_texture_contents(texture_id);
+        self.texture_storage
+            .write()
+            .unwrap()
+            .insert(texture_id, texture);
+        self.usage.write().unwrap().track(texture_id, true);
+        Ok(())
+    }
+
+    /// Drops what `texture_id` currently holds ahead of a new texture taking the id. The
+    /// sampler setting is kept.
+    fn release_texture_contents(&self, texture_id: u64) {
+        self.revisions.write().unwrap().bump_texture(texture_id);
+        self.atlas.write().unwrap().remove_entry(texture_id);
+        self.yuv_layouts.write().unwrap().remove(&texture_id);
+        let mut bind_group_cache = self.shape_bind_group_cache.write().unwrap();
+        // If the binding cache contains entries for this texture_id, remove them
+        // as the texture is being re-allocated, and the old bind groups are no longer valid.
+        bind_group_cache
+            .retain(|(cached_texture_id, _, _), _bind_group| *cached_texture_id != texture_id);
+    }
+
+    /// Returns the GPU texture behind a standalone texture, e.g. to render into it or sample it
+    /// outside grafo. Atlas entries share a page and return `None`.
+    pub fn wgpu_texture(&self, texture_id: u64) -> Option<wgpu::Texture> {
+        self.texture_storage
+            .read()
+            .unwrap()
+            .get(&texture_id)
+            .cloned()
+    }
+
+    /// Tells grafo that a texture's contents changed outside the manager, e.g. after an engine
+    /// rendered into a texture registered with [`TextureManager::insert_wgpu_texture`], so
+    /// cached layers that sample it are redrawn.
+    pub fn mark_texture_modified(&self, texture_id: u64) {
+        self.revisions.write().unwrap().bump_texture(texture_id);
+    }
+
+    /// Content revision of a standalone texture or atlas entry; changes whenever it is written.
+    pub(crate) fn texture_revision(&self, texture_id: u64) -> u64 {
+        let revisions = self.revisions.read().unwrap();
+        revisions.textures.get(&texture_id).copied().unwrap_or(0)
+    }
+
+    /// Content revision of an atlas page; changes whenever any of its entries is written.
+    pub(crate) fn atlas_page_revision(&self, page_id: u64) -> u64 {
+        let revisions = self.revisions.read().unwrap();
+        revisions.atlas_pages.get(&page_id).copied().unwrap_or(0)
     }
 
     /// Allocates a texture and immediately loads image data into it.
@@ -160,20 +531,61 @@ impl TextureManager {
     /// `premultiply_rgba8_srgb_inplace` helper function provided in this crate to convert your
     /// RGBA8 sRGB data to premultiplied alpha.
     ///
+    /// Textures drawn well below their native size should use
+    /// [`TextureManager::allocate_texture_with_mipmaps`] instead.
+    ///
     /// # Parameters
     /// - `texture_id`: Unique identifier for the texture.
     /// - `texture_dimensions`: A tuple `(width, height)` representing the dimensions of the texture.
     /// - `texture_data`: A byte slice containing the image data. The data length is expected to
     ///   match the texture dimensions and pixel format (RGBA8 with premultiplied alpha).
+    ///
+    /// # Panics
+    /// Panics before allocating if `texture_data` is not `width * height * 4` bytes long. Use
+    /// [`TextureManager::allocate_texture`] and [`TextureManager::load_data_into_texture`] to
+    /// handle the mismatch as an error instead.
     pub fn allocate_texture_with_data(
         &self,
         texture_id: u64,
         texture_dimensions: (u32, u32),
         texture_data: &[u8],
     ) {
+        assert_rgba8_data_size(texture_id, texture_dimensions, texture_data);
         self.allocate_texture(texture_id, texture_dimensions);
         self.load_data_into_texture(texture_id, texture_dimensions, texture_data)
-            .unwrap();
+            .expect("freshly allocated texture accepts data of validated size");
+    }
+
+    /// Allocates a texture with a full mip chain and loads image data into it.
+    ///
+    /// The smaller levels are generated on the GPU from `texture_data`, and regenerated
+    /// whenever [`TextureManager::load_data_into_texture`] replaces it. Sampling then blends
+    /// the levels matching the texture's on-screen size, which avoids shimmering and aliasing
+    /// for thumbnails and photos drawn well below their native size.
+    ///
+    /// # Parameters
+    /// - `texture_id`: Unique identifier for the texture.
+    /// - `texture_dimensions`: A tuple `(width, height)` representing the dimensions of the texture.
+    /// - `texture_data`: RGBA8 image data with premultiplied alpha, as for
+    ///   [`TextureManager::allocate_texture_with_data`].
+    ///
+    /// # Panics
+    /// Panics before allocating if `texture_data` is not `width * height * 4` bytes long, like
+    /// [`TextureManager::allocate_texture_with_data`].
+    pub fn allocate_texture_with_mipmaps(
+        &self,
+        texture_id: u64,
+        texture_dimensions: (u32, u32),
+        texture_data: &[u8],
+    ) {
+        assert_rgba8_data_size(texture_id, texture_dimensions, texture_data);
+        self.allocate_texture_with_options(
+            texture_id,
+            texture_dimensions,
+            TextureOptions::new().mipmaps(true),
+        );
+        self.load_data_into_texture(texture_id, texture_dimensions, texture_data)
+            .expect("freshly allocated texture accepts data of validated size");
     }
 
     /// Loads image data into an already allocated texture. If you are seeing fringes when
@@ -184,13 +596,21 @@ impl TextureManager {
     /// # Parameters
     /// - `texture_id`: Unique identifier for the texture.
     /// - `texture_dimensions`: A tuple `(width, height)` representing the dimensions of the texture.
-    /// - `texture_data`: A byte slice containing the image data in an RGBA8 format with premultiplied alpha.
+    /// - `texture_data`: A byte slice containing the image data in the texture's
+    ///   [`TextureFormat`], RGBA8 with premultiplied alpha unless allocated otherwise.
     ///   If your texture isn't premultiplied, consider using a `premultiply_rgba8_srgb_inplace` helper
     ///   function provided in this crate. This is needed to avoid fringes when sampling/minifying near transparent edges.
     ///
+    /// Atlas entries are rewritten in place within their page, like a full-size
+    /// [`TextureManager::update_texture_region`].
+    ///
     /// # Returns
     /// - `Ok(())` if the operation succeeds.
     /// - `Err(TextureManagerError::TextureNotFound(texture_id))` if the texture does not exist.
+    /// - `Err(TextureManagerError::DataSizeMismatch { .. })` if `texture_data` does not match
+    ///   the dimensions in the texture's format.
+    /// - `Err(TextureManagerError::RegionOutOfBounds { .. })` if `texture_dimensions` exceed
+    ///   an atlas entry's size.
     pub fn load_data_into_texture(
         &self,
         texture_id: u64,
@@ -198,9 +618,20 @@ impl TextureManager {
         texture_data: &[u8],
     ) -> Result<(), TextureManagerError> {
         let texture_storage = self.texture_storage.read().unwrap();
-        let texture = texture_storage
-            .get(&texture_id)
-            .ok_or(TextureManagerError::TextureNotFound(texture_id))?;
+        let Some(texture) = texture_storage.get(&texture_id) else {
+            drop(texture_storage);
+            return self.load_data_into_atlas_entry(texture_id, texture_dimensions, texture_data);
+        };
+        let expected = texture_dimensions.0 as usize
+            * texture_dimensions.1 as usize
+            * bytes_per_texel(texture) as usize;
+        if texture_data.len() != expected {
+            return Err(TextureManagerError::DataSizeMismatch {
+                texture_id,
+                expected,
+                actual: texture_data.len(),
+            });
+        }
 
         let texture_extent = wgpu::Extent3d {
             width: texture_dimensions.0,
@@ -214,19 +645,486 @@ impl TextureManager {
             texture_extent,
             texture_data,
         );
+        self.generate_mipmaps(texture);
+        self.revisions.write().unwrap().bump_texture(texture_id);
+
+        Ok(())
+    }
+
+    fn load_data_into_atlas_entry(
+        &self,
+        texture_id: u64,
+        texture_dimensions: (u32, u32),
+        texture_data: &[u8],
+    ) -> Result<(), TextureManagerError> {
+        if !self.atlas.read().unwrap().entries.contains_key(&texture_id) {
+            return Err(TextureManagerError::TextureNotFound(texture_id));
+        }
+        // Atlas pages are RGBA8.
+        let expected = texture_dimensions.0 as usize * texture_dimensions.1 as usize * 4;
+        if texture_data.len() != expected {
+            return Err(TextureManagerError::DataSizeMismatch {
+                texture_id,
+                expected,
+                actual: texture_data.len(),
+            });
+        }
+        self.update_texture_region(
+            texture_id,
+            (0, 0),
+            texture_dimensions,
+            texture_data,
+            4 * texture_dimensions.0,
+        )
+    }
 
+    /// Uploads `size` texels of `texture_data` into the texture at `origin`, leaving the rest
+    /// of the texture untouched.
+    ///
+    /// Use this for paint canvases, terminal cells, and other incremental changes instead of
+    /// re-uploading the whole texture. Atlas entries are updated in place within their page.
+    /// Mipmapped textures regenerate their mip chain afterwards.
+    ///
+    /// # Parameters
+    /// - `texture_id`: Identifier of an allocated texture or atlas entry.
+    /// - `origin`: Top-left texel of the region to update.
+    /// - `size`: A tuple `(width, height)` of the region in texels.
+    /// - `texture_data`: The region's texels in the texture's [`TextureFormat`], starting at
+    ///   its top-left texel.
+    /// - `bytes_per_row`: Stride between rows of `texture_data`, so a region can be read
+    ///   straight out of a larger image. At least `width` times the format's texel size.
+    ///
+    /// # Returns
+    /// - `Err(TextureManagerError::TextureNotFound(texture_id))` if the texture does not exist.
+    /// - `Err(TextureManagerError::RegionOutOfBounds { .. })` if the region exceeds the texture.
+    /// - `Err(TextureManagerError::BytesPerRowTooSmall { .. })` if rows would overlap.
+    /// - `Err(TextureManagerError::DataSizeMismatch { .. })` if `texture_data` ends before the
+    ///   region's last texel.
+    pub fn update_texture_region(
+        &self,
+        texture_id: u64,
+        origin: (u32, u32),
+        size: (u32, u32),
+        texture_data: &[u8],
+        bytes_per_row: u32,
+    ) -> Result<(), TextureManagerError> {
+        let texture_storage = self.texture_storage.read().unwrap();
+        let atlas = self.atlas.read().unwrap();
+        let (texture, texture_origin, texture_size) = match texture_storage.get(&texture_id) {
+            Some(texture) => (texture, (0, 0), (texture.width(), texture.height())),
+            None => {
+                let entry = atlas
+                    .entries
+                    .get(&texture_id)
+                    .ok_or(TextureManagerError::TextureNotFound(texture_id))?;
+                let page = atlas
+                    .pages
+                    .get(&entry.page_id)
+                    .ok_or(TextureManagerError::TextureNotFound(texture_id))?;
+                (&page.texture, entry.origin, entry.size)
+            }
+        };
+
+        let fits = |start: u32, length: u32, limit: u32| {
+            start.checked_add(length).is_some_and(|end| end <= limit)
+        };
+        if !fits(origin.0, size.0, texture_size.0) || !fits(origin.1, size.1, texture_size.1) {
+            return Err(TextureManagerError::RegionOutOfBounds {
+                texture_id,
+                origin,
+                size,
+            });
+        }
+        if size.0 == 0 || size.1 == 0 {
+            return Ok(());
+        }
+        let data_length = strided_data_length(
+            texture_id,
+            size,
+            bytes_per_texel(texture),
+            texture_data.len(),
+            bytes_per_row,
+        )?;
+
+        self.write_image_bytes_to_texture_region(
+            texture,
+            (texture_origin.0 + origin.0, texture_origin.1 + origin.1),
+            size,
+            bytes_per_row,
+            &texture_data[..data_length],
+        );
+        self.generate_mipmaps(texture);
+        let mut revisions = self.revisions.write().unwrap();
+        revisions.bump_texture(texture_id);
+        if let Some(entry) = atlas.entries.get(&texture_id) {
+            revisions.bump_atlas_page(entry.page_id);
+        }
         Ok(())
     }
 
+    /// Allocates a texture for planar YUV video frames of `texture_dimensions`.
+    ///
+    /// Shapes reference the video by `texture_id` like any other texture, and the shape
+    /// fragment shader converts to RGB with `options`' matrix and range, so decoded frames need
+    /// no CPU conversion. Upload frames with [`TextureManager::load_yuv_frame`].
+    ///
+    /// # Parameters
+    /// - `texture_id`: Unique identifier for the texture.
+    /// - `texture_dimensions`: A tuple `(width, height)` of the frame, i.e. of its luma plane.
+    /// - `options`: The plane layout and color conversion of the frames.
+    pub fn allocate_yuv_texture(
+        &self,
+        texture_id: u64,
+        texture_dimensions: (u32, u32),
+        options: YuvOptions,
+    ) {
+        let layout = YuvLayout {
+            options,
+            luma_size: texture_dimensions,
+        };
+        self.allocate_texture_with_options(
+            texture_id,
+            layout.packed_size(),
+            TextureOptions::new().format(TextureFormat::R8Mask),
+        );
+        self.yuv_layouts.write().unwrap().insert(texture_id, layout);
+    }
+
+    /// Uploads one frame into a texture allocated with [`TextureManager::allocate_yuv_texture`].
+    ///
+    /// `planes` are luma then interleaved UV for NV12, or luma, U, then V for I420. Chroma
+    /// planes are half the frame's size, rounded up, and every plane may have its own stride.
+    ///
+    /// # Returns
+    /// - `Err(TextureManagerError::NotYuvTexture(texture_id))` if the texture is not a YUV texture.
+    /// - `Err(TextureManagerError::YuvPlaneCountMismatch { .. })` if the plane count does not
+    ///   match the format.
+    /// - `Err(TextureManagerError::BytesPerRowTooSmall { .. })` or
+    ///   `Err(TextureManagerError::DataSizeMismatch { .. })` if a plane is too small.
+    pub fn load_yuv_frame(
+        &self,
+        texture_id: u64,
+        planes: &[YuvPlane<'_>],
+    ) -> Result<(), TextureManagerError> {
+        let layout = self
+            .yuv_layouts
+            .read()
+            .unwrap()
+            .get(&texture_id)
+            .copied()
+            .ok_or(TextureManagerError::NotYuvTexture(texture_id))?;
+        let expected = layout.options.format.plane_count();
+        if planes.len() != expected {
+            return Err(TextureManagerError::YuvPlaneCountMismatch {
+                texture_id,
+                expected,
+                actual: planes.len(),
+            });
+        }
+
+        let texture_storage = self.texture_storage.read().unwrap();
+        let texture = texture_storage
+            .get(&texture_id)
+            .ok_or(TextureManagerError::TextureNotFound(texture_id))?;
+        let plane_regions = layout.plane_regions();
+        // Validate every plane before writing any, so a bad frame leaves the last one intact.
+        let data_lengths = planes
+            .iter()
+            .zip(&plane_regions)
+            .map(|(plane, (_, size))| {
+                strided_data_length(texture_id, *size, 1, plane.data.len(), plane.bytes_per_row)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        for ((plane, (origin, size)), data_length) in
+            planes.iter().zip(plane_regions).zip(data_lengths)
+        {
+            if size.0 == 0 || size.1 == 0 {
+                continue;
+            }
+            self.write_image_bytes_to_texture_region(
+                texture,
+                origin,
+                size,
+                plane.bytes_per_row,
+                &plane.data[..data_length],
+            );
+        }
+        self.revisions.write().unwrap().bump_texture(texture_id);
+        Ok(())
+    }
+
+    /// Rebuilds the mip chain of `texture` from its base level, if it has one.
+    fn generate_mipmaps(&self, texture: &wgpu::Texture) {
+        if texture.mip_level_count() < 2 {
+            return;
+        }
+        let cached_generator = self
+            .mipmap_generators
+            .read()
+            .unwrap()
+            .get(&texture.format())
+            .cloned();
+        let generator = cached_generator.unwrap_or_else(|| {
+            self.mipmap_generators
+                .write()
+                .unwrap()
+                .entry(texture.format())
+                .or_insert_with(|| Arc::new(MipmapGenerator::new(&self.device, texture.format())))
+                .clone()
+        });
+        generator.generate(&self.device, &self.queue, texture);
+    }
+
     /// Removes the texture identified by `texture_id` from the manager.
+    ///
+    /// For atlas entries this frees the entry's space for later insertions; a page is
+    /// released once its last entry is removed.
     pub fn remove_texture(&self, texture_id: u64) {
+        self.revisions.write().unwrap().bump_texture(texture_id);
+        self.atlas.write().unwrap().remove_entry(texture_id);
         let mut bind_group_cache = self.shape_bind_group_cache.write().unwrap();
         // If the binding cache contains entries for this texture_id, remove them
         // as the texture is being removed, and the old bind groups are no longer valid.
         bind_group_cache
-            .retain(|(cached_texture_id, _shape_id), _bind_group| *cached_texture_id != texture_id);
+            .retain(|(cached_texture_id, _, _), _bind_group| *cached_texture_id != texture_id);
+
+        self.texture_samplers.write().unwrap().remove(&texture_id);
+        self.yuv_layouts.write().unwrap().remove(&texture_id);
+        self.usage.write().unwrap().forget(texture_id);
+        self.texture_storage.write().unwrap().remove(&texture_id);
+    }
 
+    /// Packs a small RGBA8 image into a shared atlas page under `texture_id`.
+    ///
+    /// Shapes reference the entry by `texture_id` exactly like a standalone texture, including
+    /// fit modes and source rects, which resolve against the entry's own dimensions. Shapes
+    /// whose textures share a page batch into one draw call instead of switching bind groups.
+    /// Sampling is clamped to the entry, so neighboring entries never bleed in.
+    ///
+    /// Inserting an existing id replaces it. When no page has room, the page with the most free
+    /// area that fits the image after repacking is defragmented, and a new page is created if
+    /// none does. Images too large to share a page get a standalone texture instead.
+    ///
+    /// Atlas lookups happen when a shape is added to the draw queue, so insert textures before
+    /// adding the shapes that use them in a frame.
+    ///
+    /// # Parameters
+    /// - `texture_id`: Unique identifier for the texture.
+    /// - `texture_dimensions`: A tuple `(width, height)` representing the dimensions of the image.
+    /// - `texture_data`: RGBA8 image data with premultiplied alpha.
+    pub fn insert_into_atlas(
+        &self,
+        texture_id: u64,
+        texture_dimensions: (u32, u32),
+        texture_data: &[u8],
+    ) -> Result<(), TextureManagerError> {
+        let (width, height) = texture_dimensions;
+        if width == 0 || height == 0 {
+            return Err(TextureManagerError::EmptyTexture(texture_id));
+        }
+        let expected = width as usize * height as usize * 4;
+        if texture_data.len() != expected {
+            return Err(TextureManagerError::DataSizeMismatch {
+                texture_id,
+                expected,
+                actual: texture_data.len(),
+            });
+        }
+
+        let page_size = self.atlas_page_size();
+        if width > page_size / 2 || height > page_size / 2 {
+            self.allocate_texture(texture_id, texture_dimensions);
+            return self.load_data_into_texture(texture_id, texture_dimensions, texture_data);
+        }
+
+        self.release_texture_contents(texture_id);
         self.texture_storage.write().unwrap().remove(&texture_id);
+        self.usage.write().unwrap().forget(texture_id);
+        let mut atlas = self.atlas.write().unwrap();
+        let (page_id, origin) = match atlas.allocate(texture_dimensions) {
+            Some(allocation) => allocation,
+            None => match self.repack_page_for(&mut atlas, texture_dimensions) {
+                Some(allocation) => allocation,
+                None => {
+                    let page_id = atlas.next_page_id();
+                    let mut packer = ShelfPacker::new((page_size, page_size));
+                    let origin = packer
+                        .allocate(texture_dimensions)
+                        .ok_or(TextureManagerError::AtlasEntryTooLarge(texture_id))?;
+                    atlas.pages.insert(
+                        page_id,
+                        AtlasPage {
+                            texture: self.create_atlas_page_texture((page_size, page_size)),
+                            packer,
+                            bind_groups: HashMap::new(),
+                        },
+                    );
+                    (page_id, origin)
+                }
+            },
+        };
+        atlas.entries.insert(
+            texture_id,
+            AtlasEntry {
+                page_id,
+                origin,
+                size: texture_dimensions,
+            },
+        );
+        if let Some(page) = atlas.pages.get(&page_id) {
+            self.write_image_bytes_to_texture_region(
+                &page.texture,
+                origin,
+                texture_dimensions,
+                4 * width,
+                texture_data,
+            );
+            self.revisions.write().unwrap().bump_atlas_page(page_id);
+        }
+        Ok(())
+    }
+
+    /// Number of atlas pages currently holding at least one entry.
+    pub fn atlas_page_count(&self) -> usize {
+        self.atlas.read().unwrap().pages.len()
+    }
+
+    fn atlas_page_size(&self) -> u32 {
+        ATLAS_PAGE_SIZE.min(self.device.limits().max_texture_dimension_2d)
+    }
+
+    fn create_atlas_page_texture(&self, page_size: (u32, u32)) -> wgpu::Texture {
+        self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("texture_atlas_page"),
+            size: wgpu::Extent3d {
+                width: page_size.0,
+                height: page_size.1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            // COPY_SRC lets repacking move entries into a fresh page on the GPU.
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        })
+    }
+
+    /// Repacks the page with the most free area that can fit `new_entry_size` once repacked,
+    /// copying its live entries into a fresh page texture. Returns the slot reserved for the
+    /// new entry.
+    fn repack_page_for(
+        &self,
+        atlas: &mut TextureAtlas,
+        new_entry_size: (u32, u32),
+    ) -> Option<(u64, (u32, u32))> {
+        let mut candidate_page_ids = atlas.pages.keys().copied().collect::<Vec<_>>();
+        // Roomiest first, so overflows do not keep repacking the lowest page id.
+        candidate_page_ids
+            .sort_by_key(|page_id| std::cmp::Reverse(atlas.pages[page_id].packer.free_area()));
+        let (old_page_id, RepackPlan { packer, origins }) =
+            candidate_page_ids.into_iter().find_map(|page_id| {
+                atlas
+                    .plan_repack(page_id, new_entry_size)
+                    .map(|plan| (page_id, plan))
+            })?;
+        let old_page = atlas.pages.remove(&old_page_id)?;
+        let old_page_size = old_page.texture.size();
+        let new_texture =
+            self.create_atlas_page_texture((old_page_size.width, old_page_size.height));
+        let new_page_id = atlas.next_page_id();
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("texture_atlas_repack"),
+            });
+        let mut new_entry_origin = None;
+        for (texture_id, new_origin) in origins {
+            let Some(texture_id) = texture_id else {
+                new_entry_origin = Some(new_origin);
+                continue;
+            };
+            let Some(entry) = atlas.entries.get_mut(&texture_id) else {
+                continue;
+            };
+            encoder.copy_texture_to_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture: &old_page.texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: entry.origin.0,
+                        y: entry.origin.1,
+                        z: 0,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::TexelCopyTextureInfo {
+                    texture: &new_texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: new_origin.0,
+                        y: new_origin.1,
+                        z: 0,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::Extent3d {
+                    width: entry.size.0,
+                    height: entry.size.1,
+                    depth_or_array_layers: 1,
+                },
+            );
+            entry.page_id = new_page_id;
+            entry.origin = new_origin;
+        }
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        atlas.pages.insert(
+            new_page_id,
+            AtlasPage {
+                texture: new_texture,
+                packer,
+                bind_groups: HashMap::new(),
+            },
+        );
+        new_entry_origin.map(|origin| (new_page_id, origin))
+    }
+
+    fn write_image_bytes_to_texture_region(
+        &self,
+        texture: &wgpu::Texture,
+        origin: (u32, u32),
+        region_dimensions: (u32, u32),
+        bytes_per_row: u32,
+        texture_data_bytes: &[u8],
+    ) {
+        self.queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: origin.0,
+                    y: origin.1,
+                    z: 0,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            texture_data_bytes,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(bytes_per_row),
+                rows_per_image: Some(region_dimensions.1),
+            },
+            wgpu::Extent3d {
+                width: region_dimensions.0,
+                height: region_dimensions.1,
+                depth_or_array_layers: 1,
+            },
+        );
     }
 
     fn write_image_bytes_to_texture(
@@ -249,17 +1147,17 @@ impl TextureManager {
             // The layout of the texture
             wgpu::TexelCopyBufferLayout {
                 offset: 0,
-                bytes_per_row: Some(4 * texture_dimensions.0),
+                bytes_per_row: Some(bytes_per_texel(texture) * texture_dimensions.0),
                 rows_per_image: Some(texture_dimensions.1),
             },
             texture_extent,
         );
     }
 
-    /// Creates a bind group for the provided `layout` using the stored sampler and
+    /// Creates a bind group for the provided `layout` using the sampler for `sampler` and
     /// the texture identified by `texture_id`.
     ///
-    /// Returns a cached bind group for the given `layout_epoch` and `texture_id`,
+    /// Returns a cached bind group for the given `layout_epoch`, `texture_id` and `sampler`,
     /// creating and caching it if necessary. This avoids per-frame bind group creation
     /// when binding textures for shapes.
     pub(crate) fn get_or_create_shape_bind_group(
@@ -267,13 +1165,15 @@ impl TextureManager {
         layout: &wgpu::BindGroupLayout,
         layout_epoch: u64,
         texture_id: u64,
+        sampler: SamplerOptions,
     ) -> Result<Arc<wgpu::BindGroup>, TextureManagerError> {
+        let cache_key = (texture_id, layout_epoch, sampler);
         // Fast path: check cache
         if let Some(bg) = self
             .shape_bind_group_cache
             .read()
             .unwrap()
-            .get(&(texture_id, layout_epoch))
+            .get(&cache_key)
             .cloned()
         {
             return Ok(bg);
@@ -284,8 +1184,54 @@ impl TextureManager {
         let texture = storage
             .get(&texture_id)
             .ok_or(TextureManagerError::TextureNotFound(texture_id))?;
+        let bind_group = Arc::new(self.create_shape_bind_group(layout, texture, sampler));
+
+        // Insert into cache
+        self.shape_bind_group_cache
+            .write()
+            .unwrap()
+            .insert(cache_key, bind_group.clone());
+
+        Ok(bind_group)
+    }
+
+    /// Returns a cached bind group sampling the atlas page `page_id` with `sampler`.
+    pub(crate) fn get_or_create_atlas_page_bind_group(
+        &self,
+        layout: &wgpu::BindGroupLayout,
+        layout_epoch: u64,
+        page_id: u64,
+        sampler: SamplerOptions,
+    ) -> Option<Arc<wgpu::BindGroup>> {
+        if let Some(bg) = self
+            .atlas
+            .read()
+            .unwrap()
+            .pages
+            .get(&page_id)
+            .and_then(|page| page.bind_groups.get(&(layout_epoch, sampler)))
+            .cloned()
+        {
+            return Some(bg);
+        }
+
+        let mut atlas = self.atlas.write().unwrap();
+        let page = atlas.pages.get_mut(&page_id)?;
+        let bind_group = Arc::new(self.create_shape_bind_group(layout, &page.texture, sampler));
+        page.bind_groups
+            .insert((layout_epoch, sampler), bind_group.clone());
+        Some(bind_group)
+    }
+
+    fn create_shape_bind_group(
+        &self,
+        layout: &wgpu::BindGroupLayout,
+        texture: &wgpu::Texture,
+        sampler: SamplerOptions,
+    ) -> wgpu::BindGroup {
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let bind_group = Arc::new(self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+        let sampler = self.get_or_create_sampler(sampler);
+        self.device.create_bind_group(&wgpu::BindGroupDescriptor {
             layout,
             entries: &[
                 wgpu::BindGroupEntry {
@@ -294,29 +1240,54 @@ impl TextureManager {
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    resource: wgpu::BindingResource::Sampler(&sampler),
                 },
             ],
             label: Some("shape_texture_bind_group_cached"),
-        }));
+        })
+    }
 
-        // Insert into cache
-        self.shape_bind_group_cache
-            .write()
+    pub fn is_texture_loaded(&self, texture_id: u64) -> bool {
+        self.texture_storage
+            .read()
             .unwrap()
-            .insert((texture_id, layout_epoch), bind_group.clone());
+            .contains_key(&texture_id)
+            || self.atlas.read().unwrap().entries.contains_key(&texture_id)
+    }
 
-        Ok(bind_group)
+    pub(crate) fn is_atlas_page_loaded(&self, page_id: u64) -> bool {
+        self.atlas.read().unwrap().pages.contains_key(&page_id)
     }
 
-    pub fn is_texture_loaded(&self, texture_id: u64) -> bool {
+    /// Returns where `texture_id` lives when it was packed with
+    /// [`TextureManager::insert_into_atlas`].
+    pub(crate) fn atlas_placement(&self, texture_id: u64) -> Option<AtlasPlacement> {
+        self.atlas.read().unwrap().placement(texture_id)
+    }
+
+    /// Whether `texture_id` is a [`TextureFormat::R8Mask`] texture that tints its layer.
+    pub(crate) fn is_mask_texture(&self, texture_id: u64) -> bool {
         self.texture_storage
             .read()
             .unwrap()
-            .contains_key(&texture_id)
+            .get(&texture_id)
+            .is_some_and(|texture| texture.format() == TextureFormat::R8Mask.to_wgpu())
+            && !self.yuv_layouts.read().unwrap().contains_key(&texture_id)
+    }
+
+    /// Conversion options and the luma plane's UV rect when `texture_id` holds YUV frames.
+    pub(crate) fn yuv_sampling(&self, texture_id: u64) -> Option<(YuvOptions, [f32; 4])> {
+        self.yuv_layouts
+            .read()
+            .unwrap()
+            .get(&texture_id)
+            .map(|layout| (layout.options, layout.luma_uv_rect()))
     }
 
     pub(crate) fn texture_dimensions(&self, texture_id: u64) -> Option<(u32, u32)> {
+        if let Some(layout) = self.yuv_layouts.read().unwrap().get(&texture_id) {
+            return Some(layout.luma_size);
+        }
         self.texture_storage
             .read()
             .unwrap()
@@ -325,16 +1296,84 @@ impl TextureManager {
                 let size = texture.size();
                 (size.width, size.height)
             })
+            .or_else(|| {
+                self.atlas
+                    .read()
+                    .unwrap()
+                    .entries
+                    .get(&texture_id)
+                    .map(|entry| entry.size)
+            })
     }
 }
 
+/// Maps a `[min_u, min_v, max_u, max_v]` region of a sub-rect into the UVs of the texture
+/// holding it, where the sub-rect spans `uv_rect`.
+pub(crate) fn map_uv_region(uv_rect: [f32; 4], region: [f32; 4]) -> [f32; 4] {
+    let [rect_min_u, rect_min_v, rect_max_u, rect_max_v] = uv_rect;
+    let rect_width = rect_max_u - rect_min_u;
+    let rect_height = rect_max_v - rect_min_v;
+    let [min_u, min_v, max_u, max_v] = region;
+    [
+        rect_min_u + min_u * rect_width,
+        rect_min_v + min_v * rect_height,
+        rect_min_u + max_u * rect_width,
+        rect_min_v + max_v * rect_height,
+    ]
+}
+
+/// Bytes of a strided upload of `size` texels that are read, up to the last texel of its last
+/// row, after checking that rows don't overlap and that `data_length` covers them.
+fn strided_data_length(
+    texture_id: u64,
+    size: (u32, u32),
+    bytes_per_texel: u32,
+    data_length: usize,
+    bytes_per_row: u32,
+) -> Result<usize, TextureManagerError> {
+    let row_bytes = size.0 * bytes_per_texel;
+    if bytes_per_row < row_bytes {
+        return Err(TextureManagerError::BytesPerRowTooSmall {
+            texture_id,
+            bytes_per_row,
+            minimum: row_bytes,
+        });
+    }
+    let expected =
+        bytes_per_row as usize * (size.1 as usize).saturating_sub(1) + row_bytes as usize;
+    if data_length < expected {
+        return Err(TextureManagerError::DataSizeMismatch {
+            texture_id,
+            expected,
+            actual: data_length,
+        });
+    }
+    Ok(expected)
+}
+
+/// Panics unless `texture_data` holds exactly one RGBA8 texel per pixel of `texture_dimensions`.
+fn assert_rgba8_data_size(texture_id: u64, texture_dimensions: (u32, u32), texture_data: &[u8]) {
+    let expected = texture_dimensions.0 as usize * texture_dimensions.1 as usize * 4;
+    assert_eq!(
+        texture_data.len(),
+        expected,
+        "texture {texture_id}: RGBA8 data for {}x{} must be {expected} bytes",
+        texture_dimensions.0,
+        texture_dimensions.1,
+    );
+}
+
+fn bytes_per_texel(texture: &wgpu::Texture) -> u32 {
+    TextureFormat::from_wgpu(texture.format()).map_or(4, TextureFormat::bytes_per_texel)
+}
+
 // Converts an RGBA8 sRGB image in-place to premultiplied alpha.
 // This operates in linear space for correct results:
 // 1) convert sRGB to linear
 // 2) multiply RGB by A
 // 3) convert back to sRGB
 // Alpha remains unchanged numerically in 0..1 mapped to 0..255.
-fn srgb_to_linear_u8(c: u8) -> f32 {
+pub(crate) fn srgb_to_linear_u8(c: u8) -> f32 {
     let x = c as f32 / 255.0;
     if x <= 0.04045 {
         x / 12.92
@@ -343,7 +1382,7 @@ fn srgb_to_linear_u8(c: u8) -> f32 {
     }
 }
 
-fn linear_to_srgb_u8(x: f32) -> u8 {
+pub(crate) fn linear_to_srgb_u8(x: f32) -> u8 {
     let x = x.clamp(0.0, 1.0);
     let y = if x <= 0.0031308 {
         x * 12.92
@@ -0,0 +1,357 @@
+//! Shelf packing of small textures into shared atlas pages.
+//!
+//! Each page is one RGBA8 texture split into horizontal shelves. Entries are placed into the
+//! shelf whose height wastes the least space, and freed spans are merged back so later
+//! insertions can reuse them. When a page is too fragmented to fit a new entry, its live
+//! entries are repacked into a fresh texture.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use super::SamplerOptions;
+
+/// Side length, in texels, of newly created atlas pages (clamped to the device limit).
+pub(super) const ATLAS_PAGE_SIZE: u32 = 2048;
+
+/// Shelves taller than this multiple of an entry's height are only used when no tighter
+/// shelf has room, so small icons do not strand the height of large ones.
+const SHELF_HEIGHT_TOLERANCE: f32 = 1.5;
+
+#[derive(Debug, Clone)]
+struct Shelf {
+    y: u32,
+    height: u32,
+    /// Free horizontal spans as `(x, width)`, sorted by `x` and never adjacent.
+    free_spans: Vec<(u32, u32)>,
+}
+
+impl Shelf {
+    fn new(y: u32, height: u32, width: u32) -> Self {
+        Self {
+            y,
+            height,
+            free_spans: vec![(0, width)],
+        }
+    }
+
+    fn find_span(&self, width: u32) -> Option<usize> {
+        self.free_spans
+            .iter()
+            .position(|&(_, span_width)| span_width >= width)
+    }
+
+    fn take_span(&mut self, span_index: usize, width: u32) -> u32 {
+        let (x, span_width) = self.free_spans[span_index];
+        if span_width == width {
+            self.free_spans.remove(span_index);
+        } else {
+            self.free_spans[span_index] = (x + width, span_width - width);
+        }
+        x
+    }
+
+    fn release_span(&mut self, x: u32, width: u32) {
+        let insert_at = self.free_spans.partition_point(|&(span_x, _)| span_x < x);
+        self.free_spans.insert(insert_at, (x, width));
+        if insert_at + 1 < self.free_spans.len() {
+            let (next_x, next_width) = self.free_spans[insert_at + 1];
+            if x + width == next_x {
+                self.free_spans[insert_at].1 += next_width;
+                self.free_spans.remove(insert_at + 1);
+            }
+        }
+        if insert_at > 0 {
+            let (previous_x, previous_width) = self.free_spans[insert_at - 1];
+            if previous_x + previous_width == x {
+                self.free_spans[insert_at - 1].1 += self.free_spans[insert_at].1;
+                self.free_spans.remove(insert_at);
+            }
+        }
+    }
+
+    fn is_empty(&self, page_width: u32) -> bool {
+        self.free_spans == [(0, page_width)]
+    }
+}
+
+/// Packs rectangles into a fixed-size page using horizontal shelves.
+#[derive(Debug, Clone)]
+pub(crate) struct ShelfPacker {
+    size: (u32, u32),
+    shelves: Vec<Shelf>,
+    allocated_area: u64,
+}
+
+impl ShelfPacker {
+    pub(crate) fn new(size: (u32, u32)) -> Self {
+        Self {
+            size,
+            shelves: Vec::new(),
+            allocated_area: 0,
+        }
+    }
+
+    /// Reserves a `size` rectangle and returns its top-left texel, or `None` if the page
+    /// has no room left for it.
+    pub(crate) fn allocate(&mut self, size: (u32, u32)) -> Option<(u32, u32)> {
+        let (width, height) = size;
+        if width == 0 || height == 0 || width > self.size.0 || height > self.size.1 {
+            return None;
+        }
+
+        let tight_height_limit = (height as f32 * SHELF_HEIGHT_TOLERANCE).ceil() as u32;
+        let best_fit = |shelves: &[Shelf], height_limit: u32| {
+            shelves
+                .iter()
+                .enumerate()
+                .filter(|(_, shelf)| shelf.height >= height && shelf.height <= height_limit)
+                .filter_map(|(shelf_index, shelf)| {
+                    shelf
+                        .find_span(width)
+                        .map(|span_index| (shelf.height, shelf_index, span_index))
+                })
+                .min_by_key(|&(shelf_height, _, _)| shelf_height)
+        };
+
+        let placement = best_fit(&self.shelves, tight_height_limit)
+            .map(|(_, shelf_index, span_index)| (shelf_index, span_index))
+            .or_else(|| {
+                let next_y = self
+                    .shelves
+                    .last()
+                    .map_or(0, |shelf| shelf.y + shelf.height);
+                (next_y + height <= self.size.1).then(|| {
+                    self.shelves.push(Shelf::new(next_y, height, self.size.0));
+                    (self.shelves.len() - 1, 0)
+                })
+            })
+            .or_else(|| {
+                best_fit(&self.shelves, u32::MAX)
+                    .map(|(_, shelf_index, span_index)| (shelf_index, span_index))
+            })?;
+
+        let (shelf_index, span_index) = placement;
+        let shelf = &mut self.shelves[shelf_index];
+        let x = shelf.take_span(span_index, width);
+        self.allocated_area += u64::from(width) * u64::from(height);
+        Some((x, shelf.y))
+    }
+
+    /// Releases a rectangle previously returned by [`ShelfPacker::allocate`].
+    pub(crate) fn deallocate(&mut self, origin: (u32, u32), size: (u32, u32)) {
+        let Some(shelf) = self.shelves.iter_mut().find(|shelf| shelf.y == origin.1) else {
+            return;
+        };
+        shelf.release_span(origin.0, size.0);
+        self.allocated_area = self
+            .allocated_area
+            .saturating_sub(u64::from(size.0) * u64::from(size.1));
+
+        // Trailing empty shelves give their height back to the page.
+        while self
+            .shelves
+            .last()
+            .is_some_and(|shelf| shelf.is_empty(self.size.0))
+        {
+            self.shelves.pop();
+        }
+    }
+
+    pub(crate) fn free_area(&self) -> u64 {
+        u64::from(self.size.0) * u64::from(self.size.1) - self.allocated_area
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.allocated_area == 0
+    }
+}
+
+/// Location of an atlas entry inside its page.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct AtlasPlacement {
+    pub(crate) page_id: u64,
+    /// `[min_u, min_v, max_u, max_v]` of the entry in page UVs.
+    pub(crate) uv_rect: [f32; 4],
+}
+
+impl AtlasPlacement {
+    fn new(page_id: u64, page_size: (u32, u32), origin: (u32, u32), size: (u32, u32)) -> Self {
+        let page_width = page_size.0 as f32;
+        let page_height = page_size.1 as f32;
+        Self {
+            page_id,
+            uv_rect: [
+                origin.0 as f32 / page_width,
+                origin.1 as f32 / page_height,
+                (origin.0 + size.0) as f32 / page_width,
+                (origin.1 + size.1) as f32 / page_height,
+            ],
+        }
+    }
+
+    /// Maps a `[min_u, min_v, max_u, max_v]` region of the entry into page UVs.
+    pub(crate) fn map_region(&self, entry_region: [f32; 4]) -> [f32; 4] {
+        super::map_uv_region(self.uv_rect, entry_region)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(super) struct AtlasEntry {
+    pub(super) page_id: u64,
+    pub(super) origin: (u32, u32),
+    pub(super) size: (u32, u32),
+}
+
+pub(super) struct AtlasPage {
+    pub(super) texture: wgpu::Texture,
+    pub(super) packer: ShelfPacker,
+    /// Shape bind groups for this page keyed by layout epoch and sampler.
+    pub(super) bind_groups: HashMap<(u64, SamplerOptions), Arc<wgpu::BindGroup>>,
+}
+
+/// A fresh packing of one page's live entries plus a pending new entry.
+pub(super) struct RepackPlan {
+    pub(super) packer: ShelfPacker,
+    /// New origin of every live entry; the pending entry is keyed by `None`.
+    pub(super) origins: Vec<(Option<u64>, (u32, u32))>,
+}
+
+/// Atlas pages and the entries packed into them.
+#[derive(Default)]
+pub(super) struct TextureAtlas {
+    pub(super) pages: BTreeMap<u64, AtlasPage>,
+    pub(super) entries: HashMap<u64, AtlasEntry>,
+    next_page_id: u64,
+}
+
+impl TextureAtlas {
+    pub(super) fn next_page_id(&mut self) -> u64 {
+        let page_id = self.next_page_id;
+        self.next_page_id += 1;
+        page_id
+    }
+
+    pub(super) fn placement(&self, texture_id: u64) -> Option<AtlasPlacement> {
+        let entry = self.entries.get(&texture_id)?;
+        let page = self.pages.get(&entry.page_id)?;
+        let page_size = page.texture.size();
+        Some(AtlasPlacement::new(
+            entry.page_id,
+            (page_size.width, page_size.height),
+            entry.origin,
+            entry.size,
+        ))
+    }
+
+    /// Frees the entry's rect and drops its page once nothing else lives on it.
+    pub(super) fn remove_entry(&mut self, texture_id: u64) -> bool {
+        let Some(entry) = self.entries.remove(&texture_id) else {
+            return false;
+        };
+        if let Some(page) = self.pages.get_mut(&entry.page_id) {
+            page.packer.deallocate(entry.origin, entry.size);
+            if page.packer.is_empty() {
+                self.pages.remove(&entry.page_id);
+            }
+        }
+        true
+    }
+
+    /// Packs `new_entry_size` into the first page with room, returning the page and origin.
+    pub(super) fn allocate(&mut self, new_entry_size: (u32, u32)) -> Option<(u64, (u32, u32))> {
+        self.pages.iter_mut().find_map(|(&page_id, page)| {
+            page.packer
+                .allocate(new_entry_size)
+                .map(|origin| (page_id, origin))
+        })
+    }
+
+    /// Plans a repack of `page_id` that also fits `new_entry_size`, or returns `None` when
+    /// even a fresh packing cannot fit everything.
+    pub(super) fn plan_repack(
+        &self,
+        page_id: u64,
+        new_entry_size: (u32, u32),
+    ) -> Option<RepackPlan> {
+        let page = self.pages.get(&page_id)?;
+        let needed_area = u64::from(new_entry_size.0) * u64::from(new_entry_size.1);
+        if page.packer.free_area() < needed_area {
+            return None;
+        }
+
+        let mut pending_entries = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.page_id == page_id)
+            .map(|(&texture_id, entry)| (Some(texture_id), entry.size))
+            .chain(std::iter::once((None, new_entry_size)))
+            .collect::<Vec<_>>();
+        // Tallest first keeps shelves dense.
+        pending_entries.sort_by_key(|&(texture_id, size)| (std::cmp::Reverse(size.1), texture_id));
+
+        let page_size = page.texture.size();
+        let mut packer = ShelfPacker::new((page_size.width, page_size.height));
+        let origins = pending_entries
+            .into_iter()
+            .map(|(texture_id, size)| packer.allocate(size).map(|origin| (texture_id, origin)))
+            .collect::<Option<Vec<_>>>()?;
+        Some(RepackPlan { packer, origins })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shelf_packer_reuses_freed_spans_and_reclaims_trailing_shelves() {
+        let mut packer = ShelfPacker::new((64, 64));
+        let first = packer.allocate((32, 16)).unwrap();
+        let second = packer.allocate((32, 16)).unwrap();
+        assert_eq!(first, (0, 0));
+        assert_eq!(second, (32, 0));
+        assert_eq!(packer.allocate((1, 1)), Some((0, 16)));
+        assert_eq!(packer.allocate((64, 64)), None);
+
+        packer.deallocate(first, (32, 16));
+        assert_eq!(packer.allocate((24, 12)), Some((0, 0)));
+
+        packer.deallocate((0, 16), (1, 1));
+        packer.deallocate((0, 0), (24, 12));
+        packer.deallocate(second, (32, 16));
+        assert!(packer.is_empty());
+        assert_eq!(packer.allocate((64, 64)), Some((0, 0)));
+    }
+
+    #[test]
+    fn shelf_packer_prefers_the_tightest_shelf() {
+        let mut packer = ShelfPacker::new((64, 64));
+        packer.allocate((16, 32)).unwrap();
+        packer.allocate((16, 8)).unwrap();
+        // Fits both shelves; the 8-texel shelf wastes less height.
+        assert_eq!(packer.allocate((16, 8)), Some((16, 32)));
+    }
+
+    #[test]
+    fn placement_maps_entry_regions_into_page_uvs() {
+        let placement = AtlasPlacement::new(3, (100, 50), (10, 20), (20, 10));
+        let assert_close = |actual: [f32; 4], expected: [f32; 4]| {
+            assert!(
+                actual
+                    .iter()
+                    .zip(expected)
+                    .all(|(actual, expected)| (actual - expected).abs() < 1e-6),
+                "{actual:?} != {expected:?}"
+            );
+        };
+        assert_close(placement.uv_rect, [0.1, 0.4, 0.3, 0.6]);
+        assert_close(
+            placement.map_region([0.0, 0.0, 1.0, 1.0]),
+            placement.uv_rect,
+        );
+        assert_close(
+            placement.map_region([0.5, 0.0, 1.0, 0.5]),
+            [0.2, 0.4, 0.3, 0.5],
+        );
+    }
+}
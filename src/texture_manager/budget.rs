@@ -0,0 +1,142 @@
+//! GPU memory accounting and least-recently-used eviction of standalone textures.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Called with the id of each texture evicted to stay within the memory budget.
+pub(super) type EvictionCallback = Arc<dyn Fn(u64) + Send + Sync>;
+
+/// Bytes held by a texture of `texture_dimensions` with `mip_level_count` levels.
+pub(super) fn allocation_bytes(
+    texture_dimensions: (u32, u32),
+    bytes_per_texel: u32,
+    mip_level_count: u32,
+) -> u64 {
+    (0..mip_level_count)
+        .map(|level| {
+            let width = (texture_dimensions.0 >> level).max(1) as u64;
+            let height = (texture_dimensions.1 >> level).max(1) as u64;
+            width * height * bytes_per_texel as u64
+        })
+        .sum()
+}
+
+pub(super) fn texture_bytes(texture: &wgpu::Texture) -> u64 {
+    let size = texture.size();
+    allocation_bytes(
+        (size.width, size.height),
+        texture.format().block_copy_size(None).unwrap_or(4),
+        texture.mip_level_count(),
+    )
+}
+
+/// Frame bookkeeping behind [`TextureManager::set_memory_budget`](super::TextureManager::set_memory_budget).
+#[derive(Default)]
+pub(super) struct TextureUsage {
+    /// Index of the frame being recorded.
+    pub(super) frame: u64,
+    /// Frame each standalone texture was last allocated or drawn in.
+    pub(super) last_used: HashMap<u64, u64>,
+    /// Textures registered from outside the manager, which it cannot recreate. They count
+    /// towards the budget but are never evicted.
+    pub(super) external: HashSet<u64>,
+    pub(super) budget_bytes: Option<u64>,
+    pub(super) on_evict: Option<EvictionCallback>,
+}
+
+impl TextureUsage {
+    /// Starts tracking a texture that was just stored under `texture_id`.
+    pub(super) fn track(&mut self, texture_id: u64, external: bool) {
+        self.last_used.insert(texture_id, self.frame);
+        if external {
+            self.external.insert(texture_id);
+        } else {
+            self.external.remove(&texture_id);
+        }
+    }
+
+    pub(super) fn forget(&mut self, texture_id: u64) {
+        self.last_used.remove(&texture_id);
+        self.external.remove(&texture_id);
+    }
+
+    pub(super) fn is_evictable(&self, texture_id: u64) -> bool {
+        !self.external.contains(&texture_id)
+    }
+
+    pub(super) fn frames_since_use(&self, texture_id: u64) -> u64 {
+        self.last_used
+            .get(&texture_id)
+            .map_or(0, |&last_used| self.frame.saturating_sub(last_used))
+    }
+}
+
+/// Picks textures to evict, least recently used first, until `total_bytes` fits in
+/// `budget_bytes`. Textures used in `current_frame` are never picked.
+///
+/// `candidates` holds `(texture_id, bytes, last_used_frame)` and is reordered in place.
+pub(super) fn plan_evictions(
+    candidates: &mut [(u64, u64, u64)],
+    total_bytes: u64,
+    budget_bytes: u64,
+    current_frame: u64,
+) -> Vec<u64> {
+    candidates.sort_unstable_by_key(|&(texture_id, _, last_used)| (last_used, texture_id));
+    let mut remaining_bytes = total_bytes;
+    let mut evicted = Vec::new();
+    for &(texture_id, bytes, last_used) in candidates.iter() {
+        if remaining_bytes <= budget_bytes || last_used >= current_frame {
+            break;
+        }
+        remaining_bytes = remaining_bytes.saturating_sub(bytes);
+        evicted.push(texture_id);
+    }
+    evicted
+}
+
+/// GPU memory held by a [`TextureManager`](super::TextureManager), from
+/// [`TextureManager::memory_report`](super::TextureManager::memory_report).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TextureMemoryReport {
+    /// Bytes held by standalone textures and atlas pages, including mip chains.
+    pub total_bytes: u64,
+    /// Bytes held by atlas pages, which are shared and never evicted.
+    pub atlas_bytes: u64,
+    pub budget_bytes: Option<u64>,
+    /// Standalone textures, largest first.
+    pub textures: Vec<TextureMemoryUsage>,
+}
+
+/// Memory held by one standalone texture.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TextureMemoryUsage {
+    pub texture_id: u64,
+    pub bytes: u64,
+    /// Rendered frames since the texture was last allocated or drawn.
+    pub frames_since_use: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocation_bytes_include_every_mip_level() {
+        assert_eq!(allocation_bytes((4, 4), 4, 1), 64);
+        // 4×2, 2×1, and 1×1 levels.
+        assert_eq!(allocation_bytes((4, 2), 4, 3), 32 + 8 + 4);
+        assert_eq!(allocation_bytes((3, 1), 1, 2), 3 + 1);
+    }
+
+    #[test]
+    fn evictions_take_least_recently_used_textures_until_within_budget() {
+        let mut candidates = [(1, 100, 5), (2, 100, 3), (3, 100, 4), (4, 100, 6)];
+        assert_eq!(plan_evictions(&mut candidates, 400, 250, 6), vec![2, 3]);
+        assert_eq!(
+            plan_evictions(&mut candidates, 400, 400, 6),
+            Vec::<u64>::new()
+        );
+        // Textures drawn in the current frame stay even when over budget.
+        assert_eq!(plan_evictions(&mut candidates, 400, 0, 5), vec![2, 3]);
+    }
+}
@@ -0,0 +1,103 @@
+//! Texel formats accepted by [`TextureManager`](super::TextureManager) allocations.
+
+/// Layout of the bytes uploaded into a texture, and how shapes sample it.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum TextureFormat {
+    /// RGBA8 sRGB with premultiplied alpha.
+    #[default]
+    Rgba8Srgb,
+    /// BGRA8 sRGB with premultiplied alpha, as produced by most capture APIs. Uploaded as-is;
+    /// the GPU swizzles when sampling.
+    Bgra8Srgb,
+    /// RGBA8 holding linear values with premultiplied alpha, e.g. a subtree rendered on a
+    /// renderer whose target format is not sRGB.
+    Rgba8Unorm,
+    /// BGRA8 counterpart of [`TextureFormat::Rgba8Unorm`].
+    Bgra8Unorm,
+    /// Four half floats per texel in linear space with premultiplied alpha, for HDR images.
+    Rgba16Float,
+    /// One coverage byte per texel, such as glyph or icon masks. A mask layer tints: it
+    /// multiplies the color beneath it (the shape's fill, plus the background layer for a
+    /// foreground mask) by its coverage.
+    R8Mask,
+}
+
+impl TextureFormat {
+    pub(super) fn to_wgpu(self) -> wgpu::TextureFormat {
+        match self {
+            Self::Rgba8Srgb => wgpu::TextureFormat::Rgba8UnormSrgb,
+            Self::Bgra8Srgb => wgpu::TextureFormat::Bgra8UnormSrgb,
+            Self::Rgba8Unorm => wgpu::TextureFormat::Rgba8Unorm,
+            Self::Bgra8Unorm => wgpu::TextureFormat::Bgra8Unorm,
+            Self::Rgba16Float => wgpu::TextureFormat::Rgba16Float,
+            Self::R8Mask => wgpu::TextureFormat::R8Unorm,
+        }
+    }
+
+    pub(super) fn from_wgpu(format: wgpu::TextureFormat) -> Option<Self> {
+        match format {
+            wgpu::TextureFormat::Rgba8UnormSrgb => Some(Self::Rgba8Srgb),
+            wgpu::TextureFormat::Bgra8UnormSrgb => Some(Self::Bgra8Srgb),
+            wgpu::TextureFormat::Rgba8Unorm => Some(Self::Rgba8Unorm),
+            wgpu::TextureFormat::Bgra8Unorm => Some(Self::Bgra8Unorm),
+            wgpu::TextureFormat::Rgba16Float => Some(Self::Rgba16Float),
+            wgpu::TextureFormat::R8Unorm => Some(Self::R8Mask),
+            _ => None,
+        }
+    }
+
+    pub fn bytes_per_texel(self) -> u32 {
+        match self {
+            Self::Rgba8Srgb | Self::Bgra8Srgb | Self::Rgba8Unorm | Self::Bgra8Unorm => 4,
+            Self::Rgba16Float => 8,
+            Self::R8Mask => 1,
+        }
+    }
+}
+
+/// Options for [`TextureManager::allocate_texture_with_options`](super::TextureManager::allocate_texture_with_options).
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct TextureOptions {
+    pub format: TextureFormat,
+    /// Builds a full mip chain on the GPU whenever data is loaded.
+    pub mipmaps: bool,
+}
+
+impl TextureOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn format(mut self, format: TextureFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn mipmaps(mut self, mipmaps: bool) -> Self {
+        self.mipmaps = mipmaps;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_round_trip_through_wgpu() {
+        for format in [
+            TextureFormat::Rgba8Srgb,
+            TextureFormat::Bgra8Srgb,
+            TextureFormat::Rgba8Unorm,
+            TextureFormat::Bgra8Unorm,
+            TextureFormat::Rgba16Float,
+            TextureFormat::R8Mask,
+        ] {
+            assert_eq!(TextureFormat::from_wgpu(format.to_wgpu()), Some(format));
+            assert_eq!(
+                format.to_wgpu().block_copy_size(None),
+                Some(format.bytes_per_texel())
+            );
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! PNG, JPEG, and WebP decoding into premultiplied textures, behind the `image` feature.
+
+use std::path::Path;
+use std::thread::JoinHandle;
+
+use super::{premultiply_rgba8_srgb_inplace, TextureManager, TextureManagerError, TextureOptions};
+
+/// An image decoded to premultiplied RGBA8 sRGB, ready to upload.
+///
+/// Decoding does not touch the GPU, so it can run on any thread; upload the result with
+/// [`TextureManager::load_decoded_image`].
+#[derive(Clone, Debug)]
+pub struct DecodedImage {
+    dimensions: (u32, u32),
+    pixels: Vec<u8>,
+}
+
+impl DecodedImage {
+    /// Decodes PNG, JPEG, or WebP bytes, detecting the format from its signature.
+    pub fn decode(bytes: &[u8]) -> Result<Self, TextureManagerError> {
+        Ok(Self::from_dynamic_image(image::load_from_memory(bytes)?))
+    }
+
+    /// Reads and decodes an image file, detecting the format from its contents.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, TextureManagerError> {
+        let image = image::ImageReader::open(path)
+            .map_err(image::ImageError::IoError)?
+            .with_guessed_format()
+            .map_err(image::ImageError::IoError)?
+            .decode()?;
+        Ok(Self::from_dynamic_image(image))
+    }
+
+    fn from_dynamic_image(image: image::DynamicImage) -> Self {
+        let image = image.into_rgba8();
+        let dimensions = image.dimensions();
+        let mut pixels = image.into_raw();
+        premultiply_rgba8_srgb_inplace(&mut pixels);
+        Self { dimensions, pixels }
+    }
+
+    pub fn dimensions(&self) -> (u32, u32) {
+        self.dimensions
+    }
+
+    /// Premultiplied RGBA8 sRGB texels, row by row.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+}
+
+impl TextureManager {
+    /// Decodes a PNG, JPEG, or WebP image and uploads it as texture `texture_id`, replacing any
+    /// texture with that id.
+    ///
+    /// # Returns
+    /// The image's dimensions, or `Err(TextureManagerError::ImageDecode(_))` if the bytes are
+    /// not a supported image.
+    pub fn load_image_bytes(
+        &self,
+        texture_id: u64,
+        bytes: &[u8],
+    ) -> Result<(u32, u32), TextureManagerError> {
+        self.load_decoded_image(texture_id, &DecodedImage::decode(bytes)?, false)
+    }
+
+    /// Reads and decodes an image file and uploads it as texture `texture_id`, replacing any
+    /// texture with that id.
+    ///
+    /// # Returns
+    /// The image's dimensions, or `Err(TextureManagerError::ImageDecode(_))` if the file cannot
+    /// be read or is not a supported image.
+    pub fn load_image_path(
+        &self,
+        texture_id: u64,
+        path: impl AsRef<Path>,
+    ) -> Result<(u32, u32), TextureManagerError> {
+        self.load_decoded_image(texture_id, &DecodedImage::open(path)?, false)
+    }
+
+    /// Uploads a decoded image as texture `texture_id`, building a mip chain when `mipmaps` is
+    /// set. Replaces any texture with that id.
+    ///
+    /// # Returns
+    /// The image's dimensions.
+    pub fn load_decoded_image(
+        &self,
+        texture_id: u64,
+        image: &DecodedImage,
+        mipmaps: bool,
+    ) -> Result<(u32, u32), TextureManagerError> {
+        self.allocate_texture_with_options(
+            texture_id,
+            image.dimensions,
+            TextureOptions::new().mipmaps(mipmaps),
+        );
+        self.load_data_into_texture(texture_id, image.dimensions, &image.pixels)?;
+        Ok(image.dimensions)
+    }
+
+    /// Decodes `bytes` on a new thread and uploads the texture once decoding finishes.
+    ///
+    /// Shapes referencing `texture_id` draw without the texture until it is uploaded. Join the
+    /// handle to learn the image's dimensions or why loading failed.
+    pub fn load_image_in_background(
+        &self,
+        texture_id: u64,
+        bytes: Vec<u8>,
+        mipmaps: bool,
+    ) -> JoinHandle<Result<(u32, u32), TextureManagerError>> {
+        let texture_manager = self.clone();
+        std::thread::spawn(move || {
+            let image = DecodedImage::decode(&bytes)?;
+            texture_manager.load_decoded_image(texture_id, &image, mipmaps)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decoded_images_are_premultiplied() {
+        let source = image::RgbaImage::from_raw(2, 1, vec![255, 0, 0, 255, 255, 255, 255, 0])
+            .expect("pixel count matches dimensions");
+        let mut png = Vec::new();
+        source
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .expect("PNG encoding succeeds");
+
+        let decoded = DecodedImage::decode(&png).unwrap();
+        assert_eq!(decoded.dimensions(), (2, 1));
+        assert_eq!(decoded.pixels(), &[255, 0, 0, 255, 0, 0, 0, 0]);
+
+        assert!(matches!(
+            DecodedImage::decode(b"not an image"),
+            Err(TextureManagerError::ImageDecode(_))
+        ));
+    }
+}
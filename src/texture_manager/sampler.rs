@@ -0,0 +1,143 @@
+//! Sampler state shared by textures and cached per distinct configuration.
+
+/// Filtering used when a texture is magnified or minified.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum TextureFilter {
+    /// Blend neighboring texels; smooth for photos and scaled UI.
+    #[default]
+    Linear,
+    /// Use the closest texel; crisp for pixel art and 1:1 bitmaps.
+    Nearest,
+}
+
+impl TextureFilter {
+    fn to_wgpu(self) -> wgpu::FilterMode {
+        match self {
+            Self::Linear => wgpu::FilterMode::Linear,
+            Self::Nearest => wgpu::FilterMode::Nearest,
+        }
+    }
+}
+
+/// How texture coordinates outside `[0, 1]` resolve along one axis.
+///
+/// Applies to the [`ShapeTextureFitMode`](crate::ShapeTextureFitMode)s that map the texture
+/// once (`Stretch`, `OriginalSize`, `Cover`, and `Contain`); `Tile` and `NineSlice` define
+/// their own repetition. Source rects and atlas entries wrap within their region.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum TextureAddressMode {
+    /// Repeat the edge texels.
+    #[default]
+    ClampToEdge,
+    /// Repeat the texture.
+    Repeat,
+    /// Repeat the texture, flipping every other copy.
+    MirrorRepeat,
+}
+
+impl TextureAddressMode {
+    fn to_wgpu(self) -> wgpu::AddressMode {
+        match self {
+            Self::ClampToEdge => wgpu::AddressMode::ClampToEdge,
+            Self::Repeat => wgpu::AddressMode::Repeat,
+            Self::MirrorRepeat => wgpu::AddressMode::MirrorRepeat,
+        }
+    }
+}
+
+/// Sampler configuration for a texture layer.
+///
+/// Set a texture's default with
+/// [`TextureManager::set_texture_sampler`](crate::TextureManager::set_texture_sampler), or
+/// override it for one draw with
+/// [`ShapeTextureOptions::sampler`](crate::ShapeTextureOptions::sampler). The default is linear
+/// filtering clamped to the edge.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct SamplerOptions {
+    pub mag_filter: TextureFilter,
+    pub min_filter: TextureFilter,
+    pub address_mode_u: TextureAddressMode,
+    pub address_mode_v: TextureAddressMode,
+    /// Maximum anisotropy, clamped to `1..=16`. Only used when both filters are linear.
+    pub anisotropy: u16,
+}
+
+impl Default for SamplerOptions {
+    fn default() -> Self {
+        Self {
+            mag_filter: TextureFilter::Linear,
+            min_filter: TextureFilter::Linear,
+            address_mode_u: TextureAddressMode::ClampToEdge,
+            address_mode_v: TextureAddressMode::ClampToEdge,
+            anisotropy: 1,
+        }
+    }
+}
+
+impl SamplerOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Nearest-neighbor filtering for pixel art and crisp 1:1 bitmaps.
+    pub fn nearest() -> Self {
+        Self::default().filter(TextureFilter::Nearest)
+    }
+
+    /// Sets both the magnification and minification filter.
+    pub fn filter(mut self, filter: TextureFilter) -> Self {
+        self.mag_filter = filter;
+        self.min_filter = filter;
+        self
+    }
+
+    /// Sets the address mode on both axes.
+    pub fn address_mode(mut self, address_mode: TextureAddressMode) -> Self {
+        self.address_mode_u = address_mode;
+        self.address_mode_v = address_mode;
+        self
+    }
+
+    pub fn anisotropy(mut self, anisotropy: u16) -> Self {
+        self.anisotropy = anisotropy;
+        self
+    }
+
+    /// Anisotropy accepted by wgpu: linear filtering only, at most 16.
+    fn anisotropy_clamp(&self) -> u16 {
+        if self.mag_filter == TextureFilter::Linear && self.min_filter == TextureFilter::Linear {
+            self.anisotropy.clamp(1, 16)
+        } else {
+            1
+        }
+    }
+
+    pub(super) fn create_sampler(&self, device: &wgpu::Device) -> wgpu::Sampler {
+        device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("shape_texture_sampler"),
+            address_mode_u: self.address_mode_u.to_wgpu(),
+            address_mode_v: self.address_mode_v.to_wgpu(),
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: self.mag_filter.to_wgpu(),
+            min_filter: self.min_filter.to_wgpu(),
+            mipmap_filter: wgpu::FilterMode::Linear,
+            anisotropy_clamp: self.anisotropy_clamp(),
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn anisotropy_is_clamped_and_dropped_for_nearest_filtering() {
+        assert_eq!(SamplerOptions::new().anisotropy(64).anisotropy_clamp(), 16);
+        assert_eq!(SamplerOptions::new().anisotropy(0).anisotropy_clamp(), 1);
+        assert_eq!(
+            SamplerOptions::nearest().anisotropy(8).anisotropy_clamp(),
+            1
+        );
+    }
+}
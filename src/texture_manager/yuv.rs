@@ -0,0 +1,167 @@
+//! Planar YUV video frames, converted to RGB in the shape fragment shader.
+//!
+//! All planes of a frame live in one R8 texture so a video binds like any other texture: the
+//! luma plane fills the top rows, and the half-resolution chroma planes sit side by side below
+//! it. NV12's interleaved UV plane is stored as-is across the full width. Luma is filtered by
+//! the layer's sampler; the shader fetches and interpolates chroma itself.
+
+/// Plane layout of a YUV frame, both with chroma subsampled 2×2.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum YuvFormat {
+    /// A luma plane followed by one plane of interleaved U and V bytes.
+    Nv12,
+    /// Separate luma, U, and V planes.
+    I420,
+}
+
+impl YuvFormat {
+    pub fn plane_count(self) -> usize {
+        match self {
+            Self::Nv12 => 2,
+            Self::I420 => 3,
+        }
+    }
+}
+
+/// Matrix converting YUV to RGB.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum YuvColorMatrix {
+    /// Standard-definition video.
+    #[default]
+    Bt601,
+    /// High-definition video.
+    Bt709,
+}
+
+/// Range of the encoded YUV values.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum YuvRange {
+    /// Luma in `16..=235` and chroma in `16..=240`, as most video decoders produce.
+    #[default]
+    Limited,
+    /// Every channel in `0..=255`, as produced by JPEG and some cameras.
+    Full,
+}
+
+/// Format and color conversion of a YUV texture.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct YuvOptions {
+    pub format: YuvFormat,
+    pub matrix: YuvColorMatrix,
+    pub range: YuvRange,
+}
+
+impl YuvOptions {
+    /// Limited-range BT.601 frames in `format`.
+    pub fn new(format: YuvFormat) -> Self {
+        Self {
+            format,
+            matrix: YuvColorMatrix::default(),
+            range: YuvRange::default(),
+        }
+    }
+
+    pub fn matrix(mut self, matrix: YuvColorMatrix) -> Self {
+        self.matrix = matrix;
+        self
+    }
+
+    pub fn range(mut self, range: YuvRange) -> Self {
+        self.range = range;
+        self
+    }
+}
+
+/// One plane of a YUV frame, as handed out by a decoder.
+#[derive(Copy, Clone, Debug)]
+pub struct YuvPlane<'a> {
+    pub data: &'a [u8],
+    /// Stride between rows of `data`, which decoders often pad past the plane's width.
+    pub bytes_per_row: u32,
+}
+
+impl<'a> YuvPlane<'a> {
+    pub fn new(data: &'a [u8], bytes_per_row: u32) -> Self {
+        Self {
+            data,
+            bytes_per_row,
+        }
+    }
+}
+
+/// A YUV texture's options and the layout of its planes within the packed texture.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(super) struct YuvLayout {
+    pub(super) options: YuvOptions,
+    /// Dimensions of the luma plane, which are the frame's dimensions.
+    pub(super) luma_size: (u32, u32),
+}
+
+impl YuvLayout {
+    pub(super) fn chroma_size(&self) -> (u32, u32) {
+        (self.luma_size.0.div_ceil(2), self.luma_size.1.div_ceil(2))
+    }
+
+    /// Size of the texture holding every plane. The width is even so both chroma planes fit.
+    pub(super) fn packed_size(&self) -> (u32, u32) {
+        let chroma_size = self.chroma_size();
+        (chroma_size.0 * 2, self.luma_size.1 + chroma_size.1)
+    }
+
+    /// `(origin, size)` of each plane within the packed texture, in upload order.
+    pub(super) fn plane_regions(&self) -> Vec<((u32, u32), (u32, u32))> {
+        let (chroma_width, chroma_height) = self.chroma_size();
+        let chroma_top = self.luma_size.1;
+        let luma = ((0, 0), self.luma_size);
+        match self.options.format {
+            YuvFormat::Nv12 => vec![luma, ((0, chroma_top), (chroma_width * 2, chroma_height))],
+            YuvFormat::I420 => vec![
+                luma,
+                ((0, chroma_top), (chroma_width, chroma_height)),
+                ((chroma_width, chroma_top), (chroma_width, chroma_height)),
+            ],
+        }
+    }
+
+    /// `[min_u, min_v, max_u, max_v]` of the luma plane in packed texture UVs.
+    pub(super) fn luma_uv_rect(&self) -> [f32; 4] {
+        let packed_size = self.packed_size();
+        [
+            0.0,
+            0.0,
+            self.luma_size.0 as f32 / packed_size.0 as f32,
+            self.luma_size.1 as f32 / packed_size.1 as f32,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn planes_pack_below_luma_and_odd_sizes_round_chroma_up() {
+        let i420 = YuvLayout {
+            options: YuvOptions::new(YuvFormat::I420),
+            luma_size: (5, 3),
+        };
+        assert_eq!(i420.chroma_size(), (3, 2));
+        assert_eq!(i420.packed_size(), (6, 5));
+        assert_eq!(
+            i420.plane_regions(),
+            vec![((0, 0), (5, 3)), ((0, 3), (3, 2)), ((3, 3), (3, 2))]
+        );
+        // The shader recovers the luma height from the packed height.
+        assert_eq!(i420.packed_size().1 * 2 / 3, 3);
+
+        let nv12 = YuvLayout {
+            options: YuvOptions::new(YuvFormat::Nv12),
+            luma_size: (4, 4),
+        };
+        assert_eq!(
+            nv12.plane_regions(),
+            vec![((0, 0), (4, 4)), ((0, 4), (4, 2))]
+        );
+        assert_eq!(nv12.luma_uv_rect(), [0.0, 0.0, 1.0, 4.0 / 6.0]);
+    }
+}
@@ -289,9 +289,23 @@ impl InstanceTransform {
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
 pub struct InstanceMetadata {
     pub draw_order: f32,
+    /// Bits 0-1 mark active layers. Bits 2-12 and 13-23 hold layer 0's and layer 1's flags:
+    /// sampling mode (2 bits), u and v address modes (2 bits each), mask (1 bit), YUV format
+    /// (2 bits), BT.709 matrix (1 bit), and full range (1 bit). Stored as an `f32`, which
+    /// represents integers exactly only up to 2^24, so the flags must stay within 24 bits.
     pub texture_flags: f32,
-    pub texture_uv_scale_layer0: [f32; 2],
-    pub texture_uv_scale_layer1: [f32; 2],
+    /// Per-layer UV mapping: `xy` scales and `zw` offsets the shape's texture coordinates
+    /// into the layer's fit space.
+    pub texture_uv_transform_layer0: [f32; 4],
+    pub texture_uv_transform_layer1: [f32; 4],
+    /// Per-layer `[min_u, min_v, max_u, max_v]` of the sampled region. Fit-space UVs are
+    /// mapped into it and clamped to its edge texels, so atlas entries never sample their
+    /// neighbors.
+    pub texture_region_layer0: [f32; 4],
+    pub texture_region_layer1: [f32; 4],
+    /// Per-layer parameters of the sampling mode: tile pitch or nine-slice insets.
+    pub texture_fit_params_layer0: [f32; 4],
+    pub texture_fit_params_layer1: [f32; 4],
 }
 
 impl Default for InstanceMetadata {
@@ -299,38 +313,59 @@ impl Default for InstanceMetadata {
         Self {
             draw_order: 0.0,
             texture_flags: 0.0,
-            texture_uv_scale_layer0: [1.0, 1.0],
-            texture_uv_scale_layer1: [1.0, 1.0],
+            texture_uv_transform_layer0: [1.0, 1.0, 0.0, 0.0],
+            texture_uv_transform_layer1: [1.0, 1.0, 0.0, 0.0],
+            texture_region_layer0: [0.0, 0.0, 1.0, 1.0],
+            texture_region_layer1: [0.0, 0.0, 1.0, 1.0],
+            texture_fit_params_layer0: [0.0; 4],
+            texture_fit_params_layer1: [0.0; 4],
         }
     }
 }
 
 impl InstanceMetadata {
     pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        // Draw order and texture flags share one attribute to stay within the 16 vertex
+        // attributes every backend supports.
         wgpu::VertexBufferLayout {
             array_stride: std::mem::size_of::<InstanceMetadata>() as wgpu::BufferAddress,
             step_mode: wgpu::VertexStepMode::Instance,
             attributes: &[
                 wgpu::VertexAttribute {
-                    format: wgpu::VertexFormat::Float32,
+                    format: wgpu::VertexFormat::Float32x2,
                     offset: 0,
                     shader_location: 7,
                 },
                 wgpu::VertexAttribute {
-                    format: wgpu::VertexFormat::Float32,
-                    offset: std::mem::size_of::<f32>() as wgpu::BufferAddress,
-                    shader_location: 10,
-                },
-                wgpu::VertexAttribute {
-                    format: wgpu::VertexFormat::Float32x2,
+                    format: wgpu::VertexFormat::Float32x4,
                     offset: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
                     shader_location: 11,
                 },
                 wgpu::VertexAttribute {
-                    format: wgpu::VertexFormat::Float32x2,
-                    offset: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: std::mem::size_of::<[f32; 6]>() as wgpu::BufferAddress,
                     shader_location: 12,
                 },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: std::mem::size_of::<[f32; 10]>() as wgpu::BufferAddress,
+                    shader_location: 13,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: std::mem::size_of::<[f32; 14]>() as wgpu::BufferAddress,
+                    shader_location: 14,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: std::mem::size_of::<[f32; 18]>() as wgpu::BufferAddress,
+                    shader_location: 10,
+                },
+                wgpu::VertexAttribute {
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: std::mem::size_of::<[f32; 22]>() as wgpu::BufferAddress,
+                    shader_location: 15,
+                },
             ],
         }
     }
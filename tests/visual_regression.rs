@@ -596,95 +596,2863 @@ fn single_root_no_children() {
     assert_pixels_match(&pixel_buffer, &expectations);
 }
 
-/// Regression test — OriginalSize texture fit uses physical pixels, not logical units.
+/// Atlas source rects select a sub-region of the texture, in texels or normalized UVs.
 #[test]
-fn original_size_texture_fit_uses_physical_pixels_on_hidpi() {
-    let physical_size = (200, 200);
-    let scale_factor = 2.0;
-    let Some(mut renderer) =
-        create_headless_renderer_with_size_and_scale(physical_size, scale_factor)
-    else {
+fn texture_source_rect_samples_atlas_region() {
+    let Some(mut renderer) = create_headless_renderer() else {
         return;
     };
 
-    let green_texture_id = 9_001u64;
-    let green_texture_with_transparent_border_20x20 = (0..20u32)
+    let atlas_texture_id = 9_101u64;
+    let quadrant_atlas_4x4 = (0..4u32)
         .flat_map(|y| {
-            (0..20u32).flat_map(move |x| {
-                if x == 0 || x == 19 || y == 0 || y == 19 {
-                    [0u8, 0u8, 0u8, 0u8]
+            (0..4u32).flat_map(move |x| match (x < 2, y < 2) {
+                (true, true) => [255u8, 0, 0, 255],
+                (false, true) => [0, 0, 255, 255],
+                (true, false) => [0, 255, 0, 255],
+                (false, false) => [255, 255, 255, 255],
+            })
+        })
+        .collect::<Vec<_>>();
+    renderer.texture_manager().allocate_texture_with_data(
+        atlas_texture_id,
+        (4, 4),
+        &quadrant_atlas_4x4,
+    );
+
+    let canvas_root_id = renderer
+        .add_shape(
+            grafo::Shape::rect(
+                [(0.0, 0.0), (CANVAS_WIDTH as f32, CANVAS_HEIGHT as f32)],
+                grafo::Stroke::default(),
+            ),
+            None,
+            None,
+            grafo::ShapeDrawCommandOptions::new().color(grafo::Color::TRANSPARENT),
+        )
+        .unwrap();
+
+    let source_rects = [
+        (
+            [(10.0, 10.0), (40.0, 40.0)],
+            grafo::TextureSourceRect::Texels([(0, 0), (2, 2)]),
+        ),
+        (
+            [(60.0, 10.0), (90.0, 40.0)],
+            grafo::TextureSourceRect::Uv([(0.5, 0.5), (1.0, 1.0)]),
+        ),
+    ];
+    for (rect, source_rect) in source_rects {
+        renderer
+            .add_shape(
+                grafo::Shape::rect(rect, grafo::Stroke::default()),
+                Some(canvas_root_id),
+                None,
+                grafo::ShapeDrawCommandOptions::new()
+                    .background_texture(
+                        grafo::ShapeTextureOptions::new(atlas_texture_id).source_rect(source_rect),
+                    )
+                    .color(grafo::Color::WHITE),
+            )
+            .unwrap();
+    }
+
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    let expectations = vec![
+        grafo_test_scenes::PixelExpectation::opaque(25, 25, 255, 0, 0, "texel_rect_red_quadrant"),
+        grafo_test_scenes::PixelExpectation::opaque(
+            75,
+            25,
+            255,
+            255,
+            255,
+            "uv_rect_white_quadrant",
+        ),
+    ];
+
+    assert_pixels_match(&pixel_buffer, &expectations);
+}
+
+fn solid_rgba(dimensions: (u32, u32), rgba: [u8; 4]) -> Vec<u8> {
+    rgba.repeat((dimensions.0 * dimensions.1) as usize)
+}
+
+fn add_transparent_canvas_root(renderer: &mut grafo::Renderer<'static>) -> usize {
+    renderer
+        .add_shape(
+            grafo::Shape::rect(
+                [(0.0, 0.0), (CANVAS_WIDTH as f32, CANVAS_HEIGHT as f32)],
+                grafo::Stroke::default(),
+            ),
+            None,
+            None,
+            grafo::ShapeDrawCommandOptions::new().color(grafo::Color::TRANSPARENT),
+        )
+        .unwrap()
+}
+
+fn add_textured_rect(
+    renderer: &mut grafo::Renderer<'static>,
+    parent_id: usize,
+    rect: [(f32, f32); 2],
+    texture_options: grafo::ShapeTextureOptions,
+) {
+    renderer
+        .add_shape(
+            grafo::Shape::rect(rect, grafo::Stroke::default()),
+            Some(parent_id),
+            None,
+            grafo::ShapeDrawCommandOptions::new()
+                .background_texture(texture_options)
+                .color(grafo::Color::WHITE),
+        )
+        .unwrap();
+}
+
+/// Atlas entries share one page, and sampling past an entry's edge never reaches its neighbors.
+#[test]
+fn atlas_entries_share_a_page_and_clamp_to_their_own_texels() {
+    let Some(mut renderer) = create_headless_renderer() else {
+        return;
+    };
+
+    let texture_manager = renderer.texture_manager().clone();
+    let (red_id, framed_yellow_id, green_id, blue_id) = (9_201u64, 9_202u64, 9_203u64, 9_204u64);
+    texture_manager
+        .insert_into_atlas(red_id, (8, 8), &solid_rgba((8, 8), [255, 0, 0, 255]))
+        .unwrap();
+    let framed_yellow_6x6 = (0..6u32)
+        .flat_map(|y| {
+            (0..6u32).flat_map(move |x| {
+                if x == 0 || x == 5 || y == 0 || y == 5 {
+                    [0u8, 0, 0, 0]
                 } else {
-                    [0u8, 255u8, 0u8, 255u8]
+                    [255, 255, 0, 255]
                 }
             })
         })
         .collect::<Vec<_>>();
+    texture_manager
+        .insert_into_atlas(framed_yellow_id, (6, 6), &framed_yellow_6x6)
+        .unwrap();
+    texture_manager
+        .insert_into_atlas(green_id, (8, 8), &solid_rgba((8, 8), [0, 255, 0, 255]))
+        .unwrap();
+    texture_manager
+        .insert_into_atlas(blue_id, (8, 8), &solid_rgba((8, 8), [0, 0, 255, 255]))
+        .unwrap();
+    assert_eq!(texture_manager.atlas_page_count(), 1);
+    assert!(texture_manager.is_texture_loaded(framed_yellow_id));
+
+    let canvas_root_id = add_transparent_canvas_root(&mut renderer);
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(10.0, 10.0), (40.0, 40.0)],
+        grafo::ShapeTextureOptions::new(red_id),
+    );
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(60.0, 10.0), (90.0, 40.0)],
+        grafo::ShapeTextureOptions::new(blue_id),
+    );
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(10.0, 60.0), (70.0, 120.0)],
+        grafo::ShapeTextureOptions::new(framed_yellow_id)
+            .fit_mode(grafo::ShapeTextureFitMode::OriginalSize),
+    );
+
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    let expectations = vec![
+        grafo_test_scenes::PixelExpectation::opaque(25, 25, 255, 0, 0, "red_atlas_entry"),
+        grafo_test_scenes::PixelExpectation::opaque(75, 25, 0, 0, 255, "blue_atlas_entry"),
+        grafo_test_scenes::PixelExpectation::opaque(13, 63, 255, 255, 0, "framed_entry_texels"),
+        // Unclamped, this would sample the green entry packed right of the framed one.
+        grafo_test_scenes::PixelExpectation::opaque(
+            20,
+            63,
+            255,
+            255,
+            255,
+            "past_framed_entry_clamps_to_its_transparent_edge",
+        ),
+    ];
+
+    assert_pixels_match(&pixel_buffer, &expectations);
+}
+
+/// Reinserting an atlas entry keeps its sampler, and loading data rewrites it within its page.
+#[test]
+fn atlas_entries_keep_samplers_and_accept_loaded_data() {
+    let Some(mut renderer) = create_headless_renderer() else {
+        return;
+    };
+
+    let texture_manager = renderer.texture_manager().clone();
+    let entry_id = 9_211u64;
+    texture_manager.set_texture_sampler(entry_id, grafo::SamplerOptions::nearest());
+    texture_manager
+        .insert_into_atlas(entry_id, (8, 8), &solid_rgba((8, 8), [255, 0, 0, 255]))
+        .unwrap();
+    texture_manager
+        .insert_into_atlas(entry_id, (8, 8), &solid_rgba((8, 8), [0, 255, 0, 255]))
+        .unwrap();
+    assert_eq!(
+        texture_manager.texture_sampler(entry_id),
+        grafo::SamplerOptions::nearest()
+    );
+
+    texture_manager
+        .load_data_into_texture(entry_id, (8, 8), &solid_rgba((8, 8), [0, 0, 255, 255]))
+        .unwrap();
+    assert!(matches!(
+        texture_manager.load_data_into_texture(entry_id, (8, 8), &[0; 4]),
+        Err(grafo::TextureManagerError::DataSizeMismatch { .. })
+    ));
+    assert!(matches!(
+        texture_manager.load_data_into_texture(entry_id, (9, 8), &solid_rgba((9, 8), [0; 4])),
+        Err(grafo::TextureManagerError::RegionOutOfBounds { .. })
+    ));
+
+    let canvas_root_id = add_transparent_canvas_root(&mut renderer);
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(10.0, 10.0), (40.0, 40.0)],
+        grafo::ShapeTextureOptions::new(entry_id),
+    );
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+    assert_pixels_match(
+        &pixel_buffer,
+        &[grafo_test_scenes::PixelExpectation::opaque(
+            25,
+            25,
+            0,
+            0,
+            255,
+            "loaded_atlas_entry",
+        )],
+    );
+}
+
+/// An entry that only fits after defragmenting a page moves the page's live entries.
+#[test]
+fn atlas_repacks_fragmented_page_to_fit_new_entry() {
+    let Some(mut renderer) = create_headless_renderer() else {
+        return;
+    };
+
+    let texture_manager = renderer.texture_manager().clone();
+    let shelf_entry_size = (1000, 600);
+    let kept_entries = [
+        (9_301u64, [255u8, 0, 0, 255]),
+        (9_302, [0, 255, 0, 255]),
+        (9_305, [0, 0, 255, 255]),
+        (9_306, [255, 255, 0, 255]),
+    ];
+    let removed_entries = [9_303u64, 9_304];
+    for texture_id in 9_301u64..=9_306 {
+        let rgba = kept_entries
+            .iter()
+            .find(|(kept_id, _)| *kept_id == texture_id)
+            .map_or([0, 0, 0, 255], |(_, rgba)| *rgba);
+        texture_manager
+            .insert_into_atlas(
+                texture_id,
+                shelf_entry_size,
+                &solid_rgba(shelf_entry_size, rgba),
+            )
+            .unwrap();
+    }
+    for texture_id in removed_entries {
+        texture_manager.remove_texture(texture_id);
+    }
+
+    // Taller than every remaining shelf, and the page has no fresh rows left.
+    let tall_entry_id = 9_307u64;
+    texture_manager
+        .insert_into_atlas(
+            tall_entry_id,
+            (1000, 700),
+            &solid_rgba((1000, 700), [255, 0, 255, 255]),
+        )
+        .unwrap();
+    assert_eq!(texture_manager.atlas_page_count(), 1);
+    assert!(!texture_manager.is_texture_loaded(removed_entries[0]));
+
+    let canvas_root_id = add_transparent_canvas_root(&mut renderer);
+    let entries_to_draw = kept_entries
+        .into_iter()
+        .chain(std::iter::once((tall_entry_id, [255, 0, 255, 255])));
+    let mut expectations = Vec::new();
+    for (slot, (texture_id, [r, g, b, _])) in entries_to_draw.enumerate() {
+        let left = 10.0 + slot as f32 * 40.0;
+        add_textured_rect(
+            &mut renderer,
+            canvas_root_id,
+            [(left, 10.0), (left + 30.0, 40.0)],
+            grafo::ShapeTextureOptions::new(texture_id),
+        );
+        expectations.push(grafo_test_scenes::PixelExpectation::opaque(
+            left as u32 + 15,
+            25,
+            r,
+            g,
+            b,
+            "repacked_atlas_entry",
+        ));
+    }
+
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    assert_pixels_match(&pixel_buffer, &expectations);
+}
+
+fn rgba_texture(dimensions: (u32, u32), texel: impl Fn(u32, u32) -> [u8; 4]) -> Vec<u8> {
+    (0..dimensions.1)
+        .flat_map(|y| (0..dimensions.0).map(move |x| (x, y)))
+        .flat_map(|(x, y)| texel(x, y))
+        .collect()
+}
+
+/// Cover crops the texture to the shape's aspect; Contain letterboxes it over the fill.
+#[test]
+fn cover_and_contain_fit_modes_preserve_texture_aspect() {
+    let Some(mut renderer) = create_headless_renderer() else {
+        return;
+    };
+
+    let red_blue_halves_id = 9_401u64;
     renderer.texture_manager().allocate_texture_with_data(
-        green_texture_id,
-        (20, 20),
-        &green_texture_with_transparent_border_20x20,
+        red_blue_halves_id,
+        (8, 4),
+        &rgba_texture((8, 4), |x, _| {
+            if x < 4 {
+                [255, 0, 0, 255]
+            } else {
+                [0, 0, 255, 255]
+            }
+        }),
     );
 
-    let shape = grafo::Shape::rect([(10.0, 10.0), (70.0, 70.0)], grafo::Stroke::default());
+    let canvas_root_id = add_transparent_canvas_root(&mut renderer);
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(10.0, 10.0), (50.0, 50.0)],
+        grafo::ShapeTextureOptions::new(red_blue_halves_id).fit_mode(
+            grafo::ShapeTextureFitMode::Cover {
+                alignment: grafo::TextureAlignment::Left,
+            },
+        ),
+    );
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(60.0, 10.0), (100.0, 50.0)],
+        grafo::ShapeTextureOptions::new(red_blue_halves_id).fit_mode(
+            grafo::ShapeTextureFitMode::Contain {
+                alignment: grafo::TextureAlignment::Top,
+            },
+        ),
+    );
+
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    let expectations = vec![
+        // Cover scales the texture to the shape's height, so only its red half is visible.
+        grafo_test_scenes::PixelExpectation::opaque(40, 30, 255, 0, 0, "cover_left_cropped"),
+        grafo_test_scenes::PixelExpectation::opaque(70, 20, 255, 0, 0, "contain_red_half"),
+        grafo_test_scenes::PixelExpectation::opaque(90, 20, 0, 0, 255, "contain_blue_half"),
+        grafo_test_scenes::PixelExpectation::opaque(80, 40, 255, 255, 255, "contain_letterbox"),
+    ];
+
+    assert_pixels_match(&pixel_buffer, &expectations);
+}
+
+/// Tiles repeat inside their atlas entry at texel size, with fill-colored spacing between them.
+#[test]
+fn tile_fit_mode_repeats_atlas_entry_with_spacing() {
+    let Some(mut renderer) = create_headless_renderer() else {
+        return;
+    };
+
+    let texture_manager = renderer.texture_manager().clone();
+    let (green_tile_id, red_neighbor_id) = (9_411u64, 9_412u64);
+    texture_manager
+        .insert_into_atlas(green_tile_id, (4, 4), &solid_rgba((4, 4), [0, 255, 0, 255]))
+        .unwrap();
+    texture_manager
+        .insert_into_atlas(
+            red_neighbor_id,
+            (4, 4),
+            &solid_rgba((4, 4), [255, 0, 0, 255]),
+        )
+        .unwrap();
+
+    let canvas_root_id = add_transparent_canvas_root(&mut renderer);
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(10.0, 10.0), (50.0, 30.0)],
+        grafo::ShapeTextureOptions::new(green_tile_id)
+            .fit_mode(grafo::ShapeTextureFitMode::Tile { spacing: (4, 0) }),
+    );
+
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    let expectations = vec![
+        grafo_test_scenes::PixelExpectation::opaque(12, 20, 0, 255, 0, "first_tile"),
+        grafo_test_scenes::PixelExpectation::opaque(16, 20, 255, 255, 255, "first_gap"),
+        grafo_test_scenes::PixelExpectation::opaque(20, 20, 0, 255, 0, "second_tile"),
+        grafo_test_scenes::PixelExpectation::opaque(44, 27, 0, 255, 0, "last_tile_next_row"),
+        grafo_test_scenes::PixelExpectation::opaque(48, 20, 255, 255, 255, "last_gap"),
+    ];
+
+    assert_pixels_match(&pixel_buffer, &expectations);
+}
+
+/// Nine-slice keeps 1:1 corners and stretches the edges and center of an atlas entry.
+#[test]
+fn nine_slice_fit_mode_keeps_corners_unscaled() {
+    let Some(mut renderer) = create_headless_renderer() else {
+        return;
+    };
+
+    let texture_manager = renderer.texture_manager().clone();
+    let panel_id = 9_421u64;
+    texture_manager
+        .insert_into_atlas(
+            panel_id,
+            (12, 12),
+            &rgba_texture((12, 12), |x, y| {
+                let on_vertical_edge = !(4..8).contains(&x);
+                let on_horizontal_edge = !(4..8).contains(&y);
+                match (on_vertical_edge, on_horizontal_edge) {
+                    (true, true) => [255, 0, 0, 255],
+                    (false, false) => [0, 255, 0, 255],
+                    _ => [0, 0, 255, 255],
+                }
+            }),
+        )
+        .unwrap();
+    texture_manager
+        .insert_into_atlas(9_422, (4, 4), &solid_rgba((4, 4), [255, 255, 0, 255]))
+        .unwrap();
+
+    let canvas_root_id = add_transparent_canvas_root(&mut renderer);
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(10.0, 10.0), (110.0, 50.0)],
+        grafo::ShapeTextureOptions::new(panel_id).fit_mode(grafo::ShapeTextureFitMode::NineSlice {
+            insets: grafo::NineSliceInsets::uniform(4),
+        }),
+    );
+
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    let expectations = vec![
+        grafo_test_scenes::PixelExpectation::opaque(12, 12, 255, 0, 0, "top_left_corner"),
+        grafo_test_scenes::PixelExpectation::opaque(107, 47, 255, 0, 0, "bottom_right_corner"),
+        grafo_test_scenes::PixelExpectation::opaque(60, 12, 0, 0, 255, "stretched_top_edge"),
+        // Stretching the whole texture would put the left edge here instead of the center.
+        grafo_test_scenes::PixelExpectation::opaque(40, 30, 0, 255, 0, "stretched_center"),
+    ];
+
+    assert_pixels_match(&pixel_buffer, &expectations);
+}
+
+/// Nearest filtering keeps magnified texel edges crisp; a per-draw sampler overrides it.
+#[test]
+fn nearest_sampler_keeps_magnified_texels_crisp() {
+    let Some(mut renderer) = create_headless_renderer() else {
+        return;
+    };
+
+    let red_blue_id = 9_421u64;
+    let texture_manager = renderer.texture_manager().clone();
+    texture_manager.allocate_texture_with_data(
+        red_blue_id,
+        (2, 1),
+        &[255, 0, 0, 255, 0, 0, 255, 255],
+    );
+    texture_manager.set_texture_sampler(red_blue_id, grafo::SamplerOptions::nearest());
+
+    let canvas_root_id = add_transparent_canvas_root(&mut renderer);
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(10.0, 10.0), (50.0, 30.0)],
+        grafo::ShapeTextureOptions::new(red_blue_id),
+    );
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(10.0, 40.0), (50.0, 60.0)],
+        grafo::ShapeTextureOptions::new(red_blue_id).sampler(grafo::SamplerOptions::new()),
+    );
+
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    let expectations = vec![
+        grafo_test_scenes::PixelExpectation::opaque(29, 20, 255, 0, 0, "nearest_left_of_edge"),
+        grafo_test_scenes::PixelExpectation::opaque(30, 20, 0, 0, 255, "nearest_right_of_edge"),
+    ];
+    assert_pixels_match(&pixel_buffer, &expectations);
+
+    let [red, _, blue, _] = read_pixel_rgba(&pixel_buffer, CANVAS_WIDTH, 29, 50);
+    assert!(
+        red > 64 && blue > 64,
+        "linear override should blend across the texel edge, got red {red} blue {blue}"
+    );
+}
+
+/// Repeat wraps a standalone texture; MirrorRepeat wraps an atlas entry within its own texels.
+#[test]
+fn address_modes_wrap_textures_and_atlas_entries() {
+    let Some(mut renderer) = create_headless_renderer() else {
+        return;
+    };
+
+    let green_blue_columns = rgba_texture((4, 4), |x, _| {
+        if x < 2 {
+            [0, 255, 0, 255]
+        } else {
+            [0, 0, 255, 255]
+        }
+    });
+    let texture_manager = renderer.texture_manager().clone();
+    let (standalone_id, atlas_entry_id, red_neighbor_id) = (9_431u64, 9_432u64, 9_433u64);
+    texture_manager.allocate_texture_with_data(standalone_id, (4, 4), &green_blue_columns);
+    texture_manager.set_texture_sampler(
+        standalone_id,
+        grafo::SamplerOptions::nearest().address_mode(grafo::TextureAddressMode::Repeat),
+    );
+    texture_manager
+        .insert_into_atlas(atlas_entry_id, (4, 4), &green_blue_columns)
+        .unwrap();
+    texture_manager
+        .insert_into_atlas(
+            red_neighbor_id,
+            (4, 4),
+            &solid_rgba((4, 4), [255, 0, 0, 255]),
+        )
+        .unwrap();
+
+    let canvas_root_id = add_transparent_canvas_root(&mut renderer);
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(10.0, 10.0), (26.0, 26.0)],
+        grafo::ShapeTextureOptions::new(standalone_id)
+            .fit_mode(grafo::ShapeTextureFitMode::OriginalSize),
+    );
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(10.0, 40.0), (26.0, 56.0)],
+        grafo::ShapeTextureOptions::new(atlas_entry_id)
+            .fit_mode(grafo::ShapeTextureFitMode::OriginalSize)
+            .sampler(
+                grafo::SamplerOptions::nearest()
+                    .address_mode(grafo::TextureAddressMode::MirrorRepeat),
+            ),
+    );
+
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    let expectations = vec![
+        grafo_test_scenes::PixelExpectation::opaque(15, 20, 0, 255, 0, "repeat_second_copy"),
+        grafo_test_scenes::PixelExpectation::opaque(17, 20, 0, 0, 255, "repeat_second_copy_end"),
+        grafo_test_scenes::PixelExpectation::opaque(24, 24, 0, 0, 255, "repeat_last_copy"),
+        // The second copy is flipped, so it starts with the blue columns.
+        grafo_test_scenes::PixelExpectation::opaque(15, 50, 0, 0, 255, "mirror_second_copy"),
+        grafo_test_scenes::PixelExpectation::opaque(17, 50, 0, 255, 0, "mirror_second_copy_end"),
+        grafo_test_scenes::PixelExpectation::opaque(19, 50, 0, 255, 0, "mirror_third_copy"),
+        grafo_test_scenes::PixelExpectation::opaque(23, 55, 0, 0, 255, "mirror_last_copy"),
+    ];
+
+    assert_pixels_match(&pixel_buffer, &expectations);
+}
+
+/// A minified checkerboard averages to gray through its mip chain instead of aliasing.
+#[test]
+fn mipmapped_texture_averages_when_minified() {
+    let Some(mut renderer) = create_headless_renderer() else {
+        return;
+    };
+
+    let checkerboard = rgba_texture((64, 64), |x, y| {
+        if (x + y) % 2 == 0 {
+            [0, 0, 0, 255]
+        } else {
+            [255, 255, 255, 255]
+        }
+    });
+    let texture_manager = renderer.texture_manager().clone();
+    let (mipmapped_id, single_level_id) = (9_441u64, 9_442u64);
+    texture_manager.allocate_texture_with_mipmaps(mipmapped_id, (64, 64), &checkerboard);
+    texture_manager.allocate_texture_with_data(single_level_id, (64, 64), &checkerboard);
+
+    let canvas_root_id = add_transparent_canvas_root(&mut renderer);
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(10.0, 10.0), (20.0, 20.0)],
+        grafo::ShapeTextureOptions::new(mipmapped_id),
+    );
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(30.0, 10.0), (40.0, 20.0)],
+        grafo::ShapeTextureOptions::new(single_level_id),
+    );
+
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    // Half black, half white in linear space is sRGB 188.
+    let expectations = [(12, 12), (15, 13), (17, 17), (13, 16)]
+        .into_iter()
+        .map(|(x, y)| {
+            grafo_test_scenes::PixelExpectation::opaque_approx(x, y, 188, 188, 188, 8, "mip_gray")
+        })
+        .collect::<Vec<_>>();
+    assert_pixels_match(&pixel_buffer, &expectations);
+
+    // Without mips, neighboring pixels land on different mixes of black and white.
+    let single_level_greens = (32..38)
+        .map(|x| read_pixel_rgba(&pixel_buffer, CANVAS_WIDTH, x, 13)[1])
+        .collect::<Vec<_>>();
+    let spread =
+        single_level_greens.iter().max().unwrap() - single_level_greens.iter().min().unwrap();
+    assert!(
+        spread > 40,
+        "single-level texture should alias when minified, got {single_level_greens:?}"
+    );
+}
+
+/// Reloading data into a mipmapped texture regenerates its smaller levels.
+#[test]
+fn loading_data_regenerates_mip_chain() {
+    let Some(mut renderer) = create_headless_renderer() else {
+        return;
+    };
+
+    let texture_id = 9_443u64;
+    let texture_manager = renderer.texture_manager().clone();
+    texture_manager.allocate_texture_with_mipmaps(
+        texture_id,
+        (64, 64),
+        &solid_rgba((64, 64), [255, 0, 0, 255]),
+    );
+    texture_manager
+        .load_data_into_texture(
+            texture_id,
+            (64, 64),
+            &solid_rgba((64, 64), [0, 0, 255, 255]),
+        )
+        .unwrap();
+
+    let canvas_root_id = add_transparent_canvas_root(&mut renderer);
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(10.0, 10.0), (18.0, 18.0)],
+        grafo::ShapeTextureOptions::new(texture_id),
+    );
+
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    let expectations = vec![grafo_test_scenes::PixelExpectation::opaque(
+        14,
+        14,
+        0,
+        0,
+        255,
+        "minified_reloaded_texture",
+    )];
+    assert_pixels_match(&pixel_buffer, &expectations);
+}
+
+/// R8 masks tint the shape's fill by their coverage instead of drawing colors of their own.
+#[test]
+fn r8_mask_texture_tints_fill_by_coverage() {
+    let Some(mut renderer) = create_headless_renderer() else {
+        return;
+    };
+
+    let mask_id = 9_451u64;
+    let texture_manager = renderer.texture_manager().clone();
+    texture_manager.allocate_texture_with_options(
+        mask_id,
+        (4, 1),
+        grafo::TextureOptions::new().format(grafo::TextureFormat::R8Mask),
+    );
+    texture_manager
+        .load_data_into_texture(mask_id, (4, 1), &[255, 255, 128, 0])
+        .unwrap();
+    texture_manager.set_texture_sampler(mask_id, grafo::SamplerOptions::nearest());
+
+    let canvas_root_id = add_transparent_canvas_root(&mut renderer);
+    renderer
+        .add_shape(
+            grafo::Shape::rect([(10.0, 10.0), (50.0, 30.0)], grafo::Stroke::default()),
+            Some(canvas_root_id),
+            None,
+            grafo::ShapeDrawCommandOptions::new()
+                .background_texture(grafo::ShapeTextureOptions::new(mask_id))
+                .color(grafo::Color::rgb(255, 0, 0)),
+        )
+        .unwrap();
+
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    let expectations = vec![
+        grafo_test_scenes::PixelExpectation::opaque(15, 20, 255, 0, 0, "full_coverage"),
+        // Premultiplied linear half red reads back as sRGB 188 at half alpha.
+        grafo_test_scenes::PixelExpectation::new(35, 20, 188, 0, 0, 128, "half_coverage")
+            .with_tolerance(6),
+        grafo_test_scenes::PixelExpectation::new(45, 20, 0, 0, 0, 0, "no_coverage"),
+    ];
+
+    assert_pixels_match(&pixel_buffer, &expectations);
+}
+
+/// BGRA8 and RGBA16F uploads sample as the colors they encode, without CPU conversion.
+#[test]
+fn bgra8_and_rgba16f_textures_sample_their_encoded_colors() {
+    let Some(mut renderer) = create_headless_renderer() else {
+        return;
+    };
+
+    let (bgra_id, hdr_id) = (9_452u64, 9_453u64);
+    let texture_manager = renderer.texture_manager().clone();
+    texture_manager.allocate_texture_with_options(
+        bgra_id,
+        (2, 2),
+        grafo::TextureOptions::new().format(grafo::TextureFormat::Bgra8Srgb),
+    );
+    // Blue in BGRA byte order.
+    texture_manager
+        .load_data_into_texture(bgra_id, (2, 2), &solid_rgba((2, 2), [255, 0, 0, 255]))
+        .unwrap();
+    let wrong_size = texture_manager.load_data_into_texture(bgra_id, (2, 2), &[0; 4]);
+    assert!(matches!(
+        wrong_size,
+        Err(grafo::TextureManagerError::DataSizeMismatch {
+            expected: 16,
+            actual: 4,
+            ..
+        })
+    ));
+
+    texture_manager.allocate_texture_with_options(
+        hdr_id,
+        (2, 2),
+        grafo::TextureOptions::new().format(grafo::TextureFormat::Rgba16Float),
+    );
+    // Linear half-intensity green as half floats: 0.0, 0.5, 0.0, 1.0.
+    let half_green_texel = [0x0000u16, 0x3800, 0x0000, 0x3C00]
+        .iter()
+        .flat_map(|half| half.to_le_bytes())
+        .collect::<Vec<_>>();
+    texture_manager
+        .load_data_into_texture(hdr_id, (2, 2), &half_green_texel.repeat(4))
+        .unwrap();
+
+    let canvas_root_id = add_transparent_canvas_root(&mut renderer);
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(10.0, 10.0), (30.0, 30.0)],
+        grafo::ShapeTextureOptions::new(bgra_id),
+    );
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(40.0, 10.0), (60.0, 30.0)],
+        grafo::ShapeTextureOptions::new(hdr_id),
+    );
+
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    let expectations = vec![
+        grafo_test_scenes::PixelExpectation::opaque(20, 20, 0, 0, 255, "bgra_blue"),
+        // Linear 0.5 encodes to sRGB 188.
+        grafo_test_scenes::PixelExpectation::opaque_approx(50, 20, 0, 188, 0, 3, "hdr_green"),
+    ];
+
+    assert_pixels_match(&pixel_buffer, &expectations);
+}
+
+/// Region updates rewrite only their texels, read strided rows, and stay inside atlas entries.
+#[test]
+fn update_texture_region_rewrites_only_the_region() {
+    let Some(mut renderer) = create_headless_renderer() else {
+        return;
+    };
+
+    let texture_manager = renderer.texture_manager().clone();
+    let (standalone_id, atlas_entry_id, red_neighbor_id) = (9_461u64, 9_462u64, 9_463u64);
+    texture_manager.allocate_texture_with_data(
+        standalone_id,
+        (4, 4),
+        &solid_rgba((4, 4), [255, 0, 0, 255]),
+    );
+    texture_manager
+        .insert_into_atlas(
+            atlas_entry_id,
+            (4, 4),
+            &solid_rgba((4, 4), [0, 255, 0, 255]),
+        )
+        .unwrap();
+    texture_manager
+        .insert_into_atlas(
+            red_neighbor_id,
+            (4, 4),
+            &solid_rgba((4, 4), [255, 0, 0, 255]),
+        )
+        .unwrap();
+    for texture_id in [standalone_id, atlas_entry_id] {
+        texture_manager.set_texture_sampler(texture_id, grafo::SamplerOptions::nearest());
+    }
+
+    // A 2×2 blue block read out of a 3-texel-wide source image.
+    let blue = [0u8, 0, 255, 255];
+    let source_rows = [blue, blue, [0; 4]].concat().repeat(2);
+    texture_manager
+        .update_texture_region(standalone_id, (2, 0), (2, 2), &source_rows, 12)
+        .unwrap();
+    texture_manager
+        .update_texture_region(atlas_entry_id, (0, 0), (1, 1), &blue, 4)
+        .unwrap();
+
+    assert!(matches!(
+        texture_manager.update_texture_region(atlas_entry_id, (3, 3), (2, 2), &source_rows, 12),
+        Err(grafo::TextureManagerError::RegionOutOfBounds { .. })
+    ));
+    assert!(matches!(
+        texture_manager.update_texture_region(standalone_id, (0, 0), (2, 2), &source_rows, 4),
+        Err(grafo::TextureManagerError::BytesPerRowTooSmall { minimum: 8, .. })
+    ));
+    assert!(matches!(
+        texture_manager.update_texture_region(standalone_id, (0, 0), (2, 2), &blue, 8),
+        Err(grafo::TextureManagerError::DataSizeMismatch { expected: 16, .. })
+    ));
+
+    let canvas_root_id = add_transparent_canvas_root(&mut renderer);
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(10.0, 10.0), (50.0, 50.0)],
+        grafo::ShapeTextureOptions::new(standalone_id),
+    );
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(60.0, 10.0), (100.0, 50.0)],
+        grafo::ShapeTextureOptions::new(atlas_entry_id),
+    );
+
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    let expectations = vec![
+        grafo_test_scenes::PixelExpectation::opaque(15, 15, 255, 0, 0, "untouched_left"),
+        grafo_test_scenes::PixelExpectation::opaque(35, 15, 0, 0, 255, "updated_top_right"),
+        grafo_test_scenes::PixelExpectation::opaque(45, 25, 0, 0, 255, "updated_second_row"),
+        grafo_test_scenes::PixelExpectation::opaque(45, 35, 255, 0, 0, "untouched_below"),
+        grafo_test_scenes::PixelExpectation::opaque(65, 15, 0, 0, 255, "atlas_updated_texel"),
+        grafo_test_scenes::PixelExpectation::opaque(75, 15, 0, 255, 0, "atlas_untouched"),
+        grafo_test_scenes::PixelExpectation::opaque(95, 45, 0, 255, 0, "atlas_corner"),
+    ];
+
+    assert_pixels_match(&pixel_buffer, &expectations);
+}
+
+#[test]
+fn yuv_frames_convert_to_rgb_in_the_shader() {
+    let Some(mut renderer) = create_headless_renderer() else {
+        return;
+    };
+
+    let texture_manager = renderer.texture_manager().clone();
+    let (i420_id, nv12_id, rgba_id) = (9_471u64, 9_472u64, 9_473u64);
+    texture_manager.allocate_yuv_texture(
+        i420_id,
+        (4, 4),
+        grafo::YuvOptions::new(grafo::YuvFormat::I420),
+    );
+    texture_manager.allocate_yuv_texture(
+        nv12_id,
+        (4, 4),
+        grafo::YuvOptions::new(grafo::YuvFormat::Nv12)
+            .matrix(grafo::YuvColorMatrix::Bt709)
+            .range(grafo::YuvRange::Full),
+    );
+    texture_manager.allocate_texture_with_data(rgba_id, (1, 1), &[0, 0, 0, 255]);
+
+    // Limited-range BT.601 red, with every row padded past the plane width.
+    let i420_luma = [81u8; 8 * 4];
+    let i420_u = [90u8; 4 * 2];
+    let i420_v = [240u8; 4 * 2];
+    texture_manager
+        .load_yuv_frame(
+            i420_id,
+            &[
+                grafo::YuvPlane::new(&i420_luma, 8),
+                grafo::YuvPlane::new(&i420_u, 4),
+                grafo::YuvPlane::new(&i420_v, 4),
+            ],
+        )
+        .unwrap();
+
+    // Full-range BT.709 orange: Y = 100, U = 60, V = 200.
+    let nv12_luma = [100u8; 4 * 4];
+    let nv12_uv = [60u8, 200].repeat(2 * 2);
+    texture_manager
+        .load_yuv_frame(
+            nv12_id,
+            &[
+                grafo::YuvPlane::new(&nv12_luma, 4),
+                grafo::YuvPlane::new(&nv12_uv, 4),
+            ],
+        )
+        .unwrap();
+
+    assert!(matches!(
+        texture_manager.load_yuv_frame(i420_id, &[grafo::YuvPlane::new(&i420_luma, 8)]),
+        Err(grafo::TextureManagerError::YuvPlaneCountMismatch {
+            expected: 3,
+            actual: 1,
+            ..
+        })
+    ));
+    assert!(matches!(
+        texture_manager.load_yuv_frame(rgba_id, &[]),
+        Err(grafo::TextureManagerError::NotYuvTexture(9_473))
+    ));
+
+    let canvas_root_id = add_transparent_canvas_root(&mut renderer);
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(10.0, 10.0), (50.0, 50.0)],
+        grafo::ShapeTextureOptions::new(i420_id),
+    );
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(60.0, 10.0), (100.0, 50.0)],
+        grafo::ShapeTextureOptions::new(nv12_id),
+    );
+
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    // The bottom probes would pick up chroma bytes if the luma plane were not mapped to the
+    // top of the packed texture.
+    let expectations = vec![
+        grafo_test_scenes::PixelExpectation::opaque_approx(15, 15, 254, 0, 0, 3, "i420_top"),
+        grafo_test_scenes::PixelExpectation::opaque_approx(45, 47, 254, 0, 0, 3, "i420_bottom"),
+        grafo_test_scenes::PixelExpectation::opaque_approx(65, 15, 213, 79, 0, 3, "nv12_top"),
+        grafo_test_scenes::PixelExpectation::opaque_approx(95, 47, 213, 79, 0, 3, "nv12_bottom"),
+    ];
+
+    assert_pixels_match(&pixel_buffer, &expectations);
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn background_image_load_decodes_and_uploads_png() {
+    let Some(mut renderer) = create_headless_renderer() else {
+        return;
+    };
+
+    let mut png = Vec::new();
+    image::RgbaImage::from_pixel(4, 4, image::Rgba([0, 0, 255, 255]))
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .unwrap();
+
+    let texture_manager = renderer.texture_manager().clone();
+    let texture_id = 9_481u64;
+    let dimensions = texture_manager
+        .load_image_in_background(texture_id, png, true)
+        .join()
+        .unwrap()
+        .unwrap();
+    assert_eq!(dimensions, (4, 4));
+    assert!(texture_manager.is_texture_loaded(texture_id));
+
+    let canvas_root_id = add_transparent_canvas_root(&mut renderer);
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(10.0, 10.0), (50.0, 50.0)],
+        grafo::ShapeTextureOptions::new(texture_id),
+    );
+
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    let expectations = vec![grafo_test_scenes::PixelExpectation::opaque(
+        30,
+        30,
+        0,
+        0,
+        255,
+        "decoded_texture",
+    )];
+
+    assert_pixels_match(&pixel_buffer, &expectations);
+}
+
+#[test]
+fn memory_budget_evicts_least_recently_drawn_textures() {
+    let Some(mut renderer) = create_headless_renderer() else {
+        return;
+    };
+
+    let texture_manager = renderer.texture_manager().clone();
+    let (drawn_id, idle_id, other_drawn_id) = (9_491u64, 9_492u64, 9_493u64);
+    for texture_id in [drawn_id, idle_id, other_drawn_id] {
+        texture_manager.allocate_texture_with_data(
+            texture_id,
+            (4, 4),
+            &solid_rgba((4, 4), [0, 0, 255, 255]),
+        );
+    }
+    let evicted = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let evicted_by_callback = evicted.clone();
+    texture_manager.set_eviction_callback(move |texture_id| {
+        evicted_by_callback.lock().unwrap().push(texture_id);
+    });
+    texture_manager.set_memory_budget(Some(128));
+
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    // Textures allocated during a frame count as used in it, so nothing is evicted yet.
+    renderer.render_to_buffer(&mut pixel_buffer);
+    assert_eq!(texture_manager.memory_report().total_bytes, 3 * 64);
+
+    let canvas_root_id = add_transparent_canvas_root(&mut renderer);
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(10.0, 10.0), (50.0, 50.0)],
+        grafo::ShapeTextureOptions::new(drawn_id),
+    );
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(60.0, 10.0), (100.0, 50.0)],
+        grafo::ShapeTextureOptions::new(other_drawn_id),
+    );
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    assert_eq!(*evicted.lock().unwrap(), vec![idle_id]);
+    assert!(!texture_manager.is_texture_loaded(idle_id));
+    let report = texture_manager.memory_report();
+    assert_eq!(report.total_bytes, 128);
+    assert_eq!(report.budget_bytes, Some(128));
+    assert_eq!(
+        report.textures,
+        vec![
+            grafo::TextureMemoryUsage {
+                texture_id: drawn_id,
+                bytes: 64,
+                frames_since_use: 1,
+            },
+            grafo::TextureMemoryUsage {
+                texture_id: other_drawn_id,
+                bytes: 64,
+                frames_since_use: 1,
+            },
+        ]
+    );
+
+    // Textures drawn in the frame are kept even when they alone exceed the budget.
+    texture_manager.set_memory_budget(Some(0));
+    let canvas_root_id = add_transparent_canvas_root(&mut renderer);
+    for (texture_id, left) in [(drawn_id, 10.0), (other_drawn_id, 60.0)] {
+        add_textured_rect(
+            &mut renderer,
+            canvas_root_id,
+            [(left, 10.0), (left + 40.0, 50.0)],
+            grafo::ShapeTextureOptions::new(texture_id),
+        );
+    }
+    renderer.render_to_buffer(&mut pixel_buffer);
+    assert_eq!(texture_manager.memory_report().total_bytes, 128);
+}
+
+#[test]
+fn allocating_with_mismatched_data_panics_before_allocating() {
+    let Some(renderer) = create_headless_renderer() else {
+        return;
+    };
+    let texture_manager = renderer.texture_manager().clone();
+
+    for allocate in [
+        grafo::TextureManager::allocate_texture_with_data,
+        grafo::TextureManager::allocate_texture_with_mipmaps,
+    ] {
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            allocate(&texture_manager, 7, (2, 2), &[0; 8]);
+        }));
+        assert!(result.is_err());
+        assert!(!texture_manager.is_texture_loaded(7));
+    }
+}
+
+/// Usage is recorded when a texture is drawn, so a kept queue keeps its textures, and textures
+/// registered from outside the manager are never evicted.
+#[test]
+fn memory_budget_tracks_drawn_textures_and_spares_external_ones() {
+    let Some(mut renderer) = create_headless_renderer() else {
+        return;
+    };
+
+    let texture_manager = renderer.texture_manager().clone();
+    let (drawn_id, external_id) = (9_494u64, 9_495u64);
+    texture_manager.allocate_texture_with_data(
+        drawn_id,
+        (4, 4),
+        &solid_rgba((4, 4), [0, 0, 255, 255]),
+    );
+    let external_texture =
+        renderer
+            .context()
+            .device()
+            .create_texture(&grafo::wgpu::TextureDescriptor {
+                label: Some("external_texture"),
+                size: grafo::wgpu::Extent3d {
+                    width: 4,
+                    height: 4,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: grafo::wgpu::TextureDimension::D2,
+                format: grafo::wgpu::TextureFormat::Rgba8UnormSrgb,
+                usage: grafo::wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            });
+    texture_manager
+        .insert_wgpu_texture(external_id, external_texture)
+        .unwrap();
+    texture_manager.set_memory_budget(Some(0));
+
+    let canvas_root_id = add_transparent_canvas_root(&mut renderer);
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(10.0, 10.0), (50.0, 50.0)],
+        grafo::ShapeTextureOptions::new(drawn_id),
+    );
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    for _ in 0..3 {
+        renderer.render_to_buffer(&mut pixel_buffer);
+    }
+    assert!(texture_manager.is_texture_loaded(drawn_id));
+    assert!(texture_manager.is_texture_loaded(external_id));
+
+    renderer.clear_draw_queue();
+    renderer.render_to_buffer(&mut pixel_buffer);
+    renderer.render_to_buffer(&mut pixel_buffer);
+    assert!(!texture_manager.is_texture_loaded(drawn_id));
+    assert!(texture_manager.is_texture_loaded(external_id));
+}
+
+#[test]
+fn context_from_existing_wgpu_device_draws_shared_textures() {
+    use grafo::wgpu;
+
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+    let Ok(adapter) = block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+    else {
+        println!("Skipping test: no suitable GPU adapter available.");
+        return;
+    };
+    let Ok((device, queue)) = block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+        required_features: grafo::RendererContext::required_features(),
+        ..Default::default()
+    })) else {
+        println!("Skipping test: the adapter lacks the renderer's required features.");
+        return;
+    };
+
+    // A texture the embedding application rendered or uploaded on its own.
+    let shared_texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("engine_texture"),
+        size: wgpu::Extent3d {
+            width: 2,
+            height: 2,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    queue.write_texture(
+        shared_texture.as_image_copy(),
+        &solid_rgba((2, 2), [0, 255, 0, 255]),
+        wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(8),
+            rows_per_image: None,
+        },
+        shared_texture.size(),
+    );
+    let unsampleable_texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("engine_copy_target"),
+        size: shared_texture.size(),
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        usage: wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+
+    let context = grafo::RendererContext::from_wgpu(instance, adapter, device, queue).unwrap();
+    let mut renderer =
+        grafo::Renderer::try_new_headless_with_context(context, (CANVAS_WIDTH, CANVAS_HEIGHT), 1.0)
+            .unwrap();
+
+    let texture_manager = renderer.texture_manager().clone();
+    let (shared_id, unsampleable_id) = (9_501u64, 9_502u64);
+    texture_manager
+        .insert_wgpu_texture(shared_id, shared_texture)
+        .unwrap();
+    assert!(matches!(
+        texture_manager.insert_wgpu_texture(unsampleable_id, unsampleable_texture),
+        Err(grafo::TextureManagerError::MissingTextureUsage { .. })
+    ));
+    assert!(!texture_manager.is_texture_loaded(unsampleable_id));
+    assert_eq!(
+        texture_manager
+            .wgpu_texture(shared_id)
+            .map(|texture| texture.size()),
+        Some(wgpu::Extent3d {
+            width: 2,
+            height: 2,
+            depth_or_array_layers: 1,
+        })
+    );
+
+    let canvas_root_id = add_transparent_canvas_root(&mut renderer);
+    add_textured_rect(
+        &mut renderer,
+        canvas_root_id,
+        [(10.0, 10.0), (50.0, 50.0)],
+        grafo::ShapeTextureOptions::new(shared_id),
+    );
+
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    let expectations = vec![grafo_test_scenes::PixelExpectation::opaque(
+        30,
+        30,
+        0,
+        255,
+        0,
+        "engine_texture",
+    )];
+
+    assert_pixels_match(&pixel_buffer, &expectations);
+}
+
+/// Creates a texture the embedding application owns, filled with `texel`.
+fn create_engine_target(
+    renderer: &grafo::Renderer<'static>,
+    texel: [u8; 4],
+) -> grafo::wgpu::Texture {
+    use grafo::wgpu;
+
+    let (width, height) = renderer.size();
+    let context = renderer.context();
+    let texture = context.device().create_texture(&wgpu::TextureDescriptor {
+        label: Some("engine_target"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: renderer.target_format(),
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+            | wgpu::TextureUsages::COPY_SRC
+            | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    context.queue().write_texture(
+        texture.as_image_copy(),
+        &solid_rgba((width, height), texel),
+        wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(width * 4),
+            rows_per_image: None,
+        },
+        texture.size(),
+    );
+    texture
+}
+
+/// Copies a 4-byte-per-texel texture back to the CPU, row by row without padding.
+fn read_engine_target(
+    renderer: &grafo::Renderer<'static>,
+    texture: &grafo::wgpu::Texture,
+) -> Vec<u8> {
+    use grafo::wgpu;
+
+    let context = renderer.context();
+    let (width, height) = (texture.width(), texture.height());
+    let padded_bytes_per_row = (width * 4).div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+        * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let buffer = context.device().create_buffer(&wgpu::BufferDescriptor {
+        label: Some("engine_target_readback"),
+        size: (padded_bytes_per_row * height) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    let mut encoder = context
+        .device()
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row),
+                rows_per_image: None,
+            },
+        },
+        texture.size(),
+    );
+    context.queue().submit(std::iter::once(encoder.finish()));
+
+    buffer.slice(..).map_async(wgpu::MapMode::Read, |result| {
+        result.expect("readback buffer maps");
+    });
+    context
+        .device()
+        .poll(wgpu::PollType::Wait)
+        .expect("device poll succeeds");
+    let mapped = buffer.slice(..).get_mapped_range();
+    mapped
+        .chunks(padded_bytes_per_row as usize)
+        .flat_map(|row| &row[..(width * 4) as usize])
+        .copied()
+        .collect()
+}
+
+fn add_blue_square(renderer: &mut grafo::Renderer<'static>) {
+    renderer
+        .add_shape(
+            grafo::Shape::rect([(16.0, 16.0), (48.0, 48.0)], grafo::Stroke::default()),
+            None,
+            None,
+            grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgb(0, 0, 255)),
+        )
+        .unwrap();
+}
+
+/// `render_into` records into the caller's encoder and either keeps or clears the caller's view.
+#[test]
+fn render_into_draws_over_or_clears_caller_texture() {
+    use grafo::wgpu;
+
+    let Some(mut renderer) = create_headless_renderer_with_size_and_scale((64, 64), 1.0) else {
+        return;
+    };
+    assert_eq!(
+        renderer.target_format(),
+        wgpu::TextureFormat::Bgra8UnormSrgb
+    );
+    // Red in BGRA byte order.
+    let target = create_engine_target(&renderer, [0, 0, 255, 255]);
+    let target_view = target.create_view(&wgpu::TextureViewDescriptor::default());
+
+    for (load_op, background) in [
+        (wgpu::LoadOp::Load, [255, 0, 0, 255]),
+        (
+            wgpu::LoadOp::Clear(wgpu::Color {
+                r: 0.0,
+                g: 1.0,
+                b: 0.0,
+                a: 1.0,
+            }),
+            [0, 255, 0, 255],
+        ),
+    ] {
+        add_blue_square(&mut renderer);
+        let mut encoder = renderer
+            .context()
+            .device()
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        renderer.render_into(&mut encoder, &target_view, load_op);
+        renderer
+            .context()
+            .queue()
+            .submit(std::iter::once(encoder.finish()));
+
+        let pixels = read_engine_target(&renderer, &target);
+        assert_eq!(
+            read_pixel_rgba(&pixels, 64, 4, 4),
+            background,
+            "{load_op:?}"
+        );
+        assert_eq!(read_pixel_rgba(&pixels, 64, 32, 32), [0, 0, 255, 255]);
+    }
+}
+
+/// Frames with backdrop effects are drawn offscreen and composited over the loaded view.
+#[test]
+fn render_into_composites_backdrop_effect_frames_over_loaded_target() {
+    use grafo::wgpu;
+
+    let Some(mut renderer) = create_headless_renderer_with_size_and_scale((64, 64), 1.0) else {
+        return;
+    };
+    renderer
+        .load_effect(9_601, &[CACHED_SHAPE_EFFECT_PASSTHROUGH])
+        .expect("to load the backdrop effect");
+    let target = create_engine_target(&renderer, [0, 0, 255, 255]);
+    let target_view = target.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let panel_id = renderer
+        .add_shape(
+            grafo::Shape::rect([(16.0, 16.0), (48.0, 48.0)], grafo::Stroke::default()),
+            None,
+            None,
+            grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgba(0, 0, 255, 128)),
+        )
+        .unwrap();
+    renderer
+        .set_shape_backdrop_effect(panel_id, 9_601, &[], grafo::BackdropEffectConfig::default())
+        .expect("to attach the backdrop effect");
+    let mut encoder = renderer
+        .context()
+        .device()
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    renderer.render_into(&mut encoder, &target_view, wgpu::LoadOp::Load);
+    renderer
+        .context()
+        .queue()
+        .submit(std::iter::once(encoder.finish()));
+
+    let pixels = read_engine_target(&renderer, &target);
+    assert_eq!(read_pixel_rgba(&pixels, 64, 4, 4), [255, 0, 0, 255]);
+    let [red, green, blue, alpha] = read_pixel_rgba(&pixels, 64, 32, 32);
+    assert!(
+        (180..=200).contains(&red) && green < 4 && (180..=200).contains(&blue) && alpha == 255,
+        "half-transparent blue should blend over the loaded red, got {:?}",
+        [red, green, blue, alpha]
+    );
+}
+
+/// Switching the target format recompiles pipelines and keeps readback in the new byte order.
+#[test]
+fn set_target_format_renders_and_reads_back_rgba() {
+    use grafo::wgpu;
+
+    let Some(mut renderer) = create_headless_renderer_with_size_and_scale((64, 64), 1.0) else {
+        return;
+    };
+    assert!(matches!(
+        renderer.set_target_format(wgpu::TextureFormat::R8Unorm),
+        Err(grafo::RenderTargetError::UnsupportedFormat(_))
+    ));
+    renderer
+        .set_target_format(wgpu::TextureFormat::Rgba8UnormSrgb)
+        .unwrap();
+    assert_eq!(
+        renderer.target_format(),
+        wgpu::TextureFormat::Rgba8UnormSrgb
+    );
+
+    add_blue_square(&mut renderer);
+    let mut pixel_buffer = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+    assert_eq!(&pixel_buffer[(32 * 64 + 32) * 4..][..4], &[0, 0, 255, 255]);
+
+    add_blue_square(&mut renderer);
+    let mut argb_pixels = vec![0u32; 64 * 64];
+    renderer.render_to_argb32(&mut argb_pixels);
+    assert_eq!(argb_pixels[32 * 64 + 32], 0xff00_00ff);
+
+    let target = create_engine_target(&renderer, [255, 0, 0, 255]);
+    let target_view = target.create_view(&wgpu::TextureViewDescriptor::default());
+    add_blue_square(&mut renderer);
+    let mut encoder = renderer
+        .context()
+        .device()
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    renderer.render_into(&mut encoder, &target_view, wgpu::LoadOp::Load);
+    renderer
+        .context()
+        .queue()
+        .submit(std::iter::once(encoder.finish()));
+    let pixels = read_engine_target(&renderer, &target);
+    assert_eq!(&pixels[(4 * 64 + 4) * 4..][..4], &[255, 0, 0, 255]);
+    assert_eq!(&pixels[(32 * 64 + 32) * 4..][..4], &[0, 0, 255, 255]);
+}
+
+/// Context configuration picks adapters by name and rejects limits the adapter cannot meet.
+#[test]
+fn context_config_selects_adapter_by_name_and_reports_unsupported_limits() {
+    let default_context = match block_on(grafo::RendererContext::try_new()) {
+        Ok(context) => context,
+        Err(grafo::RendererCreationError::AdapterNotAvailable(_)) => {
+            println!("Skipping test: no suitable GPU adapter available.");
+            return;
+        }
+        Err(error) => panic!("Failed to create renderer context: {error}"),
+    };
+    let adapter_info = default_context.adapter().get_info();
+
+    let named_config = grafo::ContextConfig::new()
+        .backends(adapter_info.backend.into())
+        .adapter_name(adapter_info.name.to_uppercase());
+    let named_context = block_on(grafo::RendererContext::try_new_with_config(&named_config))
+        .expect("to find the default adapter by name");
+    assert_eq!(named_context.adapter().get_info().name, adapter_info.name);
+    let mut renderer = grafo::Renderer::try_new_headless_with_context(named_context, (16, 16), 1.0)
+        .expect("to create a renderer on the named adapter");
+    renderer
+        .add_shape(
+            grafo::Shape::rect([(0.0, 0.0), (16.0, 16.0)], grafo::Stroke::default()),
+            None,
+            None,
+            grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgb(0, 255, 0)),
+        )
+        .unwrap();
+    let mut pixel_buffer = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+    assert_eq!(read_pixel_rgba(&pixel_buffer, 16, 8, 8), [0, 255, 0, 255]);
+
+    assert!(matches!(
+        block_on(grafo::RendererContext::try_new_with_config(
+            &grafo::ContextConfig::new().adapter_name("no adapter is called this")
+        )),
+        Err(grafo::RendererCreationError::AdapterNotFound(name)) if name == "no adapter is called this"
+    ));
+    assert!(matches!(
+        block_on(grafo::RendererContext::try_new_with_config(
+            &grafo::ContextConfig::new()
+                .adapter_name(adapter_info.name.clone())
+                .limits(grafo::wgpu::Limits {
+                    max_texture_dimension_2d: u32::MAX,
+                    ..grafo::wgpu::Limits::default()
+                })
+        )),
+        Err(grafo::RendererCreationError::UnsupportedLimits)
+    ));
+}
+
+/// A subtree rendered into a managed texture excludes its siblings and can be drawn as a
+/// background texture in the same frame's draw queue.
+#[test]
+fn subtree_renders_into_texture_used_as_background() {
+    let Some(mut renderer) = create_headless_renderer_with_size_and_scale((64, 64), 1.0) else {
+        return;
+    };
+    let add_rect = |renderer: &mut grafo::Renderer<'static>,
+                    rect: [(f32, f32); 2],
+                    parent_id: Option<usize>,
+                    color: grafo::Color| {
+        renderer
+            .add_shape(
+                grafo::Shape::rect(rect, grafo::Stroke::default()),
+                parent_id,
+                None,
+                grafo::ShapeDrawCommandOptions::new().color(color),
+            )
+            .unwrap()
+    };
+    let canvas_id = add_rect(
+        &mut renderer,
+        [(0.0, 0.0), (64.0, 64.0)],
+        None,
+        grafo::Color::TRANSPARENT,
+    );
+    let subtree_id = add_rect(
+        &mut renderer,
+        [(0.0, 0.0), (32.0, 32.0)],
+        Some(canvas_id),
+        grafo::Color::rgb(255, 0, 0),
+    );
+    add_rect(
+        &mut renderer,
+        [(16.0, 16.0), (32.0, 32.0)],
+        Some(subtree_id),
+        grafo::Color::rgb(0, 0, 255),
+    );
+    add_rect(
+        &mut renderer,
+        [(0.0, 16.0), (16.0, 32.0)],
+        Some(canvas_id),
+        grafo::Color::rgb(0, 255, 0),
+    );
+
+    let preview_id = 9_701u64;
+    assert!(matches!(
+        renderer.render_subtree_to_texture(9_999, preview_id, (16, 16), 0.5),
+        Err(grafo::RenderToTextureError::DrawCommand(
+            grafo::DrawCommandError::InvalidShapeId(9_999)
+        ))
+    ));
+    // The 32×32 logical subtree at half scale fills the 16×16 texture.
+    renderer
+        .render_subtree_to_texture(subtree_id, preview_id, (16, 16), 0.5)
+        .unwrap();
+    add_textured_rect(
+        &mut renderer,
+        canvas_id,
+        [(32.0, 32.0), (64.0, 64.0)],
+        grafo::ShapeTextureOptions::new(preview_id),
+    );
+
+    let mut pixel_buffer = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+    for (x, y, expected, label) in [
+        (4, 4, [255, 0, 0, 255], "subtree in the scene"),
+        (4, 20, [0, 255, 0, 255], "sibling in the scene"),
+        (20, 20, [0, 0, 255, 255], "child in the scene"),
+        (36, 36, [255, 0, 0, 255], "subtree in the texture"),
+        (36, 60, [255, 0, 0, 255], "sibling left out of the texture"),
+        (60, 60, [0, 0, 255, 255], "child in the texture"),
+    ] {
+        assert_eq!(
+            read_pixel_rgba(&pixel_buffer, 64, x, y),
+            expected,
+            "{label}"
+        );
+    }
+}
+
+/// Queues a red card at `card_x` with a child square, hinting the card as a cached layer.
+/// Geometry ids keep tessellations, and so the layer, stable across queue rebuilds.
+/// Transforms are absolute, so the child moves with the card explicitly.
+fn queue_layer_card(
+    renderer: &mut grafo::Renderer<'static>,
+    card_x: f32,
+    child_options: grafo::ShapeDrawCommandOptions,
+) {
+    renderer.clear_draw_queue();
+    let canvas_id = renderer
+        .add_shape(
+            grafo::Shape::rect([(0.0, 0.0), (64.0, 64.0)], grafo::Stroke::default()),
+            None,
+            Some(9_811),
+            grafo::ShapeDrawCommandOptions::new().color(grafo::Color::TRANSPARENT),
+        )
+        .unwrap();
+    let card_id = renderer
+        .add_shape(
+            grafo::Shape::rect([(0.0, 0.0), (24.0, 24.0)], grafo::Stroke::default()),
+            Some(canvas_id),
+            Some(9_812),
+            grafo::ShapeDrawCommandOptions::new()
+                .color(grafo::Color::rgb(255, 0, 0))
+                .transform(grafo::TransformInstance::translation(card_x, 8.0)),
+        )
+        .unwrap();
+    renderer
+        .add_shape(
+            grafo::Shape::rect([(0.0, 0.0), (12.0, 12.0)], grafo::Stroke::default()),
+            Some(card_id),
+            Some(9_813),
+            child_options.transform(grafo::TransformInstance::translation(card_x + 12.0, 20.0)),
+        )
+        .unwrap();
+    renderer.cache_as_layer(card_id).unwrap();
+}
+
+#[test]
+fn cached_layer_follows_moves_and_content_changes() {
+    let Some(mut renderer) = create_headless_renderer_with_size_and_scale((64, 64), 1.0) else {
+        return;
+    };
+    assert!(matches!(
+        renderer.cache_as_layer(9_999),
+        Err(grafo::DrawCommandError::InvalidShapeId(9_999))
+    ));
+    let blue = || grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgb(0, 0, 255));
+    let mut pixel_buffer = Vec::new();
+    let mut assert_frame = |renderer: &mut grafo::Renderer<'static>,
+                            expectations: &[(u32, u32, [u8; 4])],
+                            frame: &str| {
+        renderer.render_to_buffer(&mut pixel_buffer);
+        for &(x, y, expected) in expectations {
+            assert_eq!(
+                read_pixel_rgba(&pixel_buffer, 64, x, y),
+                expected,
+                "{frame} at ({x}, {y})"
+            );
+        }
+    };
+    let red = [255, 0, 0, 255];
+    let transparent = [0, 0, 0, 0];
+
+    queue_layer_card(&mut renderer, 8.0, blue());
+    let first_position = [
+        (10, 10, red),
+        (24, 24, [0, 0, 255, 255]),
+        (40, 40, transparent),
+    ];
+    assert_frame(&mut renderer, &first_position, "rasterized");
+    queue_layer_card(&mut renderer, 8.0, blue());
+    assert_frame(&mut renderer, &first_position, "reused");
+
+    queue_layer_card(&mut renderer, 24.0, blue());
+    assert_frame(
+        &mut renderer,
+        &[
+            (10, 10, transparent),
+            (22, 12, transparent),
+            (26, 12, red),
+            (46, 12, red),
+            (40, 24, [0, 0, 255, 255]),
+        ],
+        "shifted",
+    );
+
+    queue_layer_card(
+        &mut renderer,
+        24.0,
+        grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgb(0, 255, 0)),
+    );
+    assert_frame(
+        &mut renderer,
+        &[(26, 12, red), (40, 24, [0, 255, 0, 255])],
+        "recolored",
+    );
+}
+
+#[cfg(feature = "render_metrics")]
+#[test]
+fn cached_layer_is_redrawn_only_when_its_content_changes() {
+    let Some(mut renderer) = create_headless_renderer_with_size_and_scale((64, 64), 1.0) else {
+        return;
+    };
+    let texture_id = 9_801u64;
+    renderer
+        .texture_manager()
+        .allocate_texture_with_data(texture_id, (1, 1), &[0, 0, 255, 255]);
+    let textured = || {
+        grafo::ShapeDrawCommandOptions::new()
+            .color(grafo::Color::WHITE)
+            .background_texture(grafo::ShapeTextureOptions::new(texture_id))
+    };
+    let mut pixel_buffer = Vec::new();
+    let mut render = |renderer: &mut grafo::Renderer<'static>| {
+        renderer.render_to_buffer(&mut pixel_buffer);
+        let metrics = renderer.last_layer_cache_metrics();
+        (
+            metrics.hits,
+            metrics.shifted_hits,
+            metrics.misses,
+            metrics.collected,
+        )
+    };
+
+    queue_layer_card(&mut renderer, 8.0, textured());
+    assert_eq!(render(&mut renderer), (0, 0, 1, 0));
+    queue_layer_card(&mut renderer, 8.0, textured());
+    assert_eq!(render(&mut renderer), (1, 0, 0, 0), "rebuilt queue");
+    queue_layer_card(&mut renderer, 20.0, textured());
+    assert_eq!(render(&mut renderer), (0, 1, 0, 0), "moved by whole pixels");
+    queue_layer_card(&mut renderer, 20.5, textured());
+    assert_eq!(render(&mut renderer), (0, 0, 1, 0), "moved by a subpixel");
+
+    renderer
+        .texture_manager()
+        .load_data_into_texture(texture_id, (1, 1), &[0, 255, 0, 255])
+        .unwrap();
+    assert_eq!(
+        render(&mut renderer),
+        (0, 0, 1, 1),
+        "texture contents changed"
+    );
+    assert_eq!(render(&mut renderer), (1, 0, 0, 0), "same queue");
+
+    renderer.clear_draw_queue();
+    assert_eq!(render(&mut renderer), (0, 0, 0, 1), "hint dropped");
+}
+
+fn queue_async_readback_square(renderer: &mut grafo::Renderer<'static>, color: grafo::Color) {
+    renderer.clear_draw_queue();
+    renderer
+        .add_shape(
+            grafo::Shape::rect([(10.0, 10.0), (30.0, 30.0)], grafo::Stroke::default()),
+            None,
+            None,
+            grafo::ShapeDrawCommandOptions::new().color(color),
+        )
+        .unwrap();
+}
+
+/// Async readbacks can overlap: later frames are encoded while earlier ones are still in flight,
+/// and each handle yields its own frame, identical to the blocking readback.
+#[test]
+fn async_readbacks_overlap_and_match_blocking_readback() {
+    // 50 px rows are padded on the GPU side, so row unpadding is exercised too.
+    let Some(mut renderer) = create_headless_renderer_with_size_and_scale((50, 40), 1.0) else {
+        return;
+    };
+    let colors = [
+        grafo::Color::rgb(200, 50, 50),
+        grafo::Color::rgb(50, 200, 50),
+        grafo::Color::rgb(50, 50, 200),
+    ];
+
+    let mut expected_frames = Vec::new();
+    for color in colors {
+        queue_async_readback_square(&mut renderer, color);
+        let mut pixels = Vec::new();
+        renderer.render_to_buffer(&mut pixels);
+        assert_eq!(pixels.len(), 50 * 40 * 4);
+        expected_frames.push(pixels);
+    }
+
+    for _ in 0..2 {
+        let handles: Vec<grafo::ReadbackHandle> = colors
+            .iter()
+            .map(|color| {
+                queue_async_readback_square(&mut renderer, *color);
+                renderer.render_to_buffer_async()
+            })
+            .collect();
+        let mut handles = handles.into_iter();
+
+        let first = handles.next().unwrap();
+        assert_eq!(first.size(), (50, 40));
+        let mut pixels = Vec::new();
+        first.read_into(&mut pixels).unwrap();
+        assert_eq!(pixels, expected_frames[0]);
+
+        let second = handles.next().unwrap();
+        renderer.poll_readbacks();
+        assert_eq!(block_on(second).unwrap(), expected_frames[1]);
+
+        // Dropping an unread handle cancels it and returns its buffer for reuse.
+        drop(handles.next());
+    }
+
+    queue_async_readback_square(&mut renderer, colors[0]);
+    let mut argb_pixels = vec![0u32; 50 * 40];
+    renderer.render_to_argb32(&mut argb_pixels);
+    queue_async_readback_square(&mut renderer, colors[0]);
+    let mut async_argb_pixels = vec![0u32; 50 * 40];
+    renderer
+        .render_to_argb32_async()
+        .read_argb32_into(&mut async_argb_pixels)
+        .unwrap();
+    assert_eq!(async_argb_pixels, argb_pixels);
+    assert_eq!(argb_pixels[20 * 50 + 20], 0xffc8_3232);
+}
+
+/// Fallible readback reports bad regions and short outputs instead of returning nothing, and
+/// converts a selected region into the requested layout.
+#[test]
+fn try_render_to_buffer_reads_regions_in_requested_formats() {
+    let Some(mut renderer) = create_headless_renderer_with_size_and_scale((50, 40), 1.0) else {
+        return;
+    };
+    let red = grafo::Color::rgb(200, 50, 50);
+
+    queue_async_readback_square(&mut renderer, red);
+    let mut pixels = Vec::new();
+    let out_of_bounds = grafo::ReadbackOptions::new().region((40, 30), (20, 5));
+    assert!(matches!(
+        renderer.try_render_to_buffer(out_of_bounds, &mut pixels),
+        Err(grafo::ReadbackError::RegionOutOfBounds {
+            frame_size: (50, 40),
+            ..
+        })
+    ));
+    assert!(matches!(
+        renderer.try_render_to_argb32(&mut [0u32; 10]),
+        Err(grafo::ReadbackError::OutputTooSmall {
+            required: 2000,
+            actual: 10
+        })
+    ));
+
+    // The rejected calls left the queued square in place.
+    renderer
+        .try_render_to_buffer(grafo::ReadbackOptions::new(), &mut pixels)
+        .unwrap();
+    assert_eq!(pixels.len(), 50 * 40 * 4);
+    assert_eq!(&pixels[(20 * 50 + 20) * 4..][..4], &[200, 50, 50, 255]);
+    assert_eq!(&pixels[(5 * 50 + 5) * 4..][..4], &[0, 0, 0, 0]);
+    let full_frame = pixels.clone();
+
+    let region = grafo::ReadbackOptions::new()
+        .region((5, 15), (20, 3))
+        .format(grafo::ReadbackFormat::Bgra8)
+        .alpha(grafo::ReadbackAlpha::Straight);
+    queue_async_readback_square(&mut renderer, red);
+    renderer.try_render_to_buffer(region, &mut pixels).unwrap();
+    assert_eq!(pixels.len(), 20 * 3 * 4);
+    for row in 0..3 {
+        for column in 0..20 {
+            let frame_pixel = &full_frame[((15 + row) * 50 + 5 + column) * 4..][..4];
+            let region_pixel = &pixels[(row * 20 + column) * 4..][..4];
+            if frame_pixel[3] == 255 || frame_pixel[3] == 0 {
+                let [r, g, b, a] = [
+                    frame_pixel[0],
+                    frame_pixel[1],
+                    frame_pixel[2],
+                    frame_pixel[3],
+                ];
+                assert_eq!(region_pixel, &[b, g, r, a]);
+            }
+        }
+    }
+
+    let linear = grafo::ReadbackOptions::new()
+        .region((20, 20), (1, 1))
+        .format(grafo::ReadbackFormat::RgbaF32Linear);
+    queue_async_readback_square(&mut renderer, red);
+    renderer.try_render_to_buffer(linear, &mut pixels).unwrap();
+    let channels: Vec<f32> = pixels
+        .chunks_exact(4)
+        .map(|channel| f32::from_ne_bytes(channel.try_into().unwrap()))
+        .collect();
+    assert_eq!(channels.len(), 4);
+    assert!((channels[0] - 0.578).abs() < 0.01, "{channels:?}");
+    assert!((channels[1] - 0.032).abs() < 0.01, "{channels:?}");
+    assert_eq!(channels[3], 1.0);
+}
+
+/// PNG export writes straight alpha and records the scale factor in the pHYs chunk.
+#[cfg(feature = "png")]
+#[test]
+fn render_to_png_writes_straight_alpha_and_scale_factor_dpi() {
+    let Some(mut renderer) = create_headless_renderer_with_size_and_scale((50, 40), 2.0) else {
+        return;
+    };
+    let queue_translucent_square = |renderer: &mut grafo::Renderer<'static>| {
+        renderer.clear_draw_queue();
+        renderer
+            .add_shape(
+                grafo::Shape::rect([(5.0, 5.0), (15.0, 15.0)], grafo::Stroke::default()),
+                None,
+                None,
+                grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgba(200, 50, 50, 128)),
+            )
+            .unwrap();
+    };
+
+    queue_translucent_square(&mut renderer);
+    let mut png_bytes = Vec::new();
+    renderer.render_to_png(&mut png_bytes).unwrap();
+
+    let decoded = image::load_from_memory(&png_bytes).unwrap().into_rgba8();
+    assert_eq!(decoded.dimensions(), (50, 40));
+    let [r, g, b, a] = decoded.get_pixel(20, 20).0;
+    assert!(
+        r.abs_diff(200) <= 2 && g.abs_diff(50) <= 2 && b.abs_diff(50) <= 2 && a.abs_diff(128) <= 1,
+        "expected straight-alpha red, got {:?}",
+        [r, g, b, a]
+    );
+    assert_eq!(decoded.get_pixel(2, 2).0, [0, 0, 0, 0]);
+
+    // pHYs: length, tag, x and y pixels per meter, unit (1 = meter). 2.0 scale is 192 DPI.
+    let phys = png_bytes
+        .windows(4)
+        .position(|window| window == b"pHYs")
+        .expect("pHYs chunk");
+    let pixels_per_meter = 7559u32.to_be_bytes();
+    assert_eq!(&png_bytes[phys + 4..phys + 8], &pixels_per_meter);
+    assert_eq!(&png_bytes[phys + 8..phys + 12], &pixels_per_meter);
+    assert_eq!(png_bytes[phys + 12], 1);
+
+    let path = std::env::temp_dir().join(format!("grafo_save_frame_{}.png", std::process::id()));
+    queue_translucent_square(&mut renderer);
+    renderer.save_frame(&path).unwrap();
+    let saved = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(saved, png_bytes);
+}
+
+/// Gaussian blur along both axes that derives its texel size from the input texture, so it
+/// blurs by the same physical distance whatever the canvas size.
+const TILED_EXPORT_BLUR: &str = r#"
+@fragment
+fn effect_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    let texel = 1.0 / vec2<f32>(textureDimensions(t_input));
+    var color = vec4<f32>(0.0);
+    var total_weight = 0.0;
+    for (var y = -4; y <= 4; y++) {
+        for (var x = -4; x <= 4; x++) {
+            let offset = vec2<f32>(f32(x), f32(y));
+            let weight = exp(-dot(offset, offset) / 8.0);
+            color += textureSample(t_input, s_input, uv + offset * texel) * weight;
+            total_weight += weight;
+        }
+    }
+    return color / total_weight;
+}
+"#;
+
+fn queue_tiled_export_scene(renderer: &mut grafo::Renderer<'static>) {
+    const BLUR_EFFECT_ID: u64 = 1;
+    renderer.clear_draw_queue();
+    renderer
+        .load_effect(BLUR_EFFECT_ID, &[TILED_EXPORT_BLUR])
+        .unwrap();
+    let root = renderer
+        .add_shape(
+            grafo::Shape::rect([(0.0, 0.0), (80.0, 60.0)], grafo::Stroke::default()),
+            None,
+            None,
+            grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgb(240, 240, 240)),
+        )
+        .unwrap();
+    renderer
+        .add_shape(
+            grafo::Shape::rect([(-15.0, -6.0), (15.0, 6.0)], grafo::Stroke::default()),
+            Some(root),
+            None,
+            grafo::ShapeDrawCommandOptions::new()
+                .color(grafo::Color::rgb(30, 120, 200))
+                .transform(
+                    grafo::TransformInstance::rotation_z_deg(30.0)
+                        .then(&grafo::TransformInstance::translation(25.0, 20.0)),
+                ),
+        )
+        .unwrap();
+    let clip = renderer
+        .add_rounded_clipping_rect(
+            [(45.0, 5.0), (75.0, 30.0)],
+            grafo::BorderRadii::new(8.0),
+            Some(root),
+            None::<grafo::TransformInstance>,
+            true,
+        )
+        .unwrap();
+    renderer
+        .add_shape(
+            grafo::Shape::rect([(35.0, 15.0), (70.0, 40.0)], grafo::Stroke::default()),
+            Some(clip),
+            None,
+            grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgb(220, 60, 40)),
+        )
+        .unwrap();
+    let blurred = renderer
+        .add_shape(
+            grafo::Shape::rect([(8.0, 38.0), (40.0, 52.0)], grafo::Stroke::default()),
+            Some(root),
+            None,
+            grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgb(40, 160, 60)),
+        )
+        .unwrap();
+    renderer
+        .set_group_effect(blurred, BLUR_EFFECT_ID, &[])
+        .unwrap();
+    let panel = renderer
+        .add_shape(
+            grafo::Shape::rect([(50.0, 40.0), (74.0, 56.0)], grafo::Stroke::default()),
+            Some(root),
+            None,
+            grafo::ShapeDrawCommandOptions::new(),
+        )
+        .unwrap();
+    renderer
+        .set_shape_backdrop_effect(
+            panel,
+            BLUR_EFFECT_ID,
+            &[],
+            grafo::BackdropEffectConfig::new()
+                .capture_area(grafo::BackdropCaptureArea::ScreenRect([
+                    (10.0, 10.0),
+                    (34.0, 26.0),
+                ]))
+                .downsample(1.0),
+        )
+        .unwrap();
+}
+
+/// Tiles rendered with padding stitch into the same image as a single frame, with blurs and
+/// backdrop captures crossing the seams, and the renderer keeps its own canvas afterwards.
+#[test]
+fn tiled_render_matches_single_frame_across_seams() {
+    let scale_factor = 1.5;
+    let export_size = (120, 90);
+    let Some(mut renderer) = create_headless_renderer_with_size_and_scale((40, 30), 1.0) else {
+        return;
+    };
+    let Some(mut single_frame_renderer) =
+        create_headless_renderer_with_size_and_scale(export_size, scale_factor)
+    else {
+        return;
+    };
+    queue_tiled_export_scene(&mut single_frame_renderer);
+    let mut single_frame = Vec::new();
+    single_frame_renderer
+        .try_render_to_buffer(grafo::ReadbackOptions::new(), &mut single_frame)
+        .unwrap();
+
+    queue_tiled_export_scene(&mut renderer);
+    let invalid_tiles = grafo::TiledRenderOptions::new()
+        .tile_size(1)
+        .padding(u32::MAX / 2);
+    assert!(matches!(
+        renderer.render_tiled_to_buffer(export_size, scale_factor, invalid_tiles, &mut Vec::new()),
+        Err(grafo::ReadbackError::InvalidTileSize { .. })
+    ));
+
+    // 32 px tiles put seams through every shape; the blur reaches 4 px past them.
+    let options = grafo::TiledRenderOptions::new().tile_size(32).padding(8);
+    let mut tiled = Vec::new();
+    renderer
+        .render_tiled_to_buffer(export_size, scale_factor, options, &mut tiled)
+        .unwrap();
+    assert_eq!(tiled.len(), single_frame.len());
+    // Tile offsets are not exact in logical units at a 1.5 scale factor, so coverage may round
+    // differently by one step.
+    let max_difference = tiled
+        .iter()
+        .zip(&single_frame)
+        .map(|(tiled, single)| tiled.abs_diff(*single))
+        .max();
+    assert!(max_difference.is_some_and(|difference| difference <= 1));
+
+    let mut streamed = Vec::new();
+    renderer
+        .render_tiled_to_writer(export_size, scale_factor, options, &mut streamed)
+        .unwrap();
+    assert_eq!(streamed, tiled);
+
+    // The renderer's own canvas and transforms are untouched by the export.
+    assert_eq!(renderer.size(), (40, 30));
+    let mut own_frame = Vec::new();
+    renderer
+        .try_render_to_buffer(grafo::ReadbackOptions::new(), &mut own_frame)
+        .unwrap();
+    assert_eq!(&own_frame[(5 * 40 + 5) * 4..][..4], &[240, 240, 240, 255]);
+    let rotated_center = (20 * 40 + 25) * 4;
+    assert_eq!(&own_frame[rotated_center..][..4], &[30, 120, 200, 255]);
+}
+
+fn create_headless_renderer_with_config(
+    config: &grafo::HeadlessConfig,
+) -> Option<grafo::Renderer<'static>> {
+    match block_on(grafo::Renderer::try_new_headless_with_config(config)) {
+        Ok(r) => Some(r),
+        Err(grafo::RendererCreationError::AdapterNotAvailable(_)) => {
+            println!("Skipping test: no suitable GPU adapter available.");
+            None
+        }
+        Err(e) => panic!("Failed to create headless renderer: {e}"),
+    }
+}
+
+/// Every headless target format reads back the same linear colors, and opaque output reports
+/// alpha as an opaque window would show it.
+#[test]
+fn headless_formats_read_back_the_same_colors() {
+    let formats = [
+        wgpu::TextureFormat::Bgra8UnormSrgb,
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+        wgpu::TextureFormat::Bgra8Unorm,
+        wgpu::TextureFormat::Rgba8Unorm,
+        wgpu::TextureFormat::Rgba16Float,
+    ];
+    let linear_pixels = |renderer: &mut grafo::Renderer<'static>| {
+        renderer.clear_draw_queue();
+        renderer
+            .add_shape(
+                grafo::Shape::rect([(10.0, 10.0), (30.0, 30.0)], grafo::Stroke::default()),
+                None,
+                None,
+                grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgba(200, 50, 50, 128)),
+            )
+            .unwrap();
+        let mut pixels = Vec::new();
+        let options = grafo::ReadbackOptions::new()
+            .format(grafo::ReadbackFormat::RgbaF32Linear)
+            .alpha(grafo::ReadbackAlpha::Straight);
+        renderer.try_render_to_buffer(options, &mut pixels).unwrap();
+        let pixel = |x: usize, y: usize| -> [f32; 4] {
+            std::array::from_fn(|channel| {
+                let offset = ((y * 50 + x) * 4 + channel) * 4;
+                f32::from_ne_bytes(pixels[offset..offset + 4].try_into().unwrap())
+            })
+        };
+        (pixel(20, 20), pixel(5, 5))
+    };
+
+    let Some(mut reference_renderer) =
+        create_headless_renderer_with_config(&grafo::HeadlessConfig::new((50, 40), 1.0))
+    else {
+        return;
+    };
+    let (reference_square, reference_background) = linear_pixels(&mut reference_renderer);
+    assert!((reference_square[3] - 128.0 / 255.0).abs() < 0.01);
+    assert_eq!(reference_background, [0.0; 4]);
+
+    for format in formats {
+        let config = grafo::HeadlessConfig::new((50, 40), 1.0).format(format);
+        let mut renderer = create_headless_renderer_with_config(&config).unwrap();
+        assert_eq!(renderer.target_format(), format);
+        let (square, background) = linear_pixels(&mut renderer);
+        for (channel, reference) in square.iter().zip(reference_square) {
+            assert!(
+                (channel - reference).abs() < 0.02,
+                "{format:?}: {square:?} != {reference_square:?}"
+            );
+        }
+        assert_eq!(background, [0.0; 4], "{format:?}");
+
+        let mut words = vec![0u32; 50 * 40];
+        renderer.try_render_to_argb32(&mut words).unwrap();
+        let mut bytes = Vec::new();
+        renderer
+            .try_render_to_buffer(grafo::ReadbackOptions::new(), &mut bytes)
+            .unwrap();
+        let [r, g, b, a] = bytes[(20 * 50 + 20) * 4..][..4] else {
+            unreachable!();
+        };
+        assert_eq!(
+            words[20 * 50 + 20],
+            u32::from_be_bytes([a, r, g, b]),
+            "{format:?}"
+        );
+        let mut async_bytes = Vec::new();
+        renderer
+            .render_to_buffer_async()
+            .read_into(&mut async_bytes)
+            .unwrap();
+        let mut native_bytes = Vec::new();
+        renderer.render_to_buffer(&mut native_bytes);
+        assert_eq!(async_bytes, native_bytes, "{format:?}");
+    }
+
+    let opaque = grafo::HeadlessConfig::new((50, 40), 1.0)
+        .format(wgpu::TextureFormat::Rgba16Float)
+        .transparent(false);
+    let mut renderer = create_headless_renderer_with_config(&opaque).unwrap();
+    let (square, background) = linear_pixels(&mut renderer);
+    assert_eq!(square[3], 1.0);
+    assert!((square[0] - reference_square[0] * reference_square[3]).abs() < 0.02);
+    assert_eq!(background, [0.0, 0.0, 0.0, 1.0]);
+    let mut words = vec![0u32; 50 * 40];
+    renderer.try_render_to_argb32(&mut words).unwrap();
+    assert_eq!(words[5 * 50 + 5], 0xff00_0000);
+
+    assert!(matches!(
+        grafo::Renderer::try_new_headless_with_context_and_config(
+            reference_renderer.context().clone(),
+            &grafo::HeadlessConfig::new((50, 40), 1.0).msaa_samples(2),
+        ),
+        Err(grafo::RendererCreationError::UnsupportedSampleCount(2))
+    ));
+    assert!(matches!(
+        grafo::Renderer::try_new_headless_with_context_and_config(
+            reference_renderer.context().clone(),
+            &grafo::HeadlessConfig::new((50, 40), 1.0).format(wgpu::TextureFormat::R8Unorm),
+        ),
+        Err(grafo::RendererCreationError::UnsupportedTargetFormat(
+            wgpu::TextureFormat::R8Unorm
+        ))
+    ));
+}
+
+/// Subtrees render into textures on targets that store linear bytes, and sample back unchanged.
+#[test]
+fn subtree_renders_into_texture_on_unorm_targets() {
+    for format in [
+        wgpu::TextureFormat::Rgba8Unorm,
+        wgpu::TextureFormat::Bgra8Unorm,
+    ] {
+        let config = grafo::HeadlessConfig::new((32, 32), 1.0).format(format);
+        let Some(mut renderer) = create_headless_renderer_with_config(&config) else {
+            return;
+        };
+        let canvas_id = renderer
+            .add_shape(
+                grafo::Shape::rect([(0.0, 0.0), (32.0, 32.0)], grafo::Stroke::default()),
+                None,
+                None,
+                grafo::ShapeDrawCommandOptions::new().color(grafo::Color::TRANSPARENT),
+            )
+            .unwrap();
+        let subtree_id = renderer
+            .add_shape(
+                grafo::Shape::rect([(0.0, 0.0), (16.0, 16.0)], grafo::Stroke::default()),
+                Some(canvas_id),
+                None,
+                grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgb(200, 50, 120)),
+            )
+            .unwrap();
+
+        let preview_id = 9_702u64;
+        // Twice, as a preview refreshed every frame would.
+        for _ in 0..2 {
+            renderer
+                .render_subtree_to_texture(subtree_id, preview_id, (16, 16), 1.0)
+                .unwrap();
+        }
+        add_textured_rect(
+            &mut renderer,
+            canvas_id,
+            [(16.0, 16.0), (32.0, 32.0)],
+            grafo::ShapeTextureOptions::new(preview_id),
+        );
+
+        let mut pixel_buffer = Vec::new();
+        renderer
+            .try_render_to_buffer(grafo::ReadbackOptions::new(), &mut pixel_buffer)
+            .unwrap();
+        let drawn = read_pixel_rgba(&pixel_buffer, 32, 8, 8);
+        let sampled = read_pixel_rgba(&pixel_buffer, 32, 24, 24);
+        assert_eq!(drawn[3], 255, "{format:?}");
+        for (drawn_channel, sampled_channel) in drawn.iter().zip(sampled) {
+            assert!(
+                drawn_channel.abs_diff(sampled_channel) <= 1,
+                "{format:?}: {drawn:?} != {sampled:?}"
+            );
+        }
+    }
+}
+
+/// A headless renderer configured with MSAA renders like one switched to MSAA afterwards.
+#[test]
+fn headless_msaa_config_matches_switching_sample_count() {
+    let config = grafo::HeadlessConfig::new((50, 40), 1.0).msaa_samples(4);
+    let Some(mut configured) = create_headless_renderer_with_config(&config) else {
+        return;
+    };
+    let mut switched =
+        grafo::Renderer::try_new_headless_with_context(configured.context().clone(), (50, 40), 1.0)
+            .unwrap();
+    switched.set_msaa_samples(4);
+
+    let mut frames = [Vec::new(), Vec::new()];
+    for (renderer, frame) in [&mut configured, &mut switched]
+        .into_iter()
+        .zip(&mut frames)
+    {
+        renderer.clear_draw_queue();
+        renderer
+            .add_shape(
+                grafo::Shape::rounded_rect(
+                    [(13.0, 8.0), (37.0, 32.0)],
+                    grafo::BorderRadii::new(12.0),
+                    grafo::Stroke::default(),
+                ),
+                None,
+                None,
+                grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgb(200, 50, 50)),
+            )
+            .unwrap();
+        renderer
+            .try_render_to_buffer(grafo::ReadbackOptions::new(), frame)
+            .unwrap();
+    }
+    assert_eq!(frames[0], frames[1]);
+}
+
+/// Regression test — OriginalSize texture fit uses physical pixels, not logical units.
+#[test]
+fn original_size_texture_fit_uses_physical_pixels_on_hidpi() {
+    let physical_size = (200, 200);
+    let scale_factor = 2.0;
+    let Some(mut renderer) =
+        create_headless_renderer_with_size_and_scale(physical_size, scale_factor)
+    else {
+        return;
+    };
+
+    let green_texture_id = 9_001u64;
+    let green_texture_with_transparent_border_20x20 = (0..20u32)
+        .flat_map(|y| {
+            (0..20u32).flat_map(move |x| {
+                if x == 0 || x == 19 || y == 0 || y == 19 {
+                    [0u8, 0u8, 0u8, 0u8]
+                } else {
+                    [0u8, 255u8, 0u8, 255u8]
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    renderer.texture_manager().allocate_texture_with_data(
+        green_texture_id,
+        (20, 20),
+        &green_texture_with_transparent_border_20x20,
+    );
+
+    let shape = grafo::Shape::rect([(10.0, 10.0), (70.0, 70.0)], grafo::Stroke::default());
+    renderer
+        .add_shape(
+            shape,
+            None,
+            None,
+            grafo::ShapeDrawCommandOptions::new()
+                .background_texture(
+                    grafo::ShapeTextureOptions::new(green_texture_id)
+                        .fit_mode(grafo::ShapeTextureFitMode::OriginalSize),
+                )
+                .color(grafo::Color::WHITE),
+        )
+        .unwrap();
+
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    let expectations = vec![
+        grafo_test_scenes::PixelExpectation::opaque(
+            30,
+            30,
+            0,
+            255,
+            0,
+            "inside_20px_physical_texture_region",
+        ),
+        grafo_test_scenes::PixelExpectation::opaque(
+            60,
+            30,
+            255,
+            255,
+            255,
+            "outside_texture_region_inside_shape",
+        ),
+        grafo_test_scenes::PixelExpectation::transparent(5, 5, "outside_shape"),
+    ];
+
+    let failures = check_pixels(
+        &pixel_buffer,
+        physical_size.0,
+        physical_size.1,
+        &expectations,
+    );
+    if !failures.is_empty() {
+        let message = format!(
+            "{} pixel expectation(s) failed:\n{}",
+            failures.len(),
+            failures.join("\n"),
+        );
+        panic!("{message}");
+    }
+}
+
+/// Regression test — scissor-only clipping rect clips children without drawing itself.
+#[test]
+fn clipping_rect_clips_child_without_visible_surface() {
+    let Some(mut renderer) = create_headless_renderer() else {
+        return;
+    };
+
+    let clip_rect_id = renderer
+        .add_clipping_rect(
+            [(20.0, 20.0), (80.0, 80.0)],
+            None,
+            None::<grafo::TransformInstance>,
+            true,
+        )
+        .unwrap();
+    let child = grafo::Shape::rect([(0.0, 0.0), (100.0, 100.0)], grafo::Stroke::default());
+    renderer
+        .add_shape(
+            child,
+            Some(clip_rect_id),
+            None,
+            grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgb(200, 50, 50)),
+        )
+        .unwrap();
+
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    let expectations = vec![
+        grafo_test_scenes::PixelExpectation::opaque(50, 50, 200, 50, 50, "inside_clip_rect"),
+        grafo_test_scenes::PixelExpectation::transparent(10, 50, "left_of_clip_rect"),
+        grafo_test_scenes::PixelExpectation::transparent(50, 10, "above_clip_rect"),
+        grafo_test_scenes::PixelExpectation::transparent(90, 50, "right_of_clip_rect"),
+        grafo_test_scenes::PixelExpectation::transparent(50, 90, "below_clip_rect"),
+    ];
+
+    assert_pixels_match(&pixel_buffer, &expectations);
+}
+
+fn render_rounded_clip_with_child(soft_clip: bool) -> Option<Vec<u8>> {
+    let mut renderer = create_headless_renderer_with_size_and_scale((64, 64), 1.0)?;
+    let card_id = renderer
+        .add_shape(
+            grafo::Shape::rounded_rect(
+                [(8.0, 8.0), (56.0, 56.0)],
+                grafo::BorderRadii::new(20.0),
+                grafo::Stroke::default(),
+            ),
+            None,
+            None,
+            grafo::ShapeDrawCommandOptions::new().soft_clip(soft_clip),
+        )
+        .unwrap();
+    renderer
+        .add_shape(
+            grafo::Shape::rect([(0.0, 0.0), (64.0, 64.0)], grafo::Stroke::default()),
+            Some(card_id),
+            None,
+            grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgb(200, 50, 50)),
+        )
+        .unwrap();
+
+    let mut pixel_buffer = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+    Some(pixel_buffer)
+}
+
+fn count_partially_covered_pixels(pixel_buffer: &[u8]) -> usize {
+    pixel_buffer
+        .chunks_exact(4)
+        .filter(|pixel| pixel[3] > 0 && pixel[3] < 255)
+        .count()
+}
+
+#[test]
+fn soft_clip_antialiases_children_at_rounded_corners() {
+    let Some(hard_clip_pixels) = render_rounded_clip_with_child(false) else {
+        return;
+    };
+    let Some(soft_clip_pixels) = render_rounded_clip_with_child(true) else {
+        return;
+    };
+
+    assert_eq!(count_partially_covered_pixels(&hard_clip_pixels), 0);
+    assert!(count_partially_covered_pixels(&soft_clip_pixels) > 0);
+    assert_eq!(
+        read_pixel_rgba(&soft_clip_pixels, 64, 32, 32),
+        [200, 50, 50, 255]
+    );
+    assert_eq!(read_pixel_rgba(&soft_clip_pixels, 64, 10, 10)[3], 0);
+    assert_eq!(read_pixel_rgba(&soft_clip_pixels, 64, 60, 32)[3], 0);
+}
+
+#[test]
+fn stencil_clips_deeper_than_stencil_range_fall_back_to_bounds() {
+    let Some(mut renderer) = create_headless_renderer_with_size_and_scale((64, 64), 1.0) else {
+        return;
+    };
+    renderer.load_shape(
+        grafo::Shape::rounded_rect(
+            [(4.0, 4.0), (60.0, 60.0)],
+            grafo::BorderRadii::new(8.0),
+            grafo::Stroke::default(),
+        ),
+        9_201,
+        Some(9_202),
+    );
+
+    let mut chain_ids = Vec::new();
+    for _ in 0..300 {
+        let parent_id = chain_ids.last().copied();
+        chain_ids.push(
+            renderer
+                .add_cached_shape_to_the_render_queue(
+                    9_201,
+                    parent_id,
+                    grafo::ShapeDrawCommandOptions::new(),
+                )
+                .unwrap(),
+        );
+    }
+    renderer
+        .add_shape(
+            grafo::Shape::rect([(0.0, 0.0), (32.0, 64.0)], grafo::Stroke::default()),
+            chain_ids.last().copied(),
+            None,
+            grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgb(200, 50, 50)),
+        )
+        .unwrap();
+    // Drawn after the deepest subtree unwinds, so it needs the stencil restored exactly.
+    renderer
+        .add_shape(
+            grafo::Shape::rect([(32.0, 0.0), (64.0, 64.0)], grafo::Stroke::default()),
+            Some(chain_ids[259]),
+            None,
+            grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgb(50, 50, 200)),
+        )
+        .unwrap();
+
+    let mut pixel_buffer = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    assert_eq!(
+        read_pixel_rgba(&pixel_buffer, 64, 16, 32),
+        [200, 50, 50, 255]
+    );
+    assert_eq!(
+        read_pixel_rgba(&pixel_buffer, 64, 48, 32),
+        [50, 50, 200, 255]
+    );
+    assert_eq!(read_pixel_rgba(&pixel_buffer, 64, 1, 32)[3], 0);
+    assert_eq!(read_pixel_rgba(&pixel_buffer, 64, 5, 5)[3], 0);
+    #[cfg(feature = "render_metrics")]
+    assert_eq!(
+        renderer
+            .last_pipeline_switch_counts()
+            .stencil_overflow_fallbacks,
+        300 - u8::MAX as u32
+    );
+}
+
+/// Regression test — partially offscreen backdrop captures clear untouched pooled pixels.
+#[test]
+fn partially_offscreen_backdrop_capture_clears_reused_texture_space() {
+    let physical_size = (100, 80);
+    let Some(mut renderer) = create_headless_renderer_with_size_and_scale(physical_size, 1.0)
+    else {
+        return;
+    };
+
+    const AVERAGE_WITH_RIGHT_NEIGHBOR_EFFECT_ID: u64 = 9_101;
+    const AVERAGE_WITH_RIGHT_NEIGHBOR_WGSL: &str = r#"
+const LOOKAHEAD_UV: vec2<f32> = vec2<f32>(0.4, 0.0);
+
+@fragment
+fn effect_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    let base = textureSample(t_input, s_input, uv);
+    let lookahead = textureSample(t_input, s_input, uv + LOOKAHEAD_UV);
+    return 0.5 * (base + lookahead);
+}
+"#;
+
+    renderer
+        .load_effect(
+            AVERAGE_WITH_RIGHT_NEIGHBOR_EFFECT_ID,
+            &[AVERAGE_WITH_RIGHT_NEIGHBOR_WGSL],
+        )
+        .expect("Failed to compile deterministic backdrop test effect");
+
+    let seeded_blue_panel =
+        grafo::Shape::rect([(20.0, 20.0), (60.0, 60.0)], grafo::Stroke::default());
+    renderer
+        .add_shape(
+            seeded_blue_panel.clone(),
+            None,
+            None,
+            grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgb(40, 40, 220)),
+        )
+        .unwrap();
+    let seeded_blue_panel_id = renderer
+        .add_shape(
+            seeded_blue_panel,
+            None,
+            None,
+            grafo::ShapeDrawCommandOptions::new(),
+        )
+        .unwrap();
+    renderer
+        .set_shape_backdrop_effect(
+            seeded_blue_panel_id,
+            AVERAGE_WITH_RIGHT_NEIGHBOR_EFFECT_ID,
+            &[],
+            grafo::BackdropEffectConfig::new().capture_area(
+                grafo::BackdropCaptureArea::ScreenRect([(20.0, 20.0), (60.0, 60.0)]),
+            ),
+        )
+        .unwrap();
+
+    let mut seeded_frame: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut seeded_frame);
+
+    renderer.clear_draw_queue();
+
+    let visible_red_source =
+        grafo::Shape::rect([(70.0, 20.0), (100.0, 60.0)], grafo::Stroke::default());
+    renderer
+        .add_shape(
+            visible_red_source,
+            None,
+            None,
+            grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgb(220, 40, 40)),
+        )
+        .unwrap();
+
+    let partially_offscreen_panel =
+        grafo::Shape::rect([(70.0, 20.0), (100.0, 60.0)], grafo::Stroke::default());
+    let partially_offscreen_panel_id = renderer
+        .add_shape(
+            partially_offscreen_panel,
+            None,
+            None,
+            grafo::ShapeDrawCommandOptions::new(),
+        )
+        .unwrap();
+    renderer
+        .set_shape_backdrop_effect(
+            partially_offscreen_panel_id,
+            AVERAGE_WITH_RIGHT_NEIGHBOR_EFFECT_ID,
+            &[],
+            grafo::BackdropEffectConfig::new().capture_area(
+                grafo::BackdropCaptureArea::ScreenRect([(70.0, 20.0), (110.0, 60.0)]),
+            ),
+        )
+        .unwrap();
+
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    let sampled_pixel = read_pixel_rgba(&pixel_buffer, physical_size.0, 86, 40);
+    assert!(
+        sampled_pixel[0] > 80,
+        "expected visible red contribution after clearing untouched capture space, got {:?}",
+        sampled_pixel
+    );
+    assert!(
+        sampled_pixel[2] <= 50,
+        "expected offscreen capture space to stay transparent instead of leaking recycled blue, got {:?}",
+        sampled_pixel
+    );
+    assert!(
+        sampled_pixel[3] > 240,
+        "expected the cleared backdrop sample to composite back over the visible red source, got {:?}",
+        sampled_pixel
+    );
+}
+
+const SWAP_RED_AND_BLUE_EFFECT: &str = r#"
+@fragment
+fn effect_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    let color = textureSample(t_input, s_input, uv);
+    return vec4<f32>(color.b, color.g, color.r, color.a);
+}
+"#;
+
+#[test]
+fn clipping_rect_accepts_group_effect_for_its_subtree() {
+    let Some(mut renderer) = create_headless_renderer() else {
+        return;
+    };
+    renderer
+        .load_effect(9_301, &[SWAP_RED_AND_BLUE_EFFECT])
+        .expect("to load the group effect");
+
+    let clip_rect_id = renderer
+        .add_clipping_rect(
+            [(20.0, 20.0), (80.0, 80.0)],
+            None,
+            None::<grafo::TransformInstance>,
+            true,
+        )
+        .unwrap();
+    renderer
+        .add_shape(
+            grafo::Shape::rect([(0.0, 40.0), (100.0, 60.0)], grafo::Stroke::default()),
+            Some(clip_rect_id),
+            None,
+            grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgb(200, 50, 50)),
+        )
+        .unwrap();
+    renderer
+        .set_group_effect(clip_rect_id, 9_301, &[])
+        .expect("clip rects should accept group effects");
+
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    let expectations = vec![
+        grafo_test_scenes::PixelExpectation::opaque(50, 50, 50, 50, 200, "processed_child"),
+        grafo_test_scenes::PixelExpectation::transparent(10, 50, "child_clipped_left"),
+        grafo_test_scenes::PixelExpectation::transparent(90, 50, "child_clipped_right"),
+        grafo_test_scenes::PixelExpectation::transparent(50, 30, "empty_clip_area"),
+    ];
+    assert_pixels_match(&pixel_buffer, &expectations);
+}
+
+#[test]
+fn clipping_rect_backdrop_effect_captures_rect_bounds_and_keeps_children() {
+    let Some(mut renderer) = create_headless_renderer() else {
+        return;
+    };
+    renderer
+        .load_effect(9_311, &[SWAP_RED_AND_BLUE_EFFECT])
+        .expect("to load the backdrop effect");
+
+    renderer
+        .add_shape(
+            grafo::Shape::rect([(0.0, 0.0), (100.0, 100.0)], grafo::Stroke::default()),
+            None,
+            None,
+            grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgb(200, 50, 50)),
+        )
+        .unwrap();
+    let clip_rect_id = renderer
+        .add_clipping_rect(
+            [(20.0, 20.0), (80.0, 80.0)],
+            None,
+            None::<grafo::TransformInstance>,
+            true,
+        )
+        .unwrap();
     renderer
         .add_shape(
-            shape,
-            None,
+            grafo::Shape::rect([(40.0, 40.0), (100.0, 60.0)], grafo::Stroke::default()),
+            Some(clip_rect_id),
             None,
-            grafo::ShapeDrawCommandOptions::new()
-                .background_texture(
-                    grafo::ShapeTextureOptions::new(green_texture_id)
-                        .fit_mode(grafo::ShapeTextureFitMode::OriginalSize),
-                )
-                .color(grafo::Color::WHITE),
+            grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgb(50, 200, 50)),
         )
         .unwrap();
+    renderer
+        .set_shape_backdrop_effect(
+            clip_rect_id,
+            9_311,
+            &[],
+            grafo::BackdropEffectConfig::default(),
+        )
+        .expect("clip rects should accept backdrop effects");
 
     let mut pixel_buffer: Vec<u8> = Vec::new();
     renderer.render_to_buffer(&mut pixel_buffer);
 
     let expectations = vec![
-        grafo_test_scenes::PixelExpectation::opaque(
-            30,
-            30,
-            0,
-            255,
-            0,
-            "inside_20px_physical_texture_region",
-        ),
-        grafo_test_scenes::PixelExpectation::opaque(
-            60,
-            30,
-            255,
-            255,
-            255,
-            "outside_texture_region_inside_shape",
-        ),
-        grafo_test_scenes::PixelExpectation::transparent(5, 5, "outside_shape"),
+        grafo_test_scenes::PixelExpectation::opaque(30, 30, 50, 50, 200, "processed_backdrop"),
+        grafo_test_scenes::PixelExpectation::opaque(10, 10, 200, 50, 50, "outside_clip_rect"),
+        grafo_test_scenes::PixelExpectation::opaque(50, 50, 50, 200, 50, "child_over_backdrop"),
+        grafo_test_scenes::PixelExpectation::opaque(90, 50, 200, 50, 50, "child_clipped"),
     ];
-
-    let failures = check_pixels(
-        &pixel_buffer,
-        physical_size.0,
-        physical_size.1,
-        &expectations,
-    );
-    if !failures.is_empty() {
-        let message = format!(
-            "{} pixel expectation(s) failed:\n{}",
-            failures.len(),
-            failures.join("\n"),
-        );
-        panic!("{message}");
-    }
+    assert_pixels_match(&pixel_buffer, &expectations);
 }
 
-/// Regression test — scissor-only clipping rect clips children without drawing itself.
 #[test]
-fn clipping_rect_clips_child_without_visible_surface() {
+fn clipping_rect_accepts_shape_effect_from_rect_coverage() {
     let Some(mut renderer) = create_headless_renderer() else {
         return;
     };
+    renderer
+        .load_effect(9_321, &[CACHED_SHAPE_EFFECT_BLUE_DROP])
+        .expect("to load the shape effect");
 
     let clip_rect_id = renderer
         .add_clipping_rect(
@@ -694,143 +3462,231 @@ fn clipping_rect_clips_child_without_visible_surface() {
             true,
         )
         .unwrap();
-    let child = grafo::Shape::rect([(0.0, 0.0), (100.0, 100.0)], grafo::Stroke::default());
     renderer
+        .set_shape_effect(
+            clip_rect_id,
+            9_321,
+            &[],
+            grafo::ShapeEffectConfig::new().outset(12.0),
+        )
+        .expect("clip rects should accept shape effects");
+
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    let expectations = vec![
+        grafo_test_scenes::PixelExpectation::opaque(50, 50, 0, 0, 255, "inside_rect"),
+        grafo_test_scenes::PixelExpectation::opaque(85, 85, 0, 0, 255, "offset_drop"),
+        grafo_test_scenes::PixelExpectation::transparent(24, 24, "uncovered_after_offset"),
+        grafo_test_scenes::PixelExpectation::transparent(10, 10, "outside_rect"),
+    ];
+    assert_pixels_match(&pixel_buffer, &expectations);
+}
+
+/// Adds a horizontal and a vertical red bar through the canvas center under `parent_id`.
+fn add_crossing_bars(renderer: &mut grafo::Renderer<'static>, parent_id: usize) {
+    for bar in [[(0.0, 45.0), (100.0, 55.0)], [(45.0, 0.0), (55.0, 100.0)]] {
+        renderer
+            .add_shape(
+                grafo::Shape::rect(bar, grafo::Stroke::default()),
+                Some(parent_id),
+                None,
+                grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgb(200, 50, 50)),
+            )
+            .unwrap();
+    }
+}
+
+#[test]
+fn clip_x_overflow_clips_horizontally_and_lets_children_overflow_vertically() {
+    let Some(mut renderer) = create_headless_renderer() else {
+        return;
+    };
+
+    let parent_id = renderer
         .add_shape(
-            child,
-            Some(clip_rect_id),
+            grafo::Shape::rect([(20.0, 20.0), (80.0, 80.0)], grafo::Stroke::default()),
             None,
-            grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgb(200, 50, 50)),
+            None,
+            grafo::ShapeDrawCommandOptions::new().overflow(grafo::ShapeOverflow::ClipX),
         )
         .unwrap();
+    add_crossing_bars(&mut renderer, parent_id);
 
     let mut pixel_buffer: Vec<u8> = Vec::new();
     renderer.render_to_buffer(&mut pixel_buffer);
 
     let expectations = vec![
-        grafo_test_scenes::PixelExpectation::opaque(50, 50, 200, 50, 50, "inside_clip_rect"),
-        grafo_test_scenes::PixelExpectation::transparent(10, 50, "left_of_clip_rect"),
-        grafo_test_scenes::PixelExpectation::transparent(50, 10, "above_clip_rect"),
-        grafo_test_scenes::PixelExpectation::transparent(90, 50, "right_of_clip_rect"),
-        grafo_test_scenes::PixelExpectation::transparent(50, 90, "below_clip_rect"),
+        grafo_test_scenes::PixelExpectation::opaque(50, 50, 200, 50, 50, "center"),
+        grafo_test_scenes::PixelExpectation::opaque(50, 5, 200, 50, 50, "overflow_above"),
+        grafo_test_scenes::PixelExpectation::opaque(50, 95, 200, 50, 50, "overflow_below"),
+        grafo_test_scenes::PixelExpectation::transparent(10, 50, "clipped_left"),
+        grafo_test_scenes::PixelExpectation::transparent(90, 50, "clipped_right"),
     ];
-
     assert_pixels_match(&pixel_buffer, &expectations);
 }
 
-/// Regression test — partially offscreen backdrop captures clear untouched pooled pixels.
 #[test]
-fn partially_offscreen_backdrop_capture_clears_reused_texture_space() {
-    let physical_size = (100, 80);
-    let Some(mut renderer) = create_headless_renderer_with_size_and_scale(physical_size, 1.0)
-    else {
+fn clip_y_overflow_on_rotated_parent_clips_through_stencil_band() {
+    let Some(mut renderer) = create_headless_renderer() else {
         return;
     };
 
-    const AVERAGE_WITH_RIGHT_NEIGHBOR_EFFECT_ID: u64 = 9_101;
-    const AVERAGE_WITH_RIGHT_NEIGHBOR_WGSL: &str = r#"
-const LOOKAHEAD_UV: vec2<f32> = vec2<f32>(0.4, 0.0);
+    // Rotating by 90 degrees turns the parent's local y-band into a vertical screen band
+    // covering x in 40..60.
+    let transform = grafo::TransformInstance::rotation_z_deg(90.0)
+        .then(&grafo::TransformInstance::translation(50.0, 50.0));
+    let parent_id = renderer
+        .add_shape(
+            grafo::Shape::rect([(-30.0, -10.0), (30.0, 10.0)], grafo::Stroke::default()),
+            None,
+            None,
+            grafo::ShapeDrawCommandOptions::new()
+                .transform(transform)
+                .overflow(grafo::ShapeOverflow::ClipY),
+        )
+        .unwrap();
+    add_crossing_bars(&mut renderer, parent_id);
 
-@fragment
-fn effect_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
-    let base = textureSample(t_input, s_input, uv);
-    let lookahead = textureSample(t_input, s_input, uv + LOOKAHEAD_UV);
-    return 0.5 * (base + lookahead);
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    let expectations = vec![
+        grafo_test_scenes::PixelExpectation::opaque(50, 50, 200, 50, 50, "center"),
+        grafo_test_scenes::PixelExpectation::opaque(50, 5, 200, 50, 50, "overflow_above"),
+        grafo_test_scenes::PixelExpectation::opaque(50, 95, 200, 50, 50, "overflow_below"),
+        grafo_test_scenes::PixelExpectation::transparent(30, 50, "clipped_left"),
+        grafo_test_scenes::PixelExpectation::transparent(70, 50, "clipped_right"),
+    ];
+    assert_pixels_match(&pixel_buffer, &expectations);
 }
-"#;
 
-    renderer
-        .load_effect(
-            AVERAGE_WITH_RIGHT_NEIGHBOR_EFFECT_ID,
-            &[AVERAGE_WITH_RIGHT_NEIGHBOR_WGSL],
-        )
-        .expect("Failed to compile deterministic backdrop test effect");
+#[test]
+fn clip_y_overflow_band_covers_the_viewport_under_a_tiny_scale() {
+    let Some(mut renderer) = create_headless_renderer() else {
+        return;
+    };
 
-    let seeded_blue_panel =
-        grafo::Shape::rect([(20.0, 20.0), (60.0, 60.0)], grafo::Stroke::default());
-    renderer
+    // The same screen-space parent as the rotated test above, reached through a 1/10_000
+    // scale, so the band has to span millions of local units to reach the canvas edges.
+    let transform = grafo::TransformInstance::scale(0.0001, 0.0001)
+        .then(&grafo::TransformInstance::rotation_z_deg(90.0))
+        .then(&grafo::TransformInstance::translation(50.0, 50.0));
+    let parent_id = renderer
         .add_shape(
-            seeded_blue_panel.clone(),
+            grafo::Shape::rect(
+                [(-300_000.0, -100_000.0), (300_000.0, 100_000.0)],
+                grafo::Stroke::default(),
+            ),
             None,
             None,
-            grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgb(40, 40, 220)),
+            grafo::ShapeDrawCommandOptions::new()
+                .transform(transform)
+                .overflow(grafo::ShapeOverflow::ClipY),
         )
         .unwrap();
-    let seeded_blue_panel_id = renderer
+    add_crossing_bars(&mut renderer, parent_id);
+
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    let expectations = vec![
+        grafo_test_scenes::PixelExpectation::opaque(50, 50, 200, 50, 50, "center"),
+        grafo_test_scenes::PixelExpectation::opaque(50, 2, 200, 50, 50, "overflow_above"),
+        grafo_test_scenes::PixelExpectation::opaque(50, 97, 200, 50, 50, "overflow_below"),
+        grafo_test_scenes::PixelExpectation::transparent(30, 50, "clipped_left"),
+        grafo_test_scenes::PixelExpectation::transparent(70, 50, "clipped_right"),
+    ];
+    assert_pixels_match(&pixel_buffer, &expectations);
+}
+
+#[test]
+fn clip_x_overflow_follows_the_geometry_of_non_rect_parents() {
+    let Some(mut renderer) = create_headless_renderer() else {
+        return;
+    };
+
+    // Two disjoint squares: children show through both columns but not the gap between them.
+    let mut builder = grafo::Shape::builder();
+    for (left, right) in [(20.0, 40.0), (60.0, 80.0)] {
+        builder = builder
+            .begin((left, 40.0))
+            .line_to((right, 40.0))
+            .line_to((right, 60.0))
+            .line_to((left, 60.0))
+            .close();
+    }
+    let parent_id = renderer
         .add_shape(
-            seeded_blue_panel,
+            builder.build(),
             None,
             None,
-            grafo::ShapeDrawCommandOptions::new(),
+            grafo::ShapeDrawCommandOptions::new().overflow(grafo::ShapeOverflow::ClipX),
         )
         .unwrap();
     renderer
-        .set_shape_backdrop_effect(
-            seeded_blue_panel_id,
-            AVERAGE_WITH_RIGHT_NEIGHBOR_EFFECT_ID,
-            &[],
-            grafo::BackdropEffectConfig::new().capture_area(
-                grafo::BackdropCaptureArea::ScreenRect([(20.0, 20.0), (60.0, 60.0)]),
-            ),
+        .add_shape(
+            grafo::Shape::rect([(0.0, 0.0), (100.0, 100.0)], grafo::Stroke::default()),
+            Some(parent_id),
+            None,
+            grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgb(200, 50, 50)),
         )
         .unwrap();
 
-    let mut seeded_frame: Vec<u8> = Vec::new();
-    renderer.render_to_buffer(&mut seeded_frame);
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
 
-    renderer.clear_draw_queue();
+    let expectations = vec![
+        grafo_test_scenes::PixelExpectation::opaque(30, 5, 200, 50, 50, "left_column_above"),
+        grafo_test_scenes::PixelExpectation::opaque(70, 95, 200, 50, 50, "right_column_below"),
+        grafo_test_scenes::PixelExpectation::transparent(50, 50, "gap"),
+        grafo_test_scenes::PixelExpectation::transparent(10, 50, "clipped_left"),
+        grafo_test_scenes::PixelExpectation::transparent(90, 50, "clipped_right"),
+    ];
+    assert_pixels_match(&pixel_buffer, &expectations);
+}
 
-    let visible_red_source =
-        grafo::Shape::rect([(70.0, 20.0), (100.0, 60.0)], grafo::Stroke::default());
+#[test]
+fn single_axis_overflow_applies_to_backdrop_effect_parents() {
+    let Some(mut renderer) = create_headless_renderer() else {
+        return;
+    };
     renderer
-        .add_shape(
-            visible_red_source,
-            None,
-            None,
-            grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgb(220, 40, 40)),
-        )
-        .unwrap();
+        .load_effect(9_401, &[CACHED_SHAPE_EFFECT_PASSTHROUGH])
+        .expect("to load the backdrop effect");
 
-    let partially_offscreen_panel =
-        grafo::Shape::rect([(70.0, 20.0), (100.0, 60.0)], grafo::Stroke::default());
-    let partially_offscreen_panel_id = renderer
+    let transform = grafo::TransformInstance::rotation_z_deg(90.0)
+        .then(&grafo::TransformInstance::translation(50.0, 50.0));
+    let parent_id = renderer
         .add_shape(
-            partially_offscreen_panel,
+            grafo::Shape::rect([(-30.0, -10.0), (30.0, 10.0)], grafo::Stroke::default()),
             None,
             None,
-            grafo::ShapeDrawCommandOptions::new(),
+            grafo::ShapeDrawCommandOptions::new()
+                .transform(transform)
+                .overflow(grafo::ShapeOverflow::ClipY),
         )
         .unwrap();
     renderer
         .set_shape_backdrop_effect(
-            partially_offscreen_panel_id,
-            AVERAGE_WITH_RIGHT_NEIGHBOR_EFFECT_ID,
+            parent_id,
+            9_401,
             &[],
-            grafo::BackdropEffectConfig::new().capture_area(
-                grafo::BackdropCaptureArea::ScreenRect([(70.0, 20.0), (110.0, 60.0)]),
-            ),
+            grafo::BackdropEffectConfig::default(),
         )
         .unwrap();
+    add_crossing_bars(&mut renderer, parent_id);
 
     let mut pixel_buffer: Vec<u8> = Vec::new();
     renderer.render_to_buffer(&mut pixel_buffer);
 
-    let sampled_pixel = read_pixel_rgba(&pixel_buffer, physical_size.0, 86, 40);
-    assert!(
-        sampled_pixel[0] > 80,
-        "expected visible red contribution after clearing untouched capture space, got {:?}",
-        sampled_pixel
-    );
-    assert!(
-        sampled_pixel[2] <= 50,
-        "expected offscreen capture space to stay transparent instead of leaking recycled blue, got {:?}",
-        sampled_pixel
-    );
-    assert!(
-        sampled_pixel[3] > 240,
-        "expected the cleared backdrop sample to composite back over the visible red source, got {:?}",
-        sampled_pixel
-    );
+    let expectations = vec![
+        grafo_test_scenes::PixelExpectation::opaque(50, 50, 200, 50, 50, "center"),
+        grafo_test_scenes::PixelExpectation::opaque(50, 5, 200, 50, 50, "overflow_above"),
+        grafo_test_scenes::PixelExpectation::transparent(30, 50, "clipped_left"),
+        grafo_test_scenes::PixelExpectation::transparent(70, 50, "clipped_right"),
+    ];
+    assert_pixels_match(&pixel_buffer, &expectations);
 }
 
 /// Regression test — standalone clipping rect is a no-op and does not enter shape drawing.
@@ -858,30 +3714,64 @@ fn standalone_clipping_rect_does_not_panic() {
     );
 }
 
-/// Regression test — unsupported clip-rect transforms are rejected instead of disabling clipping.
+/// Regression test — a rotated clip rect clips its children to the rotated rect through the
+/// stencil instead of to its axis-aligned bounds.
 #[test]
-fn clipping_rect_rejects_non_axis_aligned_transform() {
+fn clipping_rect_with_rotated_transform_clips_through_stencil() {
     let Some(mut renderer) = create_headless_renderer() else {
         return;
     };
 
     let clip_rect_id = renderer
         .add_clipping_rect(
-            [(20.0, 20.0), (80.0, 80.0)],
+            [(-20.0, -20.0), (20.0, 20.0)],
             None,
-            None::<grafo::TransformInstance>,
+            Some(
+                grafo::TransformInstance::rotation_z_deg(45.0)
+                    .then(&grafo::TransformInstance::translation(50.0, 50.0)),
+            ),
             true,
         )
         .unwrap();
-    assert!(matches!(
-        renderer.add_clipping_rect(
+
+    let child = grafo::Shape::rect([(0.0, 0.0), (100.0, 100.0)], grafo::Stroke::default());
+    renderer
+        .add_shape(
+            child,
+            Some(clip_rect_id),
+            None,
+            grafo::ShapeDrawCommandOptions::new().color(grafo::Color::rgb(200, 50, 50)),
+        )
+        .unwrap();
+
+    let mut pixel_buffer: Vec<u8> = Vec::new();
+    renderer.render_to_buffer(&mut pixel_buffer);
+
+    let expectations = vec![
+        grafo_test_scenes::PixelExpectation::opaque(50, 50, 200, 50, 50, "rotated_clip_center"),
+        grafo_test_scenes::PixelExpectation::opaque(50, 25, 200, 50, 50, "rotated_clip_top_tip"),
+        grafo_test_scenes::PixelExpectation::transparent(32, 32, "outside_rotated_clip_corner"),
+        grafo_test_scenes::PixelExpectation::transparent(50, 10, "above_rotated_clip"),
+    ];
+
+    assert_pixels_match(&pixel_buffer, &expectations);
+}
+
+#[test]
+fn rounded_clipping_rect_clips_child_corners() {
+    let Some(mut renderer) = create_headless_renderer() else {
+        return;
+    };
+
+    let clip_rect_id = renderer
+        .add_rounded_clipping_rect(
             [(20.0, 20.0), (80.0, 80.0)],
+            grafo::BorderRadii::new(20.0),
             None,
-            Some(grafo::TransformInstance::rotation_z_deg(45.0)),
+            None::<grafo::TransformInstance>,
             true,
-        ),
-        Err(grafo::DrawCommandError::UnsupportedClipRectTransform)
-    ));
+        )
+        .unwrap();
 
     let child = grafo::Shape::rect([(0.0, 0.0), (100.0, 100.0)], grafo::Stroke::default());
     renderer
@@ -897,15 +3787,15 @@ fn clipping_rect_rejects_non_axis_aligned_transform() {
     renderer.render_to_buffer(&mut pixel_buffer);
 
     let expectations = vec![
-        grafo_test_scenes::PixelExpectation::opaque(
-            50,
-            50,
-            200,
-            50,
-            50,
-            "inside_unrotated_clip_rect",
+        grafo_test_scenes::PixelExpectation::opaque(50, 50, 200, 50, 50, "rounded_clip_center"),
+        grafo_test_scenes::PixelExpectation::opaque(50, 21, 200, 50, 50, "rounded_clip_top_edge"),
+        grafo_test_scenes::PixelExpectation::transparent(22, 22, "rounded_clip_top_left_corner"),
+        grafo_test_scenes::PixelExpectation::transparent(
+            78,
+            78,
+            "rounded_clip_bottom_right_corner",
         ),
-        grafo_test_scenes::PixelExpectation::transparent(10, 50, "outside_unrotated_clip_rect"),
+        grafo_test_scenes::PixelExpectation::transparent(10, 50, "left_of_rounded_clip"),
     ];
 
     assert_pixels_match(&pixel_buffer, &expectations);